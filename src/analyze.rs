@@ -0,0 +1,111 @@
+use std::sync::RwLock;
+use std::time::Instant;
+
+use sqlparser::ast::{Ident, ObjectName};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::dialect::keywords::Keyword;
+use sqlparser::parser::{IsOptional, Parser, ParserError};
+use sqlparser::tokenizer::{Token, Tokenizer};
+
+use crate::commands::{normalize_identifier, split_object_name, CommandError};
+use crate::core::{analyze_column, ColumnId, Database, Table, HISTOGRAM_BUCKETS};
+
+// ANALYZE creates the histograms of columns, which are used to estimate the cardinalities of query plans
+//  the SQL parser only knows ANALYZE TABLE of Hive, so the PostgreSQL syntax is parsed here
+//  histograms are kept in memory only, i.e., tables need to be analyzed again after a restart
+
+#[derive(Debug)]
+pub struct AnalyzeTarget {
+    pub name: ObjectName,
+    // all columns if empty
+    pub columns: Vec<Ident>
+}
+
+#[derive(Debug)]
+pub struct AnalyzeStatement {
+    // all tables if empty
+    pub targets: Vec<AnalyzeTarget>
+}
+
+// parses an ANALYZE statement, None if the SQL is not an ANALYZE statement (and thus left to the SQL parser)
+//  ANALYZE [ table [ ( column [, ...] ) ] [, ...] ]
+pub fn parse_analyze(sql: &str) -> Result<Option<AnalyzeStatement>, ParserError> {
+    let dialect = GenericDialect {};
+    let tokens = Tokenizer::new(&dialect, sql).tokenize()?;
+    let mut parser = Parser::new(tokens, &dialect);
+    if !parser.parse_keyword(Keyword::ANALYZE) {
+        return Ok(None)
+    }
+    let mut targets = Vec::new();
+    if !matches!(parser.peek_token(), Token::EOF | Token::SemiColon) {
+        loop {
+            let name = parser.parse_object_name()?;
+            let columns = parser.parse_parenthesized_column_list(IsOptional::Optional)?;
+            targets.push(AnalyzeTarget { name, columns });
+            if !parser.consume_token(&Token::Comma) {
+                break
+            }
+        }
+    }
+    while parser.consume_token(&Token::SemiColon) {}
+    if parser.peek_token() != Token::EOF {
+        return Err(ParserError::ParserError(format!("Expected end of statement, found: {}", parser.peek_token())))
+    }
+    Ok(Some(AnalyzeStatement { targets }))
+}
+
+// creates the histograms of the given columns (all if empty)
+//  the histograms are built while only holding a read lock
+fn analyze_table(name: &str, table: &RwLock<Table>, columns: &[Ident]) -> Result<(), CommandError> {
+    let start = Instant::now();
+    let (histograms, row_count) = {
+        let table = table.read().unwrap();
+        let column_ids: Vec<ColumnId> = if columns.is_empty() {
+            (0..table.column_count()).collect()
+        } else {
+            let mut column_ids = Vec::new();
+            for column in columns {
+                let column_name = normalize_identifier(column);
+                match table.column_id(&column_name) {
+                    Some(column_id) => column_ids.push(column_id),
+                    None => return Err(CommandError::UnknownColumn(column_name))
+                }
+            }
+            column_ids
+        };
+        let histograms = column_ids.into_iter()
+            .map(|column_id| analyze_column(&table, column_id, HISTOGRAM_BUCKETS).map(|histogram| (column_id, histogram)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(CommandError::Read)?;
+        (histograms, table.row_count())
+    };
+    let column_count = histograms.len();
+    let mut table = table.write().unwrap();
+    for (column_id, histogram) in histograms {
+        table.set_histogram(column_id, histogram);
+    }
+    println!("Analyzed {}: {} columns of {} rows in {:.2?}", name, column_count, row_count, start.elapsed());
+    Ok(())
+}
+
+pub fn execute_analyze(db: &Database, analyze: &AnalyzeStatement) -> Result<(), CommandError> {
+    let mut tables = Vec::new();
+    {
+        let catalog = db.catalog.read().unwrap();
+        if analyze.targets.is_empty() {
+            for (schema_name, table_name, table) in catalog.all_tables() {
+                tables.push((format!("{}.{}", schema_name, table_name), table, Vec::new()));
+            }
+        }
+        for target in analyze.targets.iter() {
+            let (schema_name, table_name) = split_object_name(&target.name)?;
+            let schema_name = catalog.resolve_table_schema(schema_name.as_deref(), &table_name)?;
+            let table = catalog.get_table(Some(&schema_name), &table_name)?;
+            tables.push((format!("{}.{}", schema_name, table_name), table, target.columns.clone()));
+        }
+    }
+    for (name, table, columns) in tables {
+        analyze_table(&name, &table, &columns)?;
+    }
+    Ok(())
+}
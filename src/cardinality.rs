@@ -0,0 +1,259 @@
+use crate::core::{Catalog, CatalogError, ColumnId, ScanPredicate, Table, Value};
+use crate::lqp::{JoinMode, LQPExpressionData, LQPNodeData, LogicalOperator, PredicateCondition, LQP};
+
+// estimation of the number of rows output by the nodes of a logical query plan, which the optimizer uses to compare plans
+//  predicates on columns are estimated with the histograms created by ANALYZE, columns without a histogram fall back
+//  to the statistics of the chunks and otherwise to default selectivities (the same as the ones of PostgreSQL)
+
+// selectivity of predicates that cannot be estimated
+pub const DEFAULT_SELECTIVITY: f64 = 1.0 / 3.0;
+// selectivity of equality predicates that cannot be estimated (e.g., of join predicates)
+pub const DEFAULT_EQUALS_SELECTIVITY: f64 = 0.005;
+// selectivity of BETWEEN on columns without histogram
+pub const DEFAULT_RANGE_SELECTIVITY: f64 = 0.005;
+
+pub struct CardinalityEstimator<'a> {
+    catalog: &'a Catalog
+}
+
+// estimated number of distinct values of a column
+//  without a histogram, the largest distinct count of a chunk is a lower bound (chunks with a delta store are counted as if all
+//  rows of the delta were distinct)
+pub fn distinct_count(table: &Table, column_id: ColumnId) -> f64 {
+    if let Some(histogram) = table.histogram(column_id) {
+        return histogram.distinct_count() as f64
+    }
+    table.chunks().iter()
+        .map(|chunk| chunk.statistics(column_id).distinct_count + chunk.delta_len())
+        .max()
+        .unwrap_or(0) as f64
+}
+
+// estimated fraction of the rows of a table matching a predicate on one of its columns
+//  histograms were created from the rows at the time of ANALYZE, their fractions are applied to the current rows
+pub fn predicate_selectivity(table: &Table, column_id: ColumnId, predicate: &ScanPredicate<Value>) -> f64 {
+    if let Some(selectivity) = table.histogram(column_id).and_then(|histogram| histogram.selectivity(predicate)) {
+        return selectivity
+    }
+    let row_count = table.row_count();
+    if row_count == 0 {
+        return 0.0
+    }
+    match predicate {
+        ScanPredicate::Equals(_) => {
+            // the distinct count is a lower bound, so this overestimates rather than underestimates
+            let distinct_count = distinct_count(table, column_id);
+            if distinct_count > 0.0 { 1.0 / distinct_count } else { 0.0 }
+        },
+        ScanPredicate::Between(_, _) => DEFAULT_RANGE_SELECTIVITY,
+        ScanPredicate::IsNull | ScanPredicate::IsNotNull => {
+            // NULLs of the main stores are counted exactly, rows of the delta stores are assumed not to be NULL
+            let null_count: usize = table.chunks().iter().map(|chunk| chunk.statistics(column_id).null_count).sum();
+            let null_fraction = null_count as f64 / row_count as f64;
+            if let ScanPredicate::IsNull = predicate { null_fraction } else { 1.0 - null_fraction }
+        },
+        _ => DEFAULT_SELECTIVITY
+    }
+}
+
+// estimated number of rows of an equi-join of inputs with the given numbers of rows and distinct values in the join columns
+//  (each value of the side with fewer distinct values is assumed to find its matches on the other side)
+pub fn equi_join_cardinality(left_rows: f64, right_rows: f64, left_distinct: f64, right_distinct: f64) -> f64 {
+    let distinct = left_distinct.max(right_distinct);
+    if distinct > 0.0 { left_rows * right_rows / distinct } else { 0.0 }
+}
+
+// estimated number of groups of an aggregate grouping by columns with the given numbers of distinct values
+//  (the columns are assumed to be independent, an aggregate without GROUP BY has exactly one group)
+pub fn group_count(input_rows: f64, distinct_counts: &[f64]) -> f64 {
+    if distinct_counts.is_empty() {
+        return 1.0
+    }
+    distinct_counts.iter().product::<f64>().min(input_rows)
+}
+
+// comparison of a column with literals (e.g., a < 5 or a BETWEEN 1 AND 3)
+pub struct ColumnComparison {
+    // node of the column and its id within the node
+    pub node: usize,
+    pub column_id: ColumnId,
+    pub condition: PredicateCondition,
+    pub values: Vec<Value>
+}
+
+// the column and the literals of a predicate, comparisons with the literal first are mirrored (e.g., 5 < a is a > 5)
+//  None if the predicate does not compare a column with non-NULL literals
+pub fn column_comparison(lqp: &LQP, predicate: usize) -> Option<ColumnComparison> {
+    let expressions = lqp.expressions();
+    let expression = &expressions[predicate];
+    let condition = match &expression.data {
+        LQPExpressionData::Predicate(condition) => *condition,
+        _ => return None
+    };
+    let literal = |argument: usize| match &expressions[argument].data {
+        LQPExpressionData::Value(value) if !value.is_null() => Some(value.clone()),
+        _ => None
+    };
+    let (column, condition, values) = match (condition, expression.arguments.as_slice()) {
+        (_, [column, values @ ..]) if values.iter().all(|value| literal(*value).is_some()) => {
+            (*column, condition, values.iter().filter_map(|value| literal(*value)).collect::<Vec<_>>())
+        },
+        (_, [value, column]) if literal(*value).is_some() => {
+            let condition = match condition {
+                PredicateCondition::LessThan => PredicateCondition::GreaterThan,
+                PredicateCondition::LessThanEquals => PredicateCondition::GreaterThanEquals,
+                PredicateCondition::GreaterThan => PredicateCondition::LessThan,
+                PredicateCondition::GreaterThanEquals => PredicateCondition::LessThanEquals,
+                condition => condition
+            };
+            (*column, condition, vec![literal(*value).unwrap()])
+        },
+        _ => return None
+    };
+    match &expressions[column].data {
+        LQPExpressionData::Column { node, column_id } => Some(ColumnComparison { node: *node, column_id: *column_id, condition, values }),
+        _ => None
+    }
+}
+
+impl<'a> CardinalityEstimator<'a> {
+    pub fn new(catalog: &'a Catalog) -> Self {
+        CardinalityEstimator { catalog }
+    }
+
+    // estimated number of output rows of each node, indexed by node id (0 for nodes that are not part of the plan)
+    pub fn estimate(&self, lqp: &LQP) -> Result<Vec<f64>, CatalogError> {
+        let mut cardinalities = vec![0.0; lqp.nodes().len()];
+        self.estimate_node(lqp, lqp.root_node(), &mut cardinalities)?;
+        Ok(cardinalities)
+    }
+
+    fn estimate_node(&self, lqp: &LQP, node_id: usize, cardinalities: &mut Vec<f64>) -> Result<f64, CatalogError> {
+        let node = &lqp.nodes()[node_id];
+        let mut inputs = [None, None];
+        for (input, input_id) in inputs.iter_mut().zip(node.inputs.iter()) {
+            if let Some(input_id) = input_id {
+                *input = Some(self.estimate_node(lqp, *input_id, cardinalities)?);
+            }
+        }
+        let cardinality = match (&node.data, inputs) {
+            (LQPNodeData::Table { schema_name, table_name, .. }, _) => {
+                let table = self.catalog.get_table(Some(schema_name), table_name)?;
+                let row_count = table.read().unwrap().row_count();
+                row_count as f64
+            },
+            // without FROM, a single row is projected
+            (LQPNodeData::Projection, [input, _]) => input.unwrap_or(1.0),
+            // the predicates are assumed to be independent
+            (LQPNodeData::Filter, [input, _]) => {
+                let mut selectivity = 1.0;
+                for predicate in node.expressions.iter() {
+                    selectivity *= self.selectivity(lqp, *predicate)?;
+                }
+                input.unwrap_or(1.0) * selectivity
+            },
+            (LQPNodeData::Join(mode), [left, right]) => {
+                let (left, right) = (left.unwrap_or(0.0), right.unwrap_or(0.0));
+                // the join predicates are assumed to be independent, like the predicates of filters
+                let mut inner = left * right;
+                for predicate in node.expressions.iter() {
+                    inner *= self.selectivity(lqp, *predicate)?;
+                }
+                // outer joins keep every row of their outer side(s)
+                match mode {
+                    JoinMode::Left => inner.max(left),
+                    JoinMode::Right => inner.max(right),
+                    JoinMode::FullOuter => inner.max(left).max(right),
+                    JoinMode::Inner | JoinMode::Cross => inner
+                }
+            },
+            (LQPNodeData::Aggregate, [input, _]) => {
+                let input = input.unwrap_or(1.0);
+                let mut distinct_counts = Vec::new();
+                for expression in node.expressions.iter() {
+                    if let LQPExpressionData::Aggregate(_) = lqp.expressions()[*expression].data {
+                        continue
+                    }
+                    // group-by expressions other than columns are assumed to have a distinct value per row
+                    distinct_counts.push(self.column_distinct_count(lqp, *expression)?.unwrap_or(input));
+                }
+                group_count(input, &distinct_counts)
+            }
+        };
+        cardinalities[node_id] = cardinality;
+        Ok(cardinality)
+    }
+
+    // estimated fraction of the rows matching a predicate
+    //  predicates comparing a column of a stored table with literals are estimated with predicate_selectivity, all others
+    //  with the default selectivity
+    fn selectivity(&self, lqp: &LQP, predicate: usize) -> Result<f64, CatalogError> {
+        let expressions = lqp.expressions();
+        let expression = &expressions[predicate];
+        let condition = match &expression.data {
+            LQPExpressionData::Predicate(condition) => *condition,
+            LQPExpressionData::Logical(LogicalOperator::And) => {
+                return Ok(self.selectivity(lqp, expression.arguments[0])? * self.selectivity(lqp, expression.arguments[1])?)
+            },
+            LQPExpressionData::Logical(LogicalOperator::Or) => {
+                let (left, right) = (self.selectivity(lqp, expression.arguments[0])?, self.selectivity(lqp, expression.arguments[1])?);
+                return Ok(left + right - left * right)
+            },
+            LQPExpressionData::Not => return Ok(1.0 - self.selectivity(lqp, expression.arguments[0])?),
+            _ => return Ok(DEFAULT_SELECTIVITY)
+        };
+        let default_selectivity = if condition == PredicateCondition::Equals { DEFAULT_EQUALS_SELECTIVITY } else { DEFAULT_SELECTIVITY };
+        // equality of columns (e.g., of an equi-join), each value of the column with fewer distinct values is assumed to have
+        //  matches in the other column
+        if let (PredicateCondition::Equals, [left, right]) = (condition, expression.arguments.as_slice()) {
+            if let (Some(left), Some(right)) = (self.column_distinct_count(lqp, *left)?, self.column_distinct_count(lqp, *right)?) {
+                return Ok(equi_join_cardinality(1.0, 1.0, left, right))
+            }
+        }
+        let comparison = match column_comparison(lqp, predicate) {
+            Some(comparison) => comparison,
+            None => return Ok(default_selectivity)
+        };
+        let table = match &lqp.nodes()[comparison.node].data {
+            LQPNodeData::Table { schema_name, table_name, .. } => self.catalog.get_table(Some(schema_name), table_name)?,
+            _ => return Ok(default_selectivity)
+        };
+        let (column_id, condition, values) = (comparison.column_id, comparison.condition, comparison.values);
+        let table = table.read().unwrap();
+        let selectivity = |predicate| predicate_selectivity(&table, column_id, &predicate);
+        let mut values = values.into_iter();
+        Ok(match (condition, values.next(), values.next()) {
+            (PredicateCondition::Equals, Some(value), None) => selectivity(ScanPredicate::Equals(value)),
+            (PredicateCondition::NotEquals, Some(value), None) => 1.0 - selectivity(ScanPredicate::Equals(value)),
+            (PredicateCondition::LessThan, Some(value), None) => selectivity(ScanPredicate::LessThan(value)),
+            (PredicateCondition::LessThanEquals, Some(value), None) => selectivity(ScanPredicate::LessThanEquals(value)),
+            (PredicateCondition::GreaterThan, Some(value), None) => selectivity(ScanPredicate::GreaterThan(value)),
+            (PredicateCondition::GreaterThanEquals, Some(value), None) => selectivity(ScanPredicate::GreaterThanEquals(value)),
+            (PredicateCondition::BetweenInclusive, Some(low), Some(high)) => selectivity(ScanPredicate::Between(low, high)),
+            (PredicateCondition::NotBetweenInclusive, Some(low), Some(high)) => 1.0 - selectivity(ScanPredicate::Between(low, high)),
+            (PredicateCondition::In | PredicateCondition::NotIn, first, second) => {
+                let mut in_selectivity = 0.0;
+                for value in first.into_iter().chain(second).chain(values) {
+                    in_selectivity += selectivity(ScanPredicate::Equals(value));
+                }
+                let in_selectivity = f64::min(in_selectivity, 1.0);
+                if condition == PredicateCondition::In { in_selectivity } else { 1.0 - in_selectivity }
+            },
+            (PredicateCondition::IsNull, None, None) => selectivity(ScanPredicate::IsNull),
+            (PredicateCondition::IsNotNull, None, None) => selectivity(ScanPredicate::IsNotNull),
+            _ => default_selectivity
+        })
+    }
+
+    // estimated number of distinct values of a column expression, None for other expressions
+    fn column_distinct_count(&self, lqp: &LQP, expression: usize) -> Result<Option<f64>, CatalogError> {
+        if let LQPExpressionData::Column { node, column_id } = lqp.expressions()[expression].data {
+            if let LQPNodeData::Table { schema_name, table_name, .. } = &lqp.nodes()[node].data {
+                let table = self.catalog.get_table(Some(schema_name), table_name)?;
+                let distinct_count = distinct_count(&table.read().unwrap(), column_id);
+                return Ok(Some(distinct_count))
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,277 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use sqlparser::dialect::GenericDialect;
+use sqlparser::tokenizer::{Token, Tokenizer};
+
+use crate::core::{merge_chunk, read_table, write_table, Catalog, COLUMN_FILE_EXTENSION};
+use crate::transaction::WriterGate;
+use crate::wal::Wal;
+
+// checkpoints persist all tables as column files, so that the write-ahead log can be truncated (which bounds its size and
+//  the time needed to replay it on startup)
+//  each checkpoint is a directory checkpoint-<id> with a subdirectory per schema containing a column file per table, it
+//  is written under a temporary name and renamed once complete, so the latest checkpoint directory is always complete
+//  the log names the checkpoint it continues, so a log that was not truncated because of a crash right after the rename
+//  is recognized as outdated by the recovery
+
+const CHECKPOINT_PREFIX: &str = "checkpoint-";
+const TEMP_EXTENSION: &str = "tmp";
+
+pub struct CheckpointStats {
+    pub id: u64,
+    pub tables: usize,
+    pub rows: usize,
+    // chunks whose delta store was merged
+    pub merged_chunks: usize,
+    pub duration: Duration
+}
+
+pub struct Checkpointer {
+    dir: PathBuf,
+    wal: Arc<Wal>,
+    writers: Arc<WriterGate>,
+    // id and time of the last checkpoint, locked while a checkpoint is made
+    last: Mutex<(u64, Instant)>
+}
+
+fn checkpoint_id(path: &Path) -> Option<u64> {
+    path.file_name()?.to_str()?.strip_prefix(CHECKPOINT_PREFIX)?.parse().ok()
+}
+
+// ids and paths of the complete checkpoints in the given directory
+fn checkpoints(dir: &Path) -> io::Result<Vec<(u64, PathBuf)>> {
+    let mut checkpoints = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if let Some(id) = checkpoint_id(&path) {
+            checkpoints.push((id, path));
+        }
+    }
+    checkpoints.sort();
+    Ok(checkpoints)
+}
+
+// makes renames and newly created files in the directory durable
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+// creates the tables of the latest checkpoint in the given directory, returns its id (None if there is no checkpoint)
+pub fn load_checkpoint(dir: &Path, catalog: &mut Catalog) -> io::Result<Option<u64>> {
+    if !dir.exists() {
+        return Ok(None)
+    }
+    let (id, path) = match checkpoints(dir)?.pop() {
+        Some(checkpoint) => checkpoint,
+        None => return Ok(None)
+    };
+    for schema_entry in fs::read_dir(&path)? {
+        let schema_path = schema_entry?.path();
+        let schema_name = schema_path.file_name().unwrap().to_string_lossy().into_owned();
+        if catalog.get_schema(&schema_name).is_err() {
+            catalog.create_schema(&schema_name).unwrap();
+        }
+        for table_entry in fs::read_dir(&schema_path)? {
+            let table_path = table_entry?.path();
+            if table_path.extension().and_then(|extension| extension.to_str()) != Some(COLUMN_FILE_EXTENSION) {
+                continue
+            }
+            let table_name = table_path.file_stem().unwrap().to_string_lossy().into_owned();
+            let table = read_table(&table_path).map_err(|err| io::Error::new(err.kind(), format!("{}: {}", table_path.display(), err)))?;
+            println!("{}.{}: {} rows in {} chunks", schema_name, table_name, table.row_count(), table.chunk_count());
+            catalog.create_table(Some(&schema_name), &table_name, table).unwrap();
+        }
+    }
+    Ok(Some(id))
+}
+
+// whether the SQL is a CHECKPOINT statement, which is not known to the SQL parser
+pub fn is_checkpoint(sql: &str) -> bool {
+    let dialect = GenericDialect {};
+    let tokens = match Tokenizer::new(&dialect, sql).tokenize() {
+        Ok(tokens) => tokens,
+        Err(_) => return false
+    };
+    let mut tokens = tokens.into_iter().filter(|token| !matches!(token, Token::Whitespace(_) | Token::SemiColon));
+    match (tokens.next(), tokens.next()) {
+        (Some(Token::Word(word)), None) => word.quote_style.is_none() && word.value.eq_ignore_ascii_case("checkpoint"),
+        _ => false
+    }
+}
+
+impl Checkpointer {
+    // dir contains the checkpoints, the last of which has the given id (0 if there is none)
+    pub fn new(dir: &Path, checkpoint: u64, wal: Arc<Wal>, writers: Arc<WriterGate>) -> Self {
+        Checkpointer { dir: dir.to_owned(), wal, writers, last: Mutex::new((checkpoint, Instant::now())) }
+    }
+
+    // whether the log reached max_wal_size or interval passed since the last checkpoint and something was logged since
+    //  (false while a checkpoint is made)
+    pub fn is_due(&self, max_wal_size: u64, interval: Duration) -> bool {
+        let last_time = match self.last.try_lock() {
+            Ok(last) => last.1,
+            Err(_) => return false
+        };
+        let size = self.wal.size();
+        size >= max_wal_size || (size > 0 && last_time.elapsed() >= interval)
+    }
+
+    // merges the delta stores of all tables, writes the tables to a new checkpoint and truncates the log
+    //  tables cannot be changed until the checkpoint is complete (but they can be read)
+    pub fn checkpoint(&self, catalog: &RwLock<Catalog>) -> io::Result<CheckpointStats> {
+        let mut last = self.last.lock().unwrap();
+        let start = Instant::now();
+        let changes_blocked = self.writers.block_changes();
+        let id = last.0 + 1;
+        let path = self.dir.join(format!("{}{}", CHECKPOINT_PREFIX, id));
+        let temp_path = path.with_extension(TEMP_EXTENSION);
+        if temp_path.exists() {
+            // left behind by a checkpoint that failed
+            fs::remove_dir_all(&temp_path)?;
+        }
+        fs::create_dir_all(&temp_path)?;
+
+        let tables = catalog.read().unwrap().all_tables();
+        let mut stats = CheckpointStats { id, tables: tables.len(), rows: 0, merged_chunks: 0, duration: Duration::default() };
+        for (schema_name, table_name, table) in tables {
+            let chunk_ids = table.read().unwrap().chunks_to_merge(1);
+            for chunk_id in chunk_ids {
+                merge_chunk(&table, chunk_id)?;
+                stats.merged_chunks += 1;
+            }
+            let schema_path = temp_path.join(&schema_name);
+            fs::create_dir_all(&schema_path)?;
+            let table = table.read().unwrap();
+            write_table(&table, &schema_path.join(format!("{}.{}", table_name, COLUMN_FILE_EXTENSION)))?;
+            sync_dir(&schema_path)?;
+            stats.rows += table.row_count();
+        }
+        sync_dir(&temp_path)?;
+        fs::rename(&temp_path, &path)?;
+        sync_dir(&self.dir)?;
+        last.0 = id;
+        self.wal.truncate(id)?;
+        last.1 = Instant::now();
+        drop(changes_blocked);
+
+        // tables mapped from older checkpoints keep their mappings when the files are removed
+        for (old_id, old_path) in checkpoints(&self.dir)? {
+            if old_id < id {
+                if let Err(err) = fs::remove_dir_all(&old_path) {
+                    println!("Could not remove {}: {}", old_path.display(), err);
+                }
+            }
+        }
+        stats.duration = start.elapsed();
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{ColumnDefinition, DataType, Table, Value};
+    use crate::transaction::TransactionManager;
+    use crate::wal::{self, SyncPolicy};
+
+    struct TestDatabase {
+        dir: PathBuf,
+        wal_path: PathBuf,
+        catalog: RwLock<Catalog>,
+        transaction_manager: TransactionManager,
+        checkpointer: Checkpointer
+    }
+
+    impl TestDatabase {
+        // a table with 100 rows and no checkpoint
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("rdbms-checkpoint-{}-{}", std::process::id(), name));
+            if dir.exists() {
+                fs::remove_dir_all(&dir).unwrap();
+            }
+            fs::create_dir_all(&dir).unwrap();
+            let mut catalog = Catalog::new();
+            let columns = vec![ColumnDefinition::new("id", DataType::BigInt, false), ColumnDefinition::new("name", DataType::Text, true)];
+            let mut table = Table::new(columns, 64);
+            table.insert((0..100).map(|i| vec![Value::BigInt(i), Value::Text(format!("initial {}", i))]).collect()).unwrap();
+            catalog.create_table(None, "t", table).unwrap();
+            let wal_path = dir.join("rdbms.wal");
+            let wal = Wal::open(&wal_path, SyncPolicy::Commit, 0).unwrap();
+            let transaction_manager = TransactionManager::new(Some(wal.clone()));
+            let checkpointer = Checkpointer::new(&dir.join("data"), 0, wal, transaction_manager.writer_gate());
+            fs::create_dir_all(dir.join("data")).unwrap();
+            TestDatabase { dir, wal_path, catalog: RwLock::new(catalog), transaction_manager, checkpointer }
+        }
+
+        fn insert(&mut self, first: i64, count: i64) {
+            let mut transaction = self.transaction_manager.new_transaction_context();
+            let table = self.catalog.read().unwrap().get_table(None, "t").unwrap();
+            let rows = (first..first + count).map(|i| vec![Value::BigInt(i), if i % 3 == 0 { Value::Null } else { Value::Text(format!("row {}", i)) }]).collect();
+            transaction.insert("public", "t", table, rows).unwrap();
+            transaction.commit(&self.catalog).unwrap();
+        }
+
+        // loads the latest checkpoint and replays the log, like on startup
+        fn restart(&self) -> (Catalog, Option<u64>, (usize, usize)) {
+            let mut catalog = Catalog::new();
+            let checkpoint = load_checkpoint(&self.dir.join("data"), &mut catalog).unwrap();
+            let replayed = wal::recover(&self.wal_path, &mut catalog, checkpoint.unwrap_or(0)).unwrap();
+            (catalog, checkpoint, replayed)
+        }
+    }
+
+    impl Drop for TestDatabase {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn rows(catalog: &Catalog) -> Vec<Vec<Value>> {
+        let table = catalog.get_table(None, "t").unwrap();
+        let table = table.read().unwrap();
+        table.chunks().iter().flat_map(|chunk| {
+            (0..chunk.len()).map(move |i| (0..chunk.column_count()).map(|column_id| chunk.get_value(column_id, i).unwrap()).collect::<Vec<_>>())
+        }).collect()
+    }
+
+    #[test]
+    fn checkpoint_and_log_restore_the_tables() {
+        let mut db = TestDatabase::new("round-trip");
+        db.insert(100, 50);
+        let stats = db.checkpointer.checkpoint(&db.catalog).unwrap();
+        assert_eq!((stats.id, stats.rows), (1, 150));
+        db.insert(150, 30);
+        db.insert(180, 1);
+        let expected = rows(&db.catalog.read().unwrap());
+        assert_eq!(expected.len(), 181);
+
+        let (catalog, checkpoint, replayed) = db.restart();
+        assert_eq!(checkpoint, Some(1));
+        assert_eq!(replayed, (2, 31));
+        assert_eq!(rows(&catalog), expected);
+    }
+
+    // the log of the previous checkpoint is still there, but its changes must not be replayed twice
+    #[test]
+    fn crash_after_rename_before_truncate() {
+        let mut db = TestDatabase::new("crash");
+        db.insert(100, 50);
+        let old_log = fs::read(&db.wal_path).unwrap();
+        db.checkpointer.checkpoint(&db.catalog).unwrap();
+        let expected = rows(&db.catalog.read().unwrap());
+        fs::write(&db.wal_path, &old_log).unwrap();
+
+        let (catalog, checkpoint, replayed) = db.restart();
+        assert_eq!(checkpoint, Some(1));
+        assert_eq!(replayed, (0, 0));
+        assert_eq!(rows(&catalog), expected);
+        // the outdated log was replaced by one continuing the checkpoint
+        let (catalog, _, replayed) = db.restart();
+        assert_eq!(replayed, (0, 0));
+        assert_eq!(rows(&catalog), expected);
+    }
+}
@@ -0,0 +1,651 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+use sqlparser::ast::*;
+
+use crate::loader::RowError;
+use crate::lqp::convert_data_type;
+use crate::transaction::TransactionContext;
+use crate::core::{CatalogError, ColumnDefinition, DataType, Database, Date, Decimal, Table, TableError, Value, MAX_DECIMAL_PRECISION};
+
+// execution of statements that do not need a query plan (e.g., INSERT ... VALUES or SELECT without FROM)
+
+#[derive(Debug)]
+pub enum CommandError {
+    Catalog(CatalogError),
+    Table(TableError),
+    NotSupported(&'static str),
+    UnknownColumn(String),
+    InvalidLiteral(String),
+    OperatorDoesNotExist(String),
+    DatatypeMismatch(String),
+    DivisionByZero,
+    // result of an arithmetic operation that does not fit into its type
+    OutOfRange(DataType),
+    // file access of COPY (path and error)
+    File(String, io::Error),
+    // invalid input row of COPY FROM (table name and error)
+    Copy(String, RowError),
+    // the changes could not be written to the write-ahead log
+    Log(io::Error),
+    Checkpoint(io::Error),
+    // the values of a table could not be read (e.g., an invalid compressed block of a column file)
+    Read(io::Error)
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Catalog(err) => write!(f, "{}", err),
+            CommandError::Table(err) => write!(f, "{}", err),
+            CommandError::NotSupported(msg) => write!(f, "Not supported: {}", msg),
+            CommandError::UnknownColumn(name) => write!(f, "column \"{}\" does not exist", name),
+            CommandError::InvalidLiteral(literal) => write!(f, "invalid literal: {}", literal),
+            CommandError::OperatorDoesNotExist(operation) => write!(f, "operator does not exist: {}", operation),
+            CommandError::DatatypeMismatch(msg) => write!(f, "{}", msg),
+            CommandError::DivisionByZero => write!(f, "division by zero"),
+            CommandError::OutOfRange(data_type) => write!(f, "{} out of range", data_type),
+            CommandError::File(path, err) => write!(f, "could not access file \"{}\": {}", path, err),
+            CommandError::Copy(table, err) => write!(f, "COPY {}, {}", table, err),
+            CommandError::Log(err) => write!(f, "could not write to the write-ahead log: {}", err),
+            CommandError::Checkpoint(err) => write!(f, "checkpoint failed: {}", err),
+            CommandError::Read(err) => write!(f, "could not read the table: {}", err)
+        }
+    }
+}
+
+impl CommandError {
+    pub fn sqlstate(&self) -> &'static str {
+        match self {
+            CommandError::Catalog(err) => err.sqlstate(),
+            CommandError::Table(err) => err.sqlstate(),
+            CommandError::NotSupported(_) => "0A000",
+            CommandError::UnknownColumn(_) => "42703",
+            CommandError::InvalidLiteral(_) => "22P02",
+            CommandError::OperatorDoesNotExist(_) => "42883",
+            CommandError::DatatypeMismatch(_) => "42804",
+            CommandError::DivisionByZero => "22012",
+            CommandError::OutOfRange(_) => "22003",
+            CommandError::File(_, err) if err.kind() == io::ErrorKind::NotFound => "58P01",
+            CommandError::File(_, _) => "58030",
+            CommandError::Copy(_, err) => err.error.sqlstate(),
+            CommandError::Log(_) => "58030",
+            CommandError::Checkpoint(_) => "58030",
+            CommandError::Read(_) => "XX001"
+        }
+    }
+}
+
+impl From<CatalogError> for CommandError {
+    fn from(err: CatalogError) -> Self {
+        CommandError::Catalog(err)
+    }
+}
+
+impl From<TableError> for CommandError {
+    fn from(err: TableError) -> Self {
+        CommandError::Table(err)
+    }
+}
+
+// unquoted identifiers are case-insensitive (and stored in lower case), quoted identifiers are taken as they are
+pub fn normalize_identifier(ident: &Ident) -> String {
+    match ident.quote_style {
+        Some(_) => ident.value.clone(),
+        None => ident.value.to_lowercase()
+    }
+}
+
+// splits a possibly schema-qualified name into (schema name, object name)
+pub fn split_object_name(name: &ObjectName) -> Result<(Option<String>, String), CommandError> {
+    match name.0.len() {
+        1 => Ok((None, normalize_identifier(&name.0[0]))),
+        2 => Ok((Some(normalize_identifier(&name.0[0])), normalize_identifier(&name.0[1]))),
+        _ => Err(CommandError::NotSupported("Object names with more than two parts"))
+    }
+}
+
+// value of a numeric literal, None if it is not a valid number
+//  like in PostgreSQL, numbers with a decimal point are exact (numeric) literals, only exponents make them inexact
+pub fn parse_number(number: &str) -> Option<Value> {
+    if let Ok(val) = number.parse::<i64>() {
+        Some(Value::BigInt(val))
+    } else if let Some(val) = Decimal::parse(number) {
+        Some(Value::Decimal(val))
+    } else {
+        number.parse::<f64>().ok().map(Value::Double)
+    }
+}
+
+// evaluation of expressions without column references, e.g., the values of INSERT ... VALUES or SELECT without FROM
+//  NULL propagates through comparisons and arithmetic, AND/OR/NOT follow three-valued logic (NULL being unknown)
+pub fn evaluate_constant(expr: &Expr) -> Result<Value, CommandError> {
+    match expr {
+        Expr::Value(sqlparser::ast::Value::Null) => Ok(Value::Null),
+        Expr::Value(sqlparser::ast::Value::Boolean(val)) => Ok(Value::Boolean(*val)),
+        Expr::Value(sqlparser::ast::Value::SingleQuotedString(text)) => Ok(Value::Text(text.clone())),
+        Expr::Value(sqlparser::ast::Value::Number(number, _)) => {
+            parse_number(number).ok_or_else(|| CommandError::InvalidLiteral(number.clone()))
+        },
+        Expr::UnaryOp { op: UnaryOperator::Minus, expr } => {
+            match evaluate_constant(expr)? {
+                Value::Null => Ok(Value::Null),
+                Value::BigInt(val) => val.checked_neg().map(Value::BigInt).ok_or(CommandError::OutOfRange(DataType::BigInt)),
+                Value::Double(val) => Ok(Value::Double(-val)),
+                Value::Decimal(val) => Ok(Value::Decimal(-val)),
+                _ => Err(CommandError::OperatorDoesNotExist(format!("- {}", expr)))
+            }
+        },
+        Expr::UnaryOp { op: UnaryOperator::Plus, expr } => {
+            match evaluate_constant(expr)? {
+                value @ (Value::Null | Value::BigInt(_) | Value::Double(_) | Value::Decimal(_)) => Ok(value),
+                _ => Err(CommandError::OperatorDoesNotExist(format!("+ {}", expr)))
+            }
+        },
+        Expr::UnaryOp { op: UnaryOperator::Not, expr } => {
+            match to_truth_value(&evaluate_constant(expr)?, "NOT")? {
+                Some(val) => Ok(Value::Boolean(!val)),
+                None => Ok(Value::Null)
+            }
+        },
+        Expr::Nested(expr) => evaluate_constant(expr),
+        // typed literals like DATE '1998-12-01'
+        Expr::TypedString { data_type, value } => cast_constant(Value::Text(value.clone()), data_type),
+        Expr::Cast { expr, data_type } => cast_constant(evaluate_constant(expr)?, data_type),
+        Expr::Case { operand, conditions, results, else_result } => {
+            // only the result of the first matching condition is evaluated
+            let operand = match operand {
+                Some(operand) => Some(evaluate_constant(operand)?),
+                None => None
+            };
+            for (condition, result) in conditions.iter().zip(results.iter()) {
+                let condition = evaluate_constant(condition)?;
+                let matches = match &operand {
+                    // CASE x WHEN y is CASE WHEN x = y, which never matches NULL
+                    Some(operand) => match operand.compare(&condition) {
+                        Some(ordering) => ordering == Ordering::Equal,
+                        None if operand.is_null() || condition.is_null() => false,
+                        None => return Err(CommandError::OperatorDoesNotExist(format!("{} = {}", operand, condition)))
+                    },
+                    None => to_truth_value(&condition, "CASE")? == Some(true)
+                };
+                if matches {
+                    return evaluate_constant(result)
+                }
+            }
+            match else_result {
+                Some(else_result) => evaluate_constant(else_result),
+                None => Ok(Value::Null)
+            }
+        },
+        Expr::IsNull(expr) => Ok(Value::Boolean(evaluate_constant(expr)?.is_null())),
+        Expr::IsNotNull(expr) => Ok(Value::Boolean(!evaluate_constant(expr)?.is_null())),
+        Expr::BinaryOp { left, op: BinaryOperator::And, right } => {
+            let left = to_truth_value(&evaluate_constant(left)?, "AND")?;
+            let right = to_truth_value(&evaluate_constant(right)?, "AND")?;
+            // false if any side is false, even if the other one is unknown
+            Ok(match (left, right) {
+                (Some(false), _) | (_, Some(false)) => Value::Boolean(false),
+                (Some(true), Some(true)) => Value::Boolean(true),
+                _ => Value::Null
+            })
+        },
+        Expr::BinaryOp { left, op: BinaryOperator::Or, right } => {
+            let left = to_truth_value(&evaluate_constant(left)?, "OR")?;
+            let right = to_truth_value(&evaluate_constant(right)?, "OR")?;
+            // true if any side is true, even if the other one is unknown
+            Ok(match (left, right) {
+                (Some(true), _) | (_, Some(true)) => Value::Boolean(true),
+                (Some(false), Some(false)) => Value::Boolean(false),
+                _ => Value::Null
+            })
+        },
+        Expr::BinaryOp { left: left_expr, op, right: right_expr } => {
+            let left = evaluate_constant(left_expr)?;
+            let right = evaluate_constant(right_expr)?;
+            if left.is_null() || right.is_null() {
+                return Ok(Value::Null)
+            }
+            if is_arithmetic_operator(op) {
+                return evaluate_arithmetic(left, op, right)
+                    .unwrap_or_else(|| Err(CommandError::OperatorDoesNotExist(format!("{} {} {}", left_expr, op, right_expr))))
+            }
+            let ordering = match left.compare(&right) {
+                Some(ordering) => ordering,
+                None => return Err(CommandError::OperatorDoesNotExist(format!("{} {} {}", left_expr, op, right_expr)))
+            };
+            let result = match op {
+                BinaryOperator::Eq => ordering == Ordering::Equal,
+                BinaryOperator::NotEq => ordering != Ordering::Equal,
+                BinaryOperator::Lt => ordering == Ordering::Less,
+                BinaryOperator::LtEq => ordering != Ordering::Greater,
+                BinaryOperator::Gt => ordering == Ordering::Greater,
+                BinaryOperator::GtEq => ordering != Ordering::Less,
+                _ => return Err(CommandError::NotSupported("Operators other than arithmetic, comparisons, AND and OR"))
+            };
+            Ok(Value::Boolean(result))
+        },
+        Expr::Function(func) if is_function(func, "coalesce") => {
+            // the first argument that is not NULL (later arguments are not evaluated)
+            for arg in func.args.iter() {
+                let value = match arg {
+                    FunctionArg::Unnamed(arg) => evaluate_constant(arg)?,
+                    FunctionArg::Named { .. } => return Err(CommandError::NotSupported("Named function arguments"))
+                };
+                if !value.is_null() {
+                    return Ok(value)
+                }
+            }
+            Ok(Value::Null)
+        },
+        _ => Err(CommandError::NotSupported("Only constant expressions are supported as values"))
+    }
+}
+
+fn is_arithmetic_operator(op: &BinaryOperator) -> bool {
+    matches!(op, BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulus)
+}
+
+// result of an arithmetic operation on values that are not NULL, None if the operator does not exist for their types
+//  the result types are the ones of the LQP translation (see arithmetic_type), e.g., integer division truncates and
+//  operations on decimals and integers are exact
+fn evaluate_arithmetic(left: Value, op: &BinaryOperator, right: Value) -> Option<Result<Value, CommandError>> {
+    let result = match (left, right) {
+        (Value::BigInt(left), Value::BigInt(right)) => {
+            if right == 0 && matches!(op, BinaryOperator::Divide | BinaryOperator::Modulus) {
+                return Some(Err(CommandError::DivisionByZero))
+            }
+            let result = match op {
+                BinaryOperator::Plus => left.checked_add(right),
+                BinaryOperator::Minus => left.checked_sub(right),
+                BinaryOperator::Multiply => left.checked_mul(right),
+                BinaryOperator::Divide => left.checked_div(right),
+                _ => left.checked_rem(right)
+            };
+            result.map(Value::BigInt).ok_or(CommandError::OutOfRange(DataType::BigInt))
+        },
+        (Value::Date(date), Value::BigInt(days)) | (Value::BigInt(days), Value::Date(date)) if *op == BinaryOperator::Plus => {
+            add_days(date, days)
+        },
+        (Value::Date(date), Value::BigInt(days)) if *op == BinaryOperator::Minus => {
+            match days.checked_neg() {
+                Some(days) => add_days(date, days),
+                None => Err(CommandError::OutOfRange(DataType::Date))
+            }
+        },
+        (Value::Date(left), Value::Date(right)) if *op == BinaryOperator::Minus => Ok(Value::BigInt((left - right) as i64)),
+        (left @ Value::Double(_), right) | (left, right @ Value::Double(_)) => {
+            let (left, right) = (to_double(&left)?, to_double(&right)?);
+            if right == 0.0 && matches!(op, BinaryOperator::Divide | BinaryOperator::Modulus) {
+                return Some(Err(CommandError::DivisionByZero))
+            }
+            let result = match op {
+                BinaryOperator::Plus => left + right,
+                BinaryOperator::Minus => left - right,
+                BinaryOperator::Multiply => left * right,
+                BinaryOperator::Divide => left / right,
+                _ => left % right
+            };
+            if result.is_finite() { Ok(Value::Double(result)) } else { Err(CommandError::OutOfRange(DataType::Double)) }
+        },
+        (left, right) => {
+            let (left, right) = (to_decimal(&left)?, to_decimal(&right)?);
+            if right.unscaled_value() == 0 && matches!(op, BinaryOperator::Divide | BinaryOperator::Modulus) {
+                return Some(Err(CommandError::DivisionByZero))
+            }
+            let scale = std::cmp::max(left.scale(), right.scale());
+            let result = match op {
+                BinaryOperator::Plus => left.checked_add(&right),
+                BinaryOperator::Minus => left.checked_sub(&right),
+                BinaryOperator::Multiply => {
+                    left.checked_mul(&right).and_then(|result| result.rescale(std::cmp::min(result.scale(), MAX_DECIMAL_PRECISION)))
+                },
+                BinaryOperator::Divide => left.checked_div(&right),
+                _ => {
+                    // operands with the same number of fractional digits
+                    left.rescale(scale).zip(right.rescale(scale))
+                        .and_then(|(left, right)| left.unscaled_value().checked_rem(right.unscaled_value()))
+                        .map(|value| Decimal::new(value, scale))
+                }
+            };
+            match result {
+                Some(result) if result.precision() <= MAX_DECIMAL_PRECISION => Ok(Value::Decimal(result)),
+                _ => Err(CommandError::OutOfRange(DataType::Decimal(MAX_DECIMAL_PRECISION, scale)))
+            }
+        }
+    };
+    Some(result)
+}
+
+fn add_days(date: Date, days: i64) -> Result<Value, CommandError> {
+    match i32::try_from(days) {
+        Ok(days) => Ok(Value::Date(date + days)),
+        Err(_) => Err(CommandError::OutOfRange(DataType::Date))
+    }
+}
+
+fn to_double(value: &Value) -> Option<f64> {
+    match value {
+        Value::BigInt(val) => Some(*val as f64),
+        Value::Double(val) => Some(*val),
+        Value::Decimal(val) => Some(val.to_f64()),
+        _ => None
+    }
+}
+
+fn to_decimal(value: &Value) -> Option<Decimal> {
+    match value {
+        Value::BigInt(val) => Some(Decimal::from_i64(*val)),
+        Value::Decimal(val) => Some(*val),
+        _ => None
+    }
+}
+
+// explicit cast of a constant (e.g., CAST(1.5 AS BIGINT) or DATE '1998-12-01')
+//  in addition to the conversions of assignments, numbers can be rounded to integers and all values converted to and from
+//  text
+fn cast_constant(value: Value, data_type: &sqlparser::ast::DataType) -> Result<Value, CommandError> {
+    let data_type = convert_data_type(data_type).map_err(|_| CommandError::NotSupported("Data type"))?;
+    let value = match (value, data_type) {
+        (Value::Null, _) => Value::Null,
+        (Value::Text(val), _) => data_type.parse(&val)?,
+        (Value::Double(val), DataType::BigInt) => {
+            let val = val.round();
+            if val < i64::MIN as f64 || val >= i64::MAX as f64 || val.is_nan() {
+                return Err(CommandError::OutOfRange(DataType::BigInt))
+            }
+            Value::BigInt(val as i64)
+        },
+        (Value::Decimal(val), DataType::BigInt) => {
+            match val.rescale(0).and_then(|val| i64::try_from(val.unscaled_value()).ok()) {
+                Some(val) => Value::BigInt(val),
+                None => return Err(CommandError::OutOfRange(DataType::BigInt))
+            }
+        },
+        (Value::Double(val), DataType::Decimal(_, _)) => {
+            Value::Decimal(Decimal::parse(&val.to_string()).ok_or_else(|| TableError::InvalidInput(data_type, val.to_string()))?)
+        },
+        (value, DataType::Char(_) | DataType::Varchar(_) | DataType::Text) => Value::Text(value.to_string()),
+        (value, _) => value
+    };
+    Ok(ColumnDefinition::new("?column?", data_type, true).coerce(value)?)
+}
+
+fn is_function(func: &Function, name: &str) -> bool {
+    func.name.0.len() == 1 && normalize_identifier(&func.name.0[0]) == name
+}
+
+// boolean operands of logical operators, None being unknown (NULL)
+fn to_truth_value(value: &Value, operator: &str) -> Result<Option<bool>, CommandError> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Boolean(val) => Ok(Some(*val)),
+        _ => Err(CommandError::DatatypeMismatch(format!("argument of {} must be type boolean", operator)))
+    }
+}
+
+// whether the expression can be evaluated by evaluate_constant (i.e., references neither columns nor session information)
+fn is_constant(expr: &Expr) -> bool {
+    match expr {
+        Expr::Value(value) => matches!(value, sqlparser::ast::Value::Null | sqlparser::ast::Value::Boolean(_)
+            | sqlparser::ast::Value::SingleQuotedString(_) | sqlparser::ast::Value::Number(_, _)),
+        Expr::TypedString { data_type, .. } => convert_data_type(data_type).is_ok(),
+        Expr::Cast { expr, data_type } => convert_data_type(data_type).is_ok() && is_constant(expr),
+        Expr::UnaryOp { op: UnaryOperator::Plus, expr } | Expr::UnaryOp { op: UnaryOperator::Minus, expr }
+            | Expr::UnaryOp { op: UnaryOperator::Not, expr } => is_constant(expr),
+        Expr::Nested(expr) | Expr::IsNull(expr) | Expr::IsNotNull(expr) => is_constant(expr),
+        Expr::BinaryOp { left, op, right } => {
+            (is_arithmetic_operator(op) || matches!(op, BinaryOperator::Eq | BinaryOperator::NotEq | BinaryOperator::Lt
+                | BinaryOperator::LtEq | BinaryOperator::Gt | BinaryOperator::GtEq | BinaryOperator::And | BinaryOperator::Or))
+                && is_constant(left) && is_constant(right)
+        },
+        Expr::Case { operand, conditions, results, else_result } => {
+            operand.as_deref().is_none_or(is_constant) && conditions.iter().chain(results.iter()).all(is_constant)
+                && else_result.as_deref().is_none_or(is_constant)
+        },
+        Expr::Function(func) => {
+            is_function(func, "coalesce") && func.args.iter().all(|arg| matches!(arg, FunctionArg::Unnamed(arg) if is_constant(arg)))
+        },
+        _ => false
+    }
+}
+
+// type of the result column of a constant expression
+fn data_type_of(value: &Value) -> DataType {
+    match value {
+        // like an untyped literal in PostgreSQL
+        Value::Null | Value::Text(_) => DataType::Text,
+        Value::BigInt(_) => DataType::BigInt,
+        Value::Double(_) => DataType::Double,
+        Value::Date(_) => DataType::Date,
+        Value::Decimal(val) => DataType::Decimal(MAX_DECIMAL_PRECISION, val.scale()),
+        Value::Boolean(_) => DataType::Boolean
+    }
+}
+
+// result columns (name and type) and the single result row of a SELECT without FROM
+pub type ConstantSelectResult = (Vec<(String, DataType)>, Vec<Value>);
+
+// SELECT without FROM whose select list only consists of constant expressions (None for all other queries, which have
+//  to be planned)
+pub fn execute_constant_select(query: &Query) -> Result<Option<ConstantSelectResult>, CommandError> {
+    let select = match &query.body {
+        SetExpr::Select(select) if query.with.is_none() && select.from.is_empty() && select.selection.is_none() => select,
+        _ => return Ok(None)
+    };
+    let mut columns = Vec::new();
+    let mut row = Vec::new();
+    for item in select.projection.iter() {
+        let (expr, name) = match item {
+            SelectItem::UnnamedExpr(expr) => {
+                // PostgreSQL names columns of function calls after the function
+                let name = match expr {
+                    Expr::Function(func) => normalize_identifier(func.name.0.last().unwrap()),
+                    _ => String::from("?column?")
+                };
+                (expr, name)
+            },
+            SelectItem::ExprWithAlias { expr, alias } => (expr, normalize_identifier(alias)),
+            _ => return Ok(None)
+        };
+        if !is_constant(expr) {
+            return Ok(None)
+        }
+        let value = evaluate_constant(expr)?;
+        columns.push((name, data_type_of(&value)));
+        row.push(value);
+    }
+    Ok(Some((columns, row)))
+}
+
+// returns the number of inserted rows
+pub fn execute_insert(db: &Database, transaction: &mut TransactionContext, table_name: &ObjectName, columns: &[Ident], source: &Query) -> Result<usize, CommandError> {
+    let values = match &source.body {
+        SetExpr::Values(values) => values,
+        _ => return Err(CommandError::NotSupported("INSERT ... SELECT"))
+    };
+    let (schema_name, table_name) = split_object_name(table_name)?;
+    let (schema_name, table) = {
+        let catalog = db.catalog.read().unwrap();
+        let schema_name = catalog.resolve_table_schema(schema_name.as_deref(), &table_name)?;
+        let table = catalog.get_table(Some(&schema_name), &table_name)?;
+        (schema_name, table)
+    };
+    let rows = {
+        let table = table.read().unwrap();
+        insert_rows(&table, columns, values)?
+    };
+    let row_count = rows.len();
+    transaction.insert(&schema_name, &table_name, table, rows)?;
+    Ok(row_count)
+}
+
+// rows of INSERT ... VALUES with the values in the order of the columns of the table
+fn insert_rows(table: &Table, columns: &[Ident], values: &Values) -> Result<Vec<Vec<Value>>, CommandError> {
+    // position of each value of the statement in the table row (all columns in order if no column list is given)
+    let column_ids = if columns.is_empty() {
+        (0..table.column_count()).collect()
+    } else {
+        let mut column_ids = Vec::new();
+        for column in columns {
+            let name = normalize_identifier(column);
+            match table.column_id(&name) {
+                Some(column_id) => column_ids.push(column_id),
+                None => return Err(CommandError::UnknownColumn(name))
+            }
+        }
+        column_ids
+    };
+
+    let mut rows = Vec::new();
+    for exprs in values.0.iter() {
+        if exprs.len() != column_ids.len() {
+            return Err(CommandError::Table(TableError::ColumnCountMismatch(column_ids.len(), exprs.len())))
+        }
+        let mut row = vec![Value::Null; table.column_count()];
+        for (expr, column_id) in exprs.iter().zip(column_ids.iter()) {
+            row[*column_id] = evaluate_constant(expr)?;
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+// returns the size of the index in bytes, None if it was not created because a relation with its name exists already and
+//  IF NOT EXISTS was given
+pub fn execute_create_index(db: &Database, transaction: &mut TransactionContext, name: &ObjectName, table_name: &ObjectName, columns: &[OrderByExpr], unique: bool, if_not_exists: bool) -> Result<Option<usize>, CommandError> {
+    if unique {
+        return Err(CommandError::NotSupported("UNIQUE indexes"))
+    }
+    let column_name = match columns {
+        [OrderByExpr { expr: Expr::Identifier(ident), .. }] => normalize_identifier(ident),
+        [_] => return Err(CommandError::NotSupported("indexes on expressions")),
+        _ => return Err(CommandError::NotSupported("indexes on multiple columns"))
+    };
+    // indexes are always created in the schema of their table
+    let index_name = match split_object_name(name)? {
+        (None, index_name) => index_name,
+        (Some(_), _) => return Err(CommandError::NotSupported("schema-qualified index names"))
+    };
+    let (schema_name, table_name) = split_object_name(table_name)?;
+    let column_id = {
+        let table = db.catalog.read().unwrap().get_table(schema_name.as_deref(), &table_name)?;
+        let table = table.read().unwrap();
+        table.column_id(&column_name).ok_or(CommandError::UnknownColumn(column_name.clone()))?
+    };
+    transaction.begin_change();
+    let (schema_name, table) = match db.catalog.write().unwrap().create_index(schema_name.as_deref(), &table_name, &index_name) {
+        Ok(result) => result,
+        Err(CatalogError::DuplicateTable(_)) if if_not_exists => return Ok(None),
+        Err(err) => return Err(err.into())
+    };
+    let mut table = table.write().unwrap();
+    if let Err(err) = table.create_index(&index_name, column_id) {
+        // the name of the index is released again (the table is unlocked first, as the catalog is locked before its tables)
+        drop(table);
+        let _ = db.catalog.write().unwrap().drop_index(Some(&schema_name), &index_name);
+        return Err(CommandError::Read(err))
+    }
+    transaction.log_create_index(&schema_name, &table_name, &index_name, &column_name);
+    Ok(Some(table.index_memory_usage(table.indexes().len() - 1)))
+}
+
+// all indexes are checked before the first one is dropped, so either all or none of them are dropped
+pub fn execute_drop_index(db: &Database, transaction: &mut TransactionContext, names: &[ObjectName], if_exists: bool) -> Result<(), CommandError> {
+    transaction.begin_change();
+    let mut catalog = db.catalog.write().unwrap();
+    let mut indexes = Vec::new();
+    for name in names {
+        let (schema_name, index_name) = split_object_name(name)?;
+        match catalog.resolve_index_schema(schema_name.as_deref(), &index_name) {
+            Ok(schema_name) => indexes.push((schema_name, index_name)),
+            Err(CatalogError::UnknownIndex(_)) if if_exists => {},
+            Err(err) => return Err(err.into())
+        }
+    }
+    for (schema_name, index_name) in indexes {
+        // the same index may be named twice
+        if let Ok((_, table_name, table)) = catalog.drop_index(Some(&schema_name), &index_name) {
+            let mut table = table.write().unwrap();
+            let column_id = table.indexes()[table.index_id(&index_name).unwrap()].column_id;
+            table.drop_index(&index_name);
+            transaction.log_drop_index(&schema_name, &table_name, &index_name, column_id);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlparser::dialect::GenericDialect;
+    use sqlparser::parser::Parser;
+
+    use super::*;
+
+    fn select(sql: &str) -> Result<Option<ConstantSelectResult>, CommandError> {
+        match Parser::parse_sql(&GenericDialect {}, sql).unwrap().pop().unwrap() {
+            Statement::Query(query) => execute_constant_select(&query),
+            statement => panic!("not a query: {}", statement)
+        }
+    }
+
+    fn select_value(sql: &str) -> Value {
+        let (_, mut row) = select(sql).unwrap().expect("not a constant SELECT");
+        row.pop().unwrap()
+    }
+
+    #[test]
+    fn select_arithmetic() {
+        let (columns, row) = select("SELECT 1 + 2").unwrap().unwrap();
+        assert_eq!(columns, vec![(String::from("?column?"), DataType::BigInt)]);
+        assert_eq!(row, vec![Value::BigInt(3)]);
+        assert_eq!(select_value("SELECT 7 / 2 - -1 * 3 % 2"), Value::BigInt(4));
+        assert_eq!(select_value("SELECT 1.5 * 2"), Value::Decimal(Decimal::parse("3.0").unwrap()));
+        assert_eq!(select_value("SELECT 1 / 3.0").to_string(), "0.333333");
+        assert_eq!(select_value("SELECT 1 + CAST(0.5 AS REAL)"), Value::Double(1.5));
+        assert_eq!(select_value("SELECT DATE '1998-12-01' - 90").to_string(), "1998-09-02");
+        assert_eq!(select_value("SELECT DATE '1998-12-01' - DATE '1998-11-30'"), Value::BigInt(1));
+        assert_eq!(select_value("SELECT +2 * NULL"), Value::Null);
+    }
+
+    #[test]
+    fn select_arithmetic_errors() {
+        assert_eq!(select("SELECT 1 / 0").unwrap_err().sqlstate(), "22012");
+        assert_eq!(select("SELECT 1.0 % 0").unwrap_err().sqlstate(), "22012");
+        assert_eq!(select("SELECT 9223372036854775807 + 1").unwrap_err().sqlstate(), "22003");
+        assert_eq!(select("SELECT 'a' + 1").unwrap_err().sqlstate(), "42883");
+    }
+
+    #[test]
+    fn decimals_whose_scales_cannot_be_aligned() {
+        assert_eq!(select_value("SELECT 10000000000000000000 = 0.00000000000000000000000000000000000001"), Value::Boolean(false));
+        assert_eq!(select_value("SELECT 10000000000000000000 > 0.00000000000000000000000000000000000001"), Value::Boolean(true));
+        assert_eq!(select_value("SELECT -0.00000000000000000000000000000000000002 < 10000000000000000000.5"), Value::Boolean(true));
+        assert_eq!(select_value("SELECT 0.00000000000000000000000000000000000001 < 0.00000000000000000000000000000000000002"), Value::Boolean(true));
+        assert_eq!(select("SELECT 10000000000000000000 + 0.00000000000000000000000000000000000001").unwrap_err().sqlstate(), "22003");
+        assert_eq!(select("SELECT 10000000000000000000 - 0.00000000000000000000000000000000000001").unwrap_err().sqlstate(), "22003");
+        assert_eq!(select("SELECT 100000000000000000000 * 100000000000000000000").unwrap_err().sqlstate(), "22003");
+    }
+
+    #[test]
+    fn select_case_and_cast() {
+        assert_eq!(select_value("SELECT CASE WHEN 1 > 2 THEN 'a' WHEN NULL THEN 'b' ELSE 'c' END"), Value::Text(String::from("c")));
+        assert_eq!(select_value("SELECT CASE 2 WHEN 1 THEN 'a' WHEN 2 THEN 'b' END"), Value::Text(String::from("b")));
+        assert_eq!(select_value("SELECT CASE NULL WHEN NULL THEN 1 END"), Value::Null);
+        // the results of branches that are not taken are not evaluated
+        assert_eq!(select_value("SELECT CASE WHEN true THEN 1 ELSE 1 / 0 END"), Value::BigInt(1));
+        assert_eq!(select_value("SELECT CAST(2.5 AS BIGINT)"), Value::BigInt(3));
+        assert_eq!(select_value("SELECT CAST('12' AS BIGINT) + 1"), Value::BigInt(13));
+        assert_eq!(select_value("SELECT CAST(1.005 AS DECIMAL(10, 2))").to_string(), "1.01");
+        assert_eq!(select_value("SELECT CAST(1 AS TEXT)"), Value::Text(String::from("1")));
+        assert_eq!(select_value("SELECT CAST('1998-12-01' AS DATE) + 1").to_string(), "1998-12-02");
+        assert_eq!(select("SELECT CAST('x' AS BIGINT)").unwrap_err().sqlstate(), "22P02");
+    }
+
+    #[test]
+    fn column_references_are_not_constant() {
+        assert!(select("SELECT a + 1").unwrap().is_none());
+        assert!(select("SELECT CASE WHEN a THEN 1 END").unwrap().is_none());
+    }
+}
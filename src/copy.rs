@@ -0,0 +1,383 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::sync::{Arc, RwLock};
+
+use sqlparser::ast::{Expr, Ident, ObjectName, Query, SelectItem, SetExpr, TableFactor};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::dialect::keywords::Keyword;
+use sqlparser::parser::{IsOptional, Parser, ParserError};
+use sqlparser::tokenizer::{Token, Tokenizer};
+
+use crate::commands::{execute_constant_select, normalize_identifier, split_object_name, CommandError};
+use crate::core::{ColumnDefinition, ColumnId, Database, Table, Value};
+use crate::delimited::{DelimitedFormat, Format};
+use crate::loader::load_table;
+use crate::threadpool::ThreadPool;
+use crate::transaction::TransactionContext;
+
+// COPY between tables and files
+//  the SQL parser only knows COPY ... FROM STDIN followed by inline data, so COPY statements are parsed here
+
+#[derive(Debug)]
+pub enum CopyRelation {
+    // all columns if the column list is empty
+    Table { name: ObjectName, columns: Vec<Ident> },
+    // only for COPY ... TO
+    Query(Box<Query>)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CopyDirection {
+    From,
+    To
+}
+
+#[derive(Debug)]
+pub enum CopyLocation {
+    // path on the server
+    File(String),
+    // STDIN or STDOUT, i.e., the data is transferred over the client connection
+    Client
+}
+
+#[derive(Debug)]
+pub struct CopyStatement {
+    pub relation: CopyRelation,
+    pub direction: CopyDirection,
+    pub location: CopyLocation,
+    pub format: DelimitedFormat
+}
+
+fn expected<T>(expected: &str, found: Token) -> Result<T, ParserError> {
+    Err(ParserError::ParserError(format!("Expected {}, found: {}", expected, found)))
+}
+
+// parses a COPY statement, None if the SQL is not a COPY statement (and thus left to the SQL parser)
+//  COPY { table [ ( column [, ...] ) ] | ( query ) } { FROM | TO } { 'file' | STDIN | STDOUT } [ [ WITH ] ( option [, ...] ) ]
+pub fn parse_copy(sql: &str) -> Result<Option<CopyStatement>, ParserError> {
+    let dialect = GenericDialect {};
+    let tokens = Tokenizer::new(&dialect, sql).tokenize()?;
+    let mut parser = Parser::new(tokens, &dialect);
+    if !parser.parse_keyword(Keyword::COPY) {
+        return Ok(None)
+    }
+    let relation = if parser.consume_token(&Token::LParen) {
+        let query = parser.parse_query()?;
+        parser.expect_token(&Token::RParen)?;
+        CopyRelation::Query(Box::new(query))
+    } else {
+        let name = parser.parse_object_name()?;
+        let columns = parser.parse_parenthesized_column_list(IsOptional::Optional)?;
+        CopyRelation::Table { name, columns }
+    };
+    let direction = match parser.expect_one_of_keywords(&[Keyword::FROM, Keyword::TO])? {
+        Keyword::FROM => CopyDirection::From,
+        _ => CopyDirection::To
+    };
+    if let (CopyRelation::Query(_), CopyDirection::From) = (&relation, &direction) {
+        return Err(ParserError::ParserError(String::from("COPY (query) FROM is not supported")))
+    }
+    let location = match parser.next_token() {
+        Token::SingleQuotedString(path) => CopyLocation::File(path),
+        Token::Word(word) if direction == CopyDirection::From && word.keyword == Keyword::STDIN => CopyLocation::Client,
+        Token::Word(word) if direction == CopyDirection::To && word.value.eq_ignore_ascii_case("stdout") => CopyLocation::Client,
+        token => return expected(if direction == CopyDirection::From { "file name or STDIN" } else { "file name or STDOUT" }, token)
+    };
+    let format = parse_copy_options(&mut parser)?;
+    while parser.consume_token(&Token::SemiColon) {}
+    if parser.peek_token() != Token::EOF {
+        return expected("end of statement", parser.peek_token())
+    }
+    Ok(Some(CopyStatement { relation, direction, location, format }))
+}
+
+// option values may be strings, numbers or words
+fn parse_option_value(parser: &mut Parser) -> Result<String, ParserError> {
+    match parser.next_token() {
+        Token::SingleQuotedString(value) | Token::Number(value, _) => Ok(value),
+        Token::Word(word) => Ok(word.value),
+        token => expected("option value", token)
+    }
+}
+
+// options in parentheses (e.g., WITH (FORMAT csv, HEADER)) or in the syntax before PostgreSQL 9.0 (e.g., WITH CSV HEADER)
+fn parse_copy_options(parser: &mut Parser) -> Result<DelimitedFormat, ParserError> {
+    // WITH is optional
+    let _ = parser.parse_keyword(Keyword::WITH);
+    let mut options: Vec<(String, Option<String>)> = Vec::new();
+    if parser.consume_token(&Token::LParen) {
+        loop {
+            let name = match parser.next_token() {
+                Token::Word(word) => word.value.to_lowercase(),
+                token => return expected("option name", token)
+            };
+            let value = match parser.peek_token() {
+                Token::Comma | Token::RParen => None,
+                _ => Some(parse_option_value(parser)?)
+            };
+            options.push((name, value));
+            if !parser.consume_token(&Token::Comma) {
+                break
+            }
+        }
+        parser.expect_token(&Token::RParen)?;
+    } else {
+        while let Token::Word(word) = parser.peek_token() {
+            let name = word.value.to_lowercase();
+            match name.as_str() {
+                "csv" | "binary" => options.push((String::from("format"), Some(name))),
+                "header" => options.push((name, None)),
+                "delimiter" | "null" | "quote" | "escape" => {
+                    parser.next_token();
+                    let _ = parser.parse_keyword(Keyword::AS);
+                    options.push((name, Some(parser.parse_literal_string()?)));
+                    continue
+                },
+                _ => break
+            }
+            parser.next_token();
+        }
+    }
+
+    // the format determines the defaults of the other options
+    let mut format = DelimitedFormat::text();
+    for (name, value) in options.iter() {
+        if name == "format" {
+            format = match value.as_deref().map(|value| value.to_lowercase()).as_deref() {
+                Some("text") => DelimitedFormat::text(),
+                Some("csv") => DelimitedFormat::csv(),
+                _ => return Err(ParserError::ParserError(format!("COPY format \"{}\" not recognized", value.as_deref().unwrap_or(""))))
+            };
+        }
+    }
+    let mut escape = None;
+    for (name, value) in options {
+        match (name.as_str(), value) {
+            ("format", _) => {},
+            ("header", None) => format.header = true,
+            ("header", Some(value)) => {
+                format.header = match value.to_lowercase().as_str() {
+                    "true" | "on" | "1" => true,
+                    "false" | "off" | "0" => false,
+                    _ => return Err(ParserError::ParserError(String::from("header requires a Boolean value")))
+                };
+            },
+            (_, None) => return Err(ParserError::ParserError(format!("option \"{}\" requires a value", name))),
+            ("delimiter", Some(value)) => format.delimiter = single_byte(&value, "delimiter")?,
+            ("null", Some(value)) => format.null = value,
+            ("quote", Some(value)) if format.format == Format::Csv => format.quote = single_byte(&value, "quote")?,
+            ("escape", Some(value)) if format.format == Format::Csv => escape = Some(single_byte(&value, "escape")?),
+            ("quote", _) | ("escape", _) => return Err(ParserError::ParserError(format!("COPY {} available only in CSV mode", name))),
+            _ => return Err(ParserError::ParserError(format!("option \"{}\" not recognized", name)))
+        }
+    }
+    // the escape character defaults to the quote character
+    format.escape = escape.unwrap_or(format.quote);
+    Ok(format)
+}
+
+fn single_byte(value: &str, option: &str) -> Result<u8, ParserError> {
+    match value.as_bytes() {
+        [byte] => Ok(*byte),
+        _ => Err(ParserError::ParserError(format!("COPY {} must be a single one-byte character", option)))
+    }
+}
+
+// ids of the named columns (all columns if no names are given)
+fn resolve_columns(definitions: &[ColumnDefinition], names: &[Ident]) -> Result<Vec<ColumnId>, CommandError> {
+    if names.is_empty() {
+        return Ok((0..definitions.len()).collect())
+    }
+    let mut column_ids = Vec::new();
+    for name in names {
+        let name = normalize_identifier(name);
+        match definitions.iter().position(|column| column.name == name) {
+            Some(column_id) => column_ids.push(column_id),
+            None => return Err(CommandError::UnknownColumn(name))
+        }
+    }
+    Ok(column_ids)
+}
+
+// SELECT * or a list of columns from a single table, without any other clauses, is exported like the table itself
+fn as_table_select(query: &Query) -> Option<(&ObjectName, Vec<Ident>)> {
+    if query.with.is_some() || !query.order_by.is_empty() || query.limit.is_some() || query.offset.is_some() || query.fetch.is_some() {
+        return None
+    }
+    let select = match &query.body {
+        SetExpr::Select(select) => select,
+        _ => return None
+    };
+    if select.distinct || select.top.is_some() || select.from.len() != 1 || !select.from[0].joins.is_empty() || select.selection.is_some()
+        || !select.group_by.is_empty() || select.having.is_some() {
+        return None
+    }
+    let name = match &select.from[0].relation {
+        TableFactor::Table { name, alias: None, args, with_hints } if args.is_empty() && with_hints.is_empty() => name,
+        _ => return None
+    };
+    let mut columns = Vec::new();
+    for item in select.projection.iter() {
+        match item {
+            SelectItem::Wildcard if select.projection.len() == 1 => {},
+            SelectItem::UnnamedExpr(Expr::Identifier(ident)) => columns.push(ident.clone()),
+            _ => return None
+        }
+    }
+    Some((name, columns))
+}
+
+// size of the output buffer that is written at once
+const WRITE_BUFFER_SIZE: usize = 1 << 16;
+
+// the rows exported by COPY ... TO
+enum Export {
+    Table { table: Arc<RwLock<Table>>, column_ids: Vec<ColumnId> },
+    // result of a constant SELECT (column names and the row)
+    Row(Vec<String>, Vec<Value>)
+}
+
+// COPY ... TO, with the exported rows resolved before any data is written
+pub struct CopyOut {
+    export: Export,
+    format: DelimitedFormat
+}
+
+impl CopyOut {
+    pub fn column_count(&self) -> usize {
+        match &self.export {
+            Export::Table { column_ids, .. } => column_ids.len(),
+            Export::Row(names, _) => names.len()
+        }
+    }
+
+    // writes all rows to the output, returns the number of rows
+    pub fn execute<W: Write>(&self, output: &mut W) -> io::Result<usize> {
+        let mut buffer = Vec::new();
+        match &self.export {
+            Export::Table { table, column_ids } => {
+                let table = table.read().unwrap();
+                if self.format.header {
+                    let names: Vec<&str> = column_ids.iter().map(|column_id| table.column_definition(*column_id).name.as_str()).collect();
+                    self.format.write_header(&mut buffer, &names);
+                }
+                for chunk in table.chunks() {
+                    for offset in 0..chunk.len() {
+                        let row = column_ids.iter().map(|column_id| chunk.get_value(*column_id, offset)).collect::<io::Result<Vec<_>>>()?;
+                        self.format.write_record(&mut buffer, &row);
+                        if buffer.len() >= WRITE_BUFFER_SIZE {
+                            output.write_all(&buffer)?;
+                            buffer.clear();
+                        }
+                    }
+                }
+                output.write_all(&buffer)?;
+                Ok(table.row_count())
+            },
+            Export::Row(names, row) => {
+                if self.format.header {
+                    let names: Vec<&str> = names.iter().map(|name| name.as_str()).collect();
+                    self.format.write_header(&mut buffer, &names);
+                }
+                self.format.write_record(&mut buffer, row);
+                output.write_all(&buffer)?;
+                Ok(1)
+            }
+        }
+    }
+}
+
+// returns the schema name, table name and table
+fn resolve_table(db: &Database, name: &ObjectName) -> Result<(String, String, Arc<RwLock<Table>>), CommandError> {
+    let (schema_name, table_name) = split_object_name(name)?;
+    let catalog = db.catalog.read().unwrap();
+    let schema_name = catalog.resolve_table_schema(schema_name.as_deref(), &table_name)?;
+    let table = catalog.get_table(Some(&schema_name), &table_name)?;
+    Ok((schema_name, table_name, table))
+}
+
+pub fn prepare_copy_out(db: &Database, copy: &CopyStatement) -> Result<CopyOut, CommandError> {
+    let (name, columns) = match &copy.relation {
+        CopyRelation::Table { name, columns } => (name, columns.clone()),
+        CopyRelation::Query(query) => match as_table_select(query) {
+            Some(table_select) => table_select,
+            None => match execute_constant_select(query)? {
+                Some((fields, row)) => {
+                    let names = fields.into_iter().map(|(name, _)| name).collect();
+                    return Ok(CopyOut { export: Export::Row(names, row), format: copy.format.clone() })
+                },
+                None => return Err(CommandError::NotSupported("COPY (query) TO for queries other than constant SELECTs and SELECTs of columns of a single table"))
+            }
+        }
+    };
+    let (_, _, table) = resolve_table(db, name)?;
+    let column_ids = resolve_columns(table.read().unwrap().column_definitions(), &columns)?;
+    Ok(CopyOut { export: Export::Table { table, column_ids }, format: copy.format.clone() })
+}
+
+// COPY ... FROM, with the target table and columns resolved before any data is read
+pub struct CopyIn {
+    schema_name: String,
+    table_name: String,
+    table: Arc<RwLock<Table>>,
+    fields: Vec<ColumnId>,
+    format: DelimitedFormat
+}
+
+impl CopyIn {
+    pub fn column_count(&self) -> usize {
+        self.fields.len()
+    }
+
+    // loads the input into new chunks, which are appended to the table when the transaction commits (so either all or no
+    //  rows are added), returns the number of rows
+    //  source names the input in error messages
+    pub fn execute<R: BufRead>(self, pool: &ThreadPool, transaction: &mut TransactionContext, input: R, source: &str) -> Result<usize, CommandError> {
+        let (definitions, chunk_size) = {
+            let table = self.table.read().unwrap();
+            (table.column_definitions().clone(), table.target_chunk_size())
+        };
+        let (loaded, errors) = load_table(pool, input, definitions, Some(self.fields), &self.format, chunk_size)
+            .map_err(|err| CommandError::File(String::from(source), err))?;
+        if let Some(error) = errors.into_iter().next() {
+            return Err(CommandError::Copy(self.table_name, error))
+        }
+        let row_count = loaded.row_count();
+        transaction.append_chunks(&self.schema_name, &self.table_name, self.table, loaded.into_chunks()).map_err(CommandError::Read)?;
+        Ok(row_count)
+    }
+}
+
+pub fn prepare_copy_in(db: &Database, copy: &CopyStatement) -> Result<CopyIn, CommandError> {
+    let (name, columns) = match &copy.relation {
+        CopyRelation::Table { name, columns } => (name, columns),
+        CopyRelation::Query(_) => return Err(CommandError::NotSupported("COPY (query) FROM"))
+    };
+    let (schema_name, table_name, table) = resolve_table(db, name)?;
+    let fields = resolve_columns(table.read().unwrap().column_definitions(), columns)?;
+    Ok(CopyIn { schema_name, table_name, table, fields, format: copy.format.clone() })
+}
+
+// COPY from or to a file, returns the number of copied rows
+//  data transferred over the client connection (STDIN/STDOUT) is handled by the protocol using prepare_copy_in/prepare_copy_out
+pub fn execute_copy(db: &Database, transaction: &mut TransactionContext, copy: &CopyStatement) -> Result<usize, CommandError> {
+    let path = match &copy.location {
+        CopyLocation::File(path) => path,
+        CopyLocation::Client => return Err(CommandError::NotSupported("COPY FROM STDIN and COPY TO STDOUT without a client connection"))
+    };
+    let file_error = |err| CommandError::File(path.clone(), err);
+    match copy.direction {
+        CopyDirection::From => {
+            let copy_in = prepare_copy_in(db, copy)?;
+            let file = File::open(path).map_err(file_error)?;
+            copy_in.execute(&db.loader_pool, transaction, BufReader::new(file), path)
+        },
+        CopyDirection::To => {
+            let copy_out = prepare_copy_out(db, copy)?;
+            let mut output = BufWriter::new(File::create(path).map_err(file_error)?);
+            let row_count = copy_out.execute(&mut output).map_err(file_error)?;
+            output.flush().map_err(file_error)?;
+            Ok(row_count)
+        }
+    }
+}
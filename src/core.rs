@@ -1,11 +1,64 @@
 mod avc;
+mod bit_packed_vector;
+mod buffer;
+mod catalog;
+mod checksum;
+mod column_file;
 mod database;
+mod date;
+mod decimal;
+mod delta;
+mod encoding;
+mod frame_of_reference_avc;
+mod front_coded_dict;
+mod histogram;
+mod index;
+mod lz4;
+mod lz4_avc;
+mod run_length_avc;
+mod scan;
+mod statistics;
+mod table;
+mod unencoded_avc;
+mod value;
 
 pub use avc::Dict;
 pub use avc::FixedSizeDict;
-pub use avc::ValueId;
 pub use avc::DynAttributeValueContainer;
 pub use avc::AttributeValueContainer;
 pub use avc::MainAttributeValueContainer;
 
-pub use database::Database;
\ No newline at end of file
+pub use encoding::encode_column;
+
+pub use checksum::crc32c;
+
+pub use column_file::read_table;
+pub use column_file::write_table;
+pub use column_file::COLUMN_FILE_EXTENSION;
+
+pub use catalog::Catalog;
+pub use catalog::CatalogError;
+
+pub use histogram::analyze_column;
+pub use histogram::HISTOGRAM_BUCKETS;
+
+pub use scan::ScanPredicate;
+
+pub use table::Chunk;
+pub use table::ColumnDefinition;
+pub use table::ColumnId;
+pub use table::DataType;
+pub use table::DEFAULT_CHUNK_SIZE;
+pub use table::Table;
+pub use table::TableError;
+pub use table::merge_chunk;
+
+pub use value::Value;
+pub use value::ValueType;
+
+pub use date::Date;
+pub use decimal::Decimal;
+pub use decimal::MAX_DECIMAL_PRECISION;
+pub use decimal::MIN_DIVISION_SCALE;
+
+pub use database::Database;
@@ -1,4 +1,14 @@
 use std::any::Any;
+use std::fmt;
+use std::io;
+use std::ops::Range;
+
+use super::bit_packed_vector::{bits_needed, BitPackedVector};
+use super::buffer::Buffer;
+use super::column_file::{invalid_data, SegmentReader, SegmentWriter};
+use super::scan::{scan_value_id_range, ChunkOffset, ScanPredicate};
+use super::statistics::ColumnStatistics;
+use super::value::{Value, ValueType};
 
 pub type ValueId = u32;
 
@@ -6,20 +16,78 @@ pub trait DynAttributeValueContainer {
     fn as_any(&self) -> &dyn Any;
     fn len(&self) -> usize;
     fn distinct_count(&self) -> usize;
-    fn null_value_id(&self) -> ValueId;
+    // type-independent read access, e.g., for sending values to a client
+    //  reading fails if the values cannot be decoded (e.g., a compressed block of a column file, see Lz4AttributeValueContainer)
+    fn get_value(&self, i: usize) -> io::Result<Value>;
+    fn encoding(&self) -> Encoding;
+    // approximate size of the container in bytes
+    fn memory_usage(&self) -> usize;
+    // computed from the values, which may be expensive depending on the encoding (see Chunk::statistics)
+    fn statistics(&self) -> io::Result<ColumnStatistics>;
+}
+
+// physical layout of an attribute value container
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    // sorted dictionary with bit-packed value ids (main store)
+    Dictionary,
+    // append-only dictionary (delta store)
+    UnsortedDictionary,
+    Unencoded,
+    RunLength,
+    FrameOfReference,
+    Lz4
 }
 
-pub trait AttributeValueContainer<T>: DynAttributeValueContainer {
-    fn lookup(&self, i: usize) -> Option<T>;
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Encoding::Dictionary => write!(f, "Dictionary"),
+            Encoding::UnsortedDictionary => write!(f, "UnsortedDictionary"),
+            Encoding::Unencoded => write!(f, "Unencoded"),
+            Encoding::RunLength => write!(f, "RunLength"),
+            Encoding::FrameOfReference => write!(f, "FrameOfReference"),
+            Encoding::Lz4 => write!(f, "LZ4")
+        }
+    }
 }
 
+pub trait AttributeValueContainer<T: PartialOrd>: DynAttributeValueContainer {
+    fn lookup(&self, i: usize) -> io::Result<Option<T>>;
+
+    // positions of all rows matching the predicate
+    //  by default, each value is decoded and compared, dictionary-encoded containers compare value ids instead
+    #[allow(dead_code)]
+    fn scan(&self, predicate: &ScanPredicate<T>) -> io::Result<Vec<ChunkOffset>> {
+        let mut result = Vec::new();
+        for i in 0..self.len() {
+            if predicate.matches(self.lookup(i)?.as_ref()) {
+                result.push(i as ChunkOffset);
+            }
+        }
+        Ok(result)
+    }
+}
+
+// sorted dictionary: value ids are ordered like the values, so comparisons of values can be done on value ids
 pub trait Dict<T> {
     fn lookup(&self, i: ValueId) -> T;
     fn len(&self) -> usize;
+    // approximate size of the dictionary in bytes
+    fn memory_usage(&self) -> usize;
+    // value id of the first entry that is not less than value (len() if there is none)
+    #[allow(dead_code)]
+    fn lower_bound(&self, value: &T) -> ValueId;
+    // value id of the first entry that is greater than value (len() if there is none)
+    #[allow(dead_code)]
+    fn upper_bound(&self, value: &T) -> ValueId;
+    #[allow(dead_code)]
+    fn find(&self, value: &T) -> Option<ValueId>;
 }
 
 pub struct FixedSizeDict<T: Copy + PartialOrd + Sized + Send + Sync> {
-    pub entries: Vec<T>
+    pub entries: Buffer<T>
 }
 
 impl<T: Copy + PartialOrd + Sized + Send + Sync> Dict<T> for FixedSizeDict<T> {
@@ -30,16 +98,37 @@ impl<T: Copy + PartialOrd + Sized + Send + Sync> Dict<T> for FixedSizeDict<T> {
     fn len(&self) -> usize {
         self.entries.len()
     }
+
+    fn memory_usage(&self) -> usize {
+        self.entries.len() * std::mem::size_of::<T>()
+    }
+
+    fn lower_bound(&self, value: &T) -> ValueId {
+        self.entries.partition_point(|entry| entry < value) as ValueId
+    }
+
+    fn upper_bound(&self, value: &T) -> ValueId {
+        self.entries.partition_point(|entry| entry <= value) as ValueId
+    }
+
+    fn find(&self, value: &T) -> Option<ValueId> {
+        let vid = self.lower_bound(value);
+        if (vid as usize) < self.entries.len() && self.entries[vid as usize] == *value {
+            Some(vid)
+        } else {
+            None
+        }
+    }
 }
 
 pub struct MainAttributeValueContainer<T> {
-    pub data: Vec<ValueId>,
+    pub data: BitPackedVector,
     pub dict: Box<dyn Dict<T> + Send + Sync>
 }
 
 impl<T> DynAttributeValueContainer for MainAttributeValueContainer<T>
 where
-    T: 'static
+    T: ValueType
 {
     fn as_any(&self) -> &dyn Any {
         self
@@ -53,23 +142,133 @@ where
         self.dict.len()
     }
 
-    fn null_value_id(&self) -> ValueId {
-        self.dict.len() as ValueId
+    fn get_value(&self, i: usize) -> io::Result<Value> {
+        Ok(match self.lookup(i)? {
+            Some(val) => val.into_value(),
+            None => Value::Null
+        })
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding::Dictionary
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.data.memory_usage() + self.dict.memory_usage()
+    }
+
+    // min and max are the first and last dictionary entries
+    fn statistics(&self) -> io::Result<ColumnStatistics> {
+        let null_value_id = self.null_value_id();
+        let entry = |vid: Option<ValueId>| vid.map_or(Value::Null, |vid| self.dict.lookup(vid).into_value());
+        Ok(ColumnStatistics {
+            min: entry(if null_value_id > 0 { Some(0) } else { None }),
+            max: entry(null_value_id.checked_sub(1)),
+            null_count: self.scan_value_id_range(null_value_id..null_value_id + 1).len(),
+            distinct_count: self.dict.len()
+        })
     }
 }
 
 impl<T> AttributeValueContainer<T> for MainAttributeValueContainer<T>
 where
-    T: 'static
+    T: ValueType
 {
-    fn lookup(&self, i: usize) -> Option<T> {
-        let vid = self.data[i];
-        if vid == self.null_value_id() {
-            None
-        } else if vid < self.null_value_id() {
+    fn lookup(&self, i: usize) -> io::Result<Option<T>> {
+        // value ids are checked when the container is created, so everything but a dictionary entry is NULL
+        let vid = self.data.get(i);
+        Ok(if vid < self.null_value_id() {
             Some(self.dict.lookup(vid))
         } else {
-            panic!("Invalid value id")
+            None
+        })
+    }
+
+    fn scan(&self, predicate: &ScanPredicate<T>) -> io::Result<Vec<ChunkOffset>> {
+        Ok(scan_value_id_range(&self.data, self.value_id_range(predicate)))
+    }
+}
+
+impl<T: ValueType> MainAttributeValueContainer<T> {
+    pub fn new() -> Self {
+        MainAttributeValueContainer { data: BitPackedVector::new(0), dict: T::create_dict(Vec::new()) }
+    }
+
+    pub fn null_value_id(&self) -> ValueId {
+        self.dict.len() as ValueId
+    }
+
+    // translates a predicate into the range of value ids matching it (see value_id_range)
+    #[allow(dead_code)]
+    pub fn value_id_range(&self, predicate: &ScanPredicate<T>) -> Range<ValueId> {
+        value_id_range(self.dict.as_ref(), predicate)
+    }
+
+    // positions of all rows whose value id is within the range
+    #[allow(dead_code)]
+    pub fn scan_value_id_range(&self, range: Range<ValueId>) -> Vec<ChunkOffset> {
+        scan_value_id_range(&self.data, range)
+    }
+
+    // bit-packs the value ids, using as many bits as needed for the NULL value id of the dictionary
+    pub fn from_value_ids(data: &[ValueId], dict: Box<dyn Dict<T> + Send + Sync>) -> Self {
+        let null_value_id = dict.len() as ValueId;
+        assert!(data.iter().all(|vid| *vid <= null_value_id), "Invalid value id");
+        MainAttributeValueContainer { data: BitPackedVector::from_value_ids(data, null_value_id), dict }
+    }
+
+    pub fn write(&self, writer: &mut SegmentWriter) {
+        T::write_dict(self.dict.as_ref(), writer);
+        self.data.write(writer);
+    }
+
+    pub fn read(reader: &mut SegmentReader) -> io::Result<Self> {
+        let dict = T::read_dict(reader)?;
+        let data = BitPackedVector::read(reader)?;
+        if data.bits() != bits_needed(dict.len() as ValueId) {
+            return Err(invalid_data("attribute vector does not match the dictionary"))
+        }
+        Ok(MainAttributeValueContainer { data, dict })
+    }
+}
+
+// translates a predicate into the range of value ids of a sorted dictionary matching it (NULLs only match IS NULL, as the
+//  NULL value id is larger than all other value ids)
+pub fn value_id_range<T>(dict: &dyn Dict<T>, predicate: &ScanPredicate<T>) -> Range<ValueId> {
+    let null_value_id = dict.len() as ValueId;
+    match predicate {
+        ScanPredicate::Equals(value) => {
+            match dict.find(value) {
+                Some(vid) => vid..vid + 1,
+                None => 0..0
+            }
+        },
+        ScanPredicate::LessThan(value) => 0..dict.lower_bound(value),
+        ScanPredicate::LessThanEquals(value) => 0..dict.upper_bound(value),
+        ScanPredicate::GreaterThan(value) => dict.upper_bound(value)..null_value_id,
+        ScanPredicate::GreaterThanEquals(value) => dict.lower_bound(value)..null_value_id,
+        ScanPredicate::Between(low, high) => dict.lower_bound(low)..dict.upper_bound(high),
+        ScanPredicate::IsNull => null_value_id..null_value_id + 1,
+        ScanPredicate::IsNotNull => 0..null_value_id
+    }
+}
+
+// domain encoding of a column: creates a sorted dictionary of the distinct values and replaces each value by its index in the dictionary
+//  NULLs are replaced by the NULL value id, which is the size of the dictionary
+pub fn create_avc<T: ValueType>(column: &[Option<T>]) -> MainAttributeValueContainer<T> {
+    let mut column_with_indices: Vec<(usize, &T)> = column.iter().enumerate().filter_map(|(i, val)| val.as_ref().map(|val| (i, val))).collect();
+    column_with_indices.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mut dict: Vec<T> = Vec::new();
+    let mut dv: Vec<ValueId> = vec![ValueId::MAX; column.len()];
+    for (i, val) in column_with_indices {
+        if dict.last() != Some(val) {
+            dict.push(val.clone());
         }
+        dv[i] = (dict.len() - 1) as ValueId;
+    }
+    let null_value_id = dict.len() as ValueId;
+    for vid in dv.iter_mut().filter(|vid| **vid == ValueId::MAX) {
+        *vid = null_value_id;
     }
-}
\ No newline at end of file
+    MainAttributeValueContainer::from_value_ids(&dv, T::create_dict(dict))
+}
@@ -0,0 +1,175 @@
+use std::io;
+
+use super::avc::ValueId;
+use super::buffer::Buffer;
+use super::column_file::{invalid_data, SegmentReader, SegmentWriter};
+
+// attribute vector storing each value id with the minimum number of bits needed for the largest value id
+//  (i.e., ceil(log2(distinct_count + 1)) bits, as the NULL value id equals the number of distinct values)
+//  value ids are packed into 64 bit words starting at the least significant bit and may span two words
+pub struct BitPackedVector {
+    // one additional word at the end (and at least two words), so that reading the (possibly unused) second word of a value is always valid
+    words: Buffer<u64>,
+    bits: u32,
+    len: usize
+}
+
+// number of bits needed to store all value ids up to (and including) max_value_id
+pub fn bits_needed(max_value_id: ValueId) -> u32 {
+    32 - max_value_id.leading_zeros()
+}
+
+impl BitPackedVector {
+    pub fn new(bits: u32) -> Self {
+        assert!(bits <= 32, "Value ids have at most 32 bits");
+        BitPackedVector { words: vec![0, 0].into(), bits, len: 0 }
+    }
+
+    pub fn from_value_ids(value_ids: &[ValueId], max_value_id: ValueId) -> Self {
+        let bits = bits_needed(max_value_id);
+        let mut result = BitPackedVector::new(bits);
+        result.words.make_mut().reserve((value_ids.len() * bits as usize).div_ceil(64));
+        for vid in value_ids {
+            result.push(*vid);
+        }
+        result.words.make_mut().shrink_to_fit();
+        result
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    fn mask(&self) -> u64 {
+        (1u64 << self.bits) - 1
+    }
+
+    pub fn push(&mut self, vid: ValueId) {
+        debug_assert!(self.bits == 32 || (vid as u64) >> self.bits == 0, "Value id does not fit into the bit width");
+        let bit_pos = self.len * self.bits as usize;
+        let word = bit_pos / 64;
+        let offset = bit_pos % 64;
+        let words = self.words.make_mut();
+        // the last word is always the padding word, which becomes part of the data now
+        words[word] |= (vid as u64) << offset;
+        if offset + self.bits as usize > 64 {
+            words[word + 1] |= (vid as u64) >> (64 - offset);
+        }
+        self.len += 1;
+        let needed_words = (self.len * self.bits as usize).div_ceil(64) + 1;
+        if words.len() < needed_words {
+            words.push(0);
+        }
+    }
+
+    // random access to the value id at position i
+    pub fn get(&self, i: usize) -> ValueId {
+        assert!(i < self.len, "Index out of bounds");
+        let bit_pos = i * self.bits as usize;
+        let word = bit_pos / 64;
+        let offset = bit_pos % 64;
+        // read both words the value may span at once, avoiding a branch
+        let window = self.words[word] as u128 | (self.words[word + 1] as u128) << 64;
+        ((window >> offset) as u64 & self.mask()) as ValueId
+    }
+
+    // sequential decoding, starting at position 0
+    pub fn iter(&self) -> BitPackedIter<'_> {
+        BitPackedIter { vector: self, pos: 0, next_word: 0, buffer: 0, buffered_bits: 0 }
+    }
+
+    // decodes the value ids at positions [start, start + out.len()) into out
+    #[allow(dead_code)]
+    pub fn unpack(&self, start: usize, out: &mut [ValueId]) {
+        assert!(start + out.len() <= self.len, "Index out of bounds");
+        let mut iter = self.iter_from(start);
+        for vid in out.iter_mut() {
+            *vid = iter.next().unwrap();
+        }
+    }
+
+    fn iter_from(&self, start: usize) -> BitPackedIter<'_> {
+        let bit_pos = start * self.bits as usize;
+        let word = bit_pos / 64;
+        let offset = bit_pos % 64;
+        // the first word is consumed partially, the remaining bits stay in the buffer
+        BitPackedIter {
+            vector: self,
+            pos: start,
+            next_word: word + 1,
+            buffer: self.words[word] >> offset,
+            buffered_bits: 64 - offset as u32
+        }
+    }
+
+    // size of the packed data in bytes
+    pub fn memory_usage(&self) -> usize {
+        self.words.len() * std::mem::size_of::<u64>()
+    }
+
+    pub fn write(&self, writer: &mut SegmentWriter) {
+        writer.write_u32(self.bits);
+        writer.write_len(self.len);
+        writer.write_slice(&self.words);
+    }
+
+    // the words are used directly from the mapped file
+    pub fn read(reader: &mut SegmentReader) -> io::Result<Self> {
+        let bits = reader.read_u32()?;
+        let len = reader.read_len()?;
+        let words = reader.read_slice()?;
+        let needed_words = len.checked_mul(bits as usize).map(|bits| std::cmp::max(bits.div_ceil(64) + 1, 2));
+        if bits > 32 || needed_words != Some(words.len()) {
+            return Err(invalid_data("invalid bit-packed vector"))
+        }
+        Ok(BitPackedVector { words, bits, len })
+    }
+}
+
+// keeps the current word in a buffer so that each word is only read once
+pub struct BitPackedIter<'a> {
+    vector: &'a BitPackedVector,
+    pos: usize,
+    next_word: usize,
+    buffer: u64,
+    buffered_bits: u32
+}
+
+impl<'a> Iterator for BitPackedIter<'a> {
+    type Item = ValueId;
+
+    fn next(&mut self) -> Option<ValueId> {
+        if self.pos >= self.vector.len {
+            return None
+        }
+        self.pos += 1;
+        let bits = self.vector.bits;
+        let mask = self.vector.mask();
+        if self.buffered_bits >= bits {
+            let vid = self.buffer & mask;
+            self.buffer >>= bits;
+            self.buffered_bits -= bits;
+            Some(vid as ValueId)
+        } else {
+            // the value spans the rest of the buffer and the next word
+            let word = self.vector.words[self.next_word];
+            self.next_word += 1;
+            let vid = (self.buffer | word << self.buffered_bits) & mask;
+            let consumed = bits - self.buffered_bits;
+            self.buffer = word >> consumed;
+            self.buffered_bits = 64 - consumed;
+            Some(vid as ValueId)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.vector.len - self.pos;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for BitPackedIter<'a> {}
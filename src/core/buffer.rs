@@ -0,0 +1,151 @@
+use std::fs::File;
+use std::io;
+use std::ops::Deref;
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::sync::Arc;
+
+use super::date::Date;
+
+// read-only memory mapping of a whole file (see column_file)
+//  the mapping is shared by all containers using it and unmapped once the last of them is dropped
+//  on platforms without mmap, the file is read into memory instead
+pub struct MappedFile {
+    ptr: *mut u8,
+    len: usize,
+    // 8-byte aligned like a mapping, for the values read from the file
+    #[cfg(not(unix))]
+    _data: Vec<u64>
+}
+
+// the mapping is never written to
+unsafe impl Send for MappedFile {}
+unsafe impl Sync for MappedFile {}
+
+impl MappedFile {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+        if len == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "cannot map an empty file"))
+        }
+        Self::map(file, len)
+    }
+
+    #[cfg(unix)]
+    fn map(file: File, len: usize) -> io::Result<Self> {
+        // the file can be closed once it is mapped
+        let ptr = unsafe { libc::mmap(std::ptr::null_mut(), len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0) };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error())
+        }
+        Ok(MappedFile { ptr: ptr as *mut u8, len })
+    }
+
+    #[cfg(not(unix))]
+    fn map(mut file: File, len: usize) -> io::Result<Self> {
+        use std::io::Read;
+        let mut data = vec![0u64; len.div_ceil(8)];
+        let ptr = data.as_mut_ptr() as *mut u8;
+        file.read_exact(unsafe { std::slice::from_raw_parts_mut(ptr, len) })?;
+        Ok(MappedFile { ptr, len, _data: data })
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        // nothing refers to the mapping anymore, so a failure leaks it but cannot be handled otherwise
+        if unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.len) } != 0 {
+            println!("Could not unmap {} bytes: {}", self.len, io::Error::last_os_error());
+        }
+    }
+}
+
+// types that can be read directly from the bytes of a file: implementing it is only safe if every bit pattern is a valid
+//  value and there is no padding
+#[allow(clippy::missing_safety_doc)]
+pub unsafe trait Pod: Copy + Send + Sync + 'static {}
+
+unsafe impl Pod for u8 {}
+unsafe impl Pod for u32 {}
+unsafe impl Pod for u64 {}
+unsafe impl Pod for i64 {}
+unsafe impl Pod for f64 {}
+// transparent wrapper of an i32
+unsafe impl Pod for Date {}
+
+// bytes of a slice of plain values in their in-memory layout
+pub fn as_bytes<T: Pod>(values: &[T]) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(values.as_ptr() as *const u8, std::mem::size_of_val(values)) }
+}
+
+// values located in a mapped file, constructed only through Buffer::mapped, which checks bounds and alignment
+pub struct MappedSlice {
+    file: Arc<MappedFile>,
+    offset: usize,
+    len: usize
+}
+
+// the storage of a container: either owned (e.g., after encoding a column) or borrowed from a mapped file (after loading
+//  a column file), the latter staying in the page cache of the OS instead of the heap
+pub enum Buffer<T> {
+    Owned(Vec<T>),
+    Mapped(MappedSlice)
+}
+
+impl<T> Buffer<T> {
+    // the vector of an owned buffer, mapped buffers are copied first
+    pub fn make_mut(&mut self) -> &mut Vec<T>
+    where
+        T: Clone
+    {
+        if let Buffer::Mapped(_) = self {
+            *self = Buffer::Owned(self.to_vec());
+        }
+        match self {
+            Buffer::Owned(vec) => vec,
+            Buffer::Mapped(_) => unreachable!()
+        }
+    }
+}
+
+impl<T: Pod> Buffer<T> {
+    // len values starting at the given byte offset of the file
+    pub fn mapped(file: &Arc<MappedFile>, offset: usize, len: usize) -> io::Result<Self> {
+        let size = len.checked_mul(std::mem::size_of::<T>());
+        let in_bounds = size.and_then(|size| offset.checked_add(size)).is_some_and(|end| end <= file.len);
+        if !in_bounds {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "values exceed the mapped file"))
+        }
+        if !(file.ptr as usize + offset).is_multiple_of(std::mem::align_of::<T>()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "misaligned values in mapped file"))
+        }
+        Ok(Buffer::Mapped(MappedSlice { file: file.clone(), offset, len }))
+    }
+}
+
+impl<T> Deref for Buffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            Buffer::Owned(vec) => vec,
+            // only created for Pod types, after checking bounds and alignment
+            Buffer::Mapped(slice) => unsafe {
+                std::slice::from_raw_parts((slice.file.ptr as *const u8).add(slice.offset) as *const T, slice.len)
+            }
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for Buffer<T> {
+    fn from(vec: Vec<T>) -> Self {
+        Buffer::Owned(vec)
+    }
+}
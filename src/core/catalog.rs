@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use super::table::Table;
+
+pub const DEFAULT_SCHEMA: &str = "public";
+
+#[allow(dead_code)]
+pub struct Schema {
+    pub name: String,
+    tables: HashMap<String, Arc<RwLock<Table>>>,
+    // names of the indexes of the tables of the schema and the names of their tables (index names share the namespace of the
+    //  table names, like relations in PostgreSQL)
+    indexes: HashMap<String, String>
+}
+
+#[allow(dead_code)]
+impl Schema {
+    pub fn new(name: &str) -> Self {
+        Schema { name: name.to_owned(), tables: HashMap::new(), indexes: HashMap::new() }
+    }
+
+    pub fn get_table(&self, name: &str) -> Option<Arc<RwLock<Table>>> {
+        self.tables.get(name).cloned()
+    }
+
+    pub fn table_names(&self) -> Vec<&String> {
+        let mut names: Vec<&String> = self.tables.keys().collect();
+        names.sort();
+        names
+    }
+
+    fn contains_relation(&self, name: &str) -> bool {
+        self.tables.contains_key(name) || self.indexes.contains_key(name)
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+pub enum CatalogError {
+    UnknownSchema(String),
+    UnknownTable(String),
+    UnknownIndex(String),
+    DuplicateSchema(String),
+    DuplicateTable(String)
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CatalogError::UnknownSchema(name) => write!(f, "schema \"{}\" does not exist", name),
+            CatalogError::UnknownTable(name) => write!(f, "relation \"{}\" does not exist", name),
+            CatalogError::UnknownIndex(name) => write!(f, "index \"{}\" does not exist", name),
+            CatalogError::DuplicateSchema(name) => write!(f, "schema \"{}\" already exists", name),
+            CatalogError::DuplicateTable(name) => write!(f, "relation \"{}\" already exists", name)
+        }
+    }
+}
+
+impl CatalogError {
+    pub fn sqlstate(&self) -> &'static str {
+        match self {
+            CatalogError::UnknownSchema(_) => "3F000",
+            CatalogError::UnknownTable(_) => "42P01",
+            CatalogError::UnknownIndex(_) => "42704",
+            CatalogError::DuplicateSchema(_) => "42P06",
+            CatalogError::DuplicateTable(_) => "42P07"
+        }
+    }
+}
+
+// the catalog maps (schema name, table name) pairs to tables
+//  unqualified table names are looked up in the schemas of the search path (in order)
+pub struct Catalog {
+    schemas: HashMap<String, Schema>,
+    search_path: Vec<String>
+}
+
+#[allow(dead_code)]
+impl Catalog {
+    pub fn new() -> Self {
+        let mut schemas = HashMap::new();
+        schemas.insert(DEFAULT_SCHEMA.to_owned(), Schema::new(DEFAULT_SCHEMA));
+        Catalog { schemas, search_path: vec![DEFAULT_SCHEMA.to_owned()] }
+    }
+
+    pub fn create_schema(&mut self, name: &str) -> Result<(), CatalogError> {
+        if self.schemas.contains_key(name) {
+            return Err(CatalogError::DuplicateSchema(name.to_owned()))
+        }
+        self.schemas.insert(name.to_owned(), Schema::new(name));
+        Ok(())
+    }
+
+    pub fn get_schema(&self, name: &str) -> Result<&Schema, CatalogError> {
+        self.schemas.get(name).ok_or_else(|| CatalogError::UnknownSchema(name.to_owned()))
+    }
+
+    pub fn schema_names(&self) -> Vec<&String> {
+        let mut names: Vec<&String> = self.schemas.keys().collect();
+        names.sort();
+        names
+    }
+
+    pub fn search_path(&self) -> &Vec<String> {
+        &self.search_path
+    }
+
+    // the schema new tables are created in if no schema is given
+    pub fn current_schema(&self) -> &str {
+        &self.search_path[0]
+    }
+
+    pub fn create_table(&mut self, schema_name: Option<&str>, table_name: &str, table: Table) -> Result<Arc<RwLock<Table>>, CatalogError> {
+        let schema_name = schema_name.unwrap_or(&self.search_path[0]).to_owned();
+        let schema = self.schemas.get_mut(&schema_name).ok_or(CatalogError::UnknownSchema(schema_name))?;
+        if schema.contains_relation(table_name) {
+            return Err(CatalogError::DuplicateTable(table_name.to_owned()))
+        }
+        // indexes of tables read from column files
+        for index in table.indexes() {
+            if schema.contains_relation(&index.name) || index.name == table_name {
+                return Err(CatalogError::DuplicateTable(index.name.clone()))
+            }
+        }
+        for index in table.indexes() {
+            schema.indexes.insert(index.name.clone(), table_name.to_owned());
+        }
+        let table = Arc::new(RwLock::new(table));
+        schema.tables.insert(table_name.to_owned(), table.clone());
+        Ok(table)
+    }
+
+    pub fn drop_table(&mut self, schema_name: Option<&str>, table_name: &str) -> Result<(), CatalogError> {
+        let schema_name = self.resolve_table_schema(schema_name, table_name)?;
+        let schema = self.schemas.get_mut(&schema_name).unwrap();
+        schema.tables.remove(table_name);
+        schema.indexes.retain(|_, index_table_name| index_table_name != table_name);
+        Ok(())
+    }
+
+    // registers a new index of the given table in the schema of the table, returns the schema name and the table
+    //  the index itself is created by the caller (see Table::create_index)
+    pub fn create_index(&mut self, schema_name: Option<&str>, table_name: &str, index_name: &str) -> Result<(String, Arc<RwLock<Table>>), CatalogError> {
+        let schema_name = self.resolve_table_schema(schema_name, table_name)?;
+        let schema = self.schemas.get_mut(&schema_name).unwrap();
+        if schema.contains_relation(index_name) {
+            return Err(CatalogError::DuplicateTable(index_name.to_owned()))
+        }
+        schema.indexes.insert(index_name.to_owned(), table_name.to_owned());
+        let table = schema.tables[table_name].clone();
+        Ok((schema_name, table))
+    }
+
+    // removes an index from the catalog, returns the schema name, table name and table, from which the caller removes the index
+    pub fn drop_index(&mut self, schema_name: Option<&str>, index_name: &str) -> Result<(String, String, Arc<RwLock<Table>>), CatalogError> {
+        let schema_name = self.resolve_index_schema(schema_name, index_name)?;
+        let schema = self.schemas.get_mut(&schema_name).unwrap();
+        let table_name = schema.indexes.remove(index_name).unwrap();
+        let table = schema.tables[&table_name].clone();
+        Ok((schema_name, table_name, table))
+    }
+
+    // all tables of all schemas as (schema name, table name, table)
+    pub fn all_tables(&self) -> Vec<(String, String, Arc<RwLock<Table>>)> {
+        let mut result = Vec::new();
+        for schema in self.schemas.values() {
+            for (table_name, table) in schema.tables.iter() {
+                result.push((schema.name.clone(), table_name.clone(), table.clone()));
+            }
+        }
+        result
+    }
+
+    pub fn get_table(&self, schema_name: Option<&str>, table_name: &str) -> Result<Arc<RwLock<Table>>, CatalogError> {
+        let schema_name = self.resolve_table_schema(schema_name, table_name)?;
+        Ok(self.schemas[&schema_name].tables[table_name].clone())
+    }
+
+    // returns the name of the schema containing the given table, searching the search path for unqualified names
+    pub fn resolve_table_schema(&self, schema_name: Option<&str>, table_name: &str) -> Result<String, CatalogError> {
+        match schema_name {
+            Some(schema_name) => {
+                let schema = self.get_schema(schema_name)?;
+                if schema.tables.contains_key(table_name) {
+                    Ok(schema_name.to_owned())
+                } else {
+                    Err(CatalogError::UnknownTable(format!("{}.{}", schema_name, table_name)))
+                }
+            },
+            None => {
+                for schema_name in &self.search_path {
+                    if let Some(schema) = self.schemas.get(schema_name) {
+                        if schema.tables.contains_key(table_name) {
+                            return Ok(schema_name.clone())
+                        }
+                    }
+                }
+                Err(CatalogError::UnknownTable(table_name.to_owned()))
+            }
+        }
+    }
+
+    // returns the name of the schema containing the given index, searching the search path for unqualified names
+    pub fn resolve_index_schema(&self, schema_name: Option<&str>, index_name: &str) -> Result<String, CatalogError> {
+        match schema_name {
+            Some(schema_name) => {
+                if self.get_schema(schema_name)?.indexes.contains_key(index_name) {
+                    Ok(schema_name.to_owned())
+                } else {
+                    Err(CatalogError::UnknownIndex(format!("{}.{}", schema_name, index_name)))
+                }
+            },
+            None => self.search_path.iter()
+                .find(|schema_name| self.schemas.get(*schema_name).is_some_and(|schema| schema.indexes.contains_key(index_name)))
+                .cloned()
+                .ok_or_else(|| CatalogError::UnknownIndex(index_name.to_owned()))
+        }
+    }
+}
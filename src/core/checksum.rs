@@ -0,0 +1,49 @@
+// CRC-32C (Castagnoli) checksums of the column file format, computed 8 bytes at a time using 8 lookup tables
+//  (slicing-by-8): table[k][b] is the CRC of byte b followed by k zero bytes
+
+const POLYNOMIAL: u32 = 0x82f63b78;
+
+const fn make_tables() -> [[u32; 256]; 8] {
+    let mut tables = [[0u32; 256]; 8];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ POLYNOMIAL } else { crc >> 1 };
+            bit += 1;
+        }
+        tables[0][i] = crc;
+        i += 1;
+    }
+    let mut k = 1;
+    while k < 8 {
+        let mut i = 0;
+        while i < 256 {
+            let previous = tables[k - 1][i];
+            tables[k][i] = (previous >> 8) ^ tables[0][(previous & 0xff) as usize];
+            i += 1;
+        }
+        k += 1;
+    }
+    tables
+}
+
+static TABLES: [[u32; 256]; 8] = make_tables();
+
+pub fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    let mut words = data.chunks_exact(8);
+    for word in &mut words {
+        let low = u32::from_le_bytes([word[0], word[1], word[2], word[3]]) ^ crc;
+        let high = u32::from_le_bytes([word[4], word[5], word[6], word[7]]);
+        crc = TABLES[7][(low & 0xff) as usize] ^ TABLES[6][(low >> 8 & 0xff) as usize]
+            ^ TABLES[5][(low >> 16 & 0xff) as usize] ^ TABLES[4][(low >> 24) as usize]
+            ^ TABLES[3][(high & 0xff) as usize] ^ TABLES[2][(high >> 8 & 0xff) as usize]
+            ^ TABLES[1][(high >> 16 & 0xff) as usize] ^ TABLES[0][(high >> 24) as usize];
+    }
+    for byte in words.remainder() {
+        crc = (crc >> 8) ^ TABLES[0][((crc ^ *byte as u32) & 0xff) as usize];
+    }
+    !crc
+}
@@ -0,0 +1,574 @@
+use std::convert::TryFrom;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use super::avc::{DynAttributeValueContainer, Encoding, MainAttributeValueContainer};
+use super::buffer::{as_bytes, Buffer, MappedFile, Pod};
+use super::checksum::crc32c;
+use super::date::Date;
+use super::decimal::Decimal;
+use super::frame_of_reference_avc::FrameOfReferenceAttributeValueContainer;
+use super::lz4_avc::Lz4AttributeValueContainer;
+use super::run_length_avc::RunLengthAttributeValueContainer;
+use super::statistics::ColumnStatistics;
+use super::table::{Chunk, ColumnDefinition, DataType, Table};
+use super::unencoded_avc::UnencodedAttributeValueContainer;
+use super::value::ValueType;
+
+// native file format for tables, holding the encoded main stores of all chunks so that a table can be memory-mapped
+//  instead of parsing and encoding its values again
+//  header:   magic "RDBMSCOL", format version (u32), reserved (u32)
+//  segments: one per chunk and column, holding the statistics of the column (since version 2, see ColumnStatistics)
+//            followed by the attribute value container (e.g., dictionary and attribute vector)
+//  footer:   the table schema and, for each chunk, its row count and the encoding, position, length and checksum of each
+//            of its segments, followed by the name and column of each index (since version 3, the indexes themselves are
+//            created when the table is read)
+//  trailer:  position and length of the footer (u64 each), checksum of the footer (u32), magic "RCOL"
+//  all integers are little endian, fixed-size values (e.g., the words of bit-packed vectors or the entries of numeric
+//  dictionaries) are stored in their in-memory layout at 8 byte aligned positions, so that they can be used directly from
+//  the mapping (see Buffer)
+
+pub const COLUMN_FILE_EXTENSION: &str = "col";
+
+const MAGIC: &[u8; 8] = b"RDBMSCOL";
+const TRAILER_MAGIC: &[u8; 4] = b"RCOL";
+// incremented on every change of the format, older versions can still be read
+const FORMAT_VERSION: u32 = 3;
+const HEADER_SIZE: usize = 16;
+const TRAILER_SIZE: usize = 24;
+const ALIGNMENT: usize = 8;
+
+pub fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_owned())
+}
+
+fn padding(len: usize) -> usize {
+    (ALIGNMENT - len % ALIGNMENT) % ALIGNMENT
+}
+
+// serialization of a segment (or the footer), positions within a segment are aligned relative to its start
+pub struct SegmentWriter {
+    buf: Vec<u8>
+}
+
+impl SegmentWriter {
+    pub fn new() -> Self {
+        SegmentWriter { buf: Vec::new() }
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.buf.push(value);
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_len(&mut self, len: usize) {
+        self.write_u64(len as u64);
+    }
+
+    // the number of values followed by the values in their in-memory layout (at an aligned position)
+    pub fn write_slice<T: Pod>(&mut self, values: &[T]) {
+        self.write_len(values.len());
+        self.buf.resize(self.buf.len() + padding(self.buf.len()), 0);
+        self.buf.extend_from_slice(as_bytes(values));
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_slice(bytes);
+    }
+}
+
+// reads a segment (or the footer) of a mapped column file, the values of slices are not copied but refer to the mapping
+pub struct SegmentReader<'a> {
+    file: &'a Arc<MappedFile>,
+    start: usize,
+    pos: usize,
+    end: usize
+}
+
+impl<'a> SegmentReader<'a> {
+    fn new(file: &'a Arc<MappedFile>, start: usize, end: usize) -> Self {
+        SegmentReader { file, start, pos: start, end }
+    }
+
+    // advances by len bytes, returning the previous position
+    fn advance(&mut self, len: usize) -> io::Result<usize> {
+        match self.pos.checked_add(len) {
+            Some(end) if end <= self.end => {
+                let pos = self.pos;
+                self.pos = end;
+                Ok(pos)
+            },
+            _ => Err(invalid_data("unexpected end of segment"))
+        }
+    }
+
+    fn read_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let pos = self.advance(N)?;
+        let mut result = [0; N];
+        result.copy_from_slice(&self.file.as_slice()[pos..pos + N]);
+        Ok(result)
+    }
+
+    pub fn read_u8(&mut self) -> io::Result<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    pub fn read_u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    pub fn read_u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.read_array()?))
+    }
+
+    // number of bytes left in the segment
+    pub fn remaining(&self) -> usize {
+        self.end - self.pos
+    }
+
+    pub fn read_len(&mut self) -> io::Result<usize> {
+        usize::try_from(self.read_u64()?).map_err(|_| invalid_data("length out of range"))
+    }
+
+    // position and number of the values of a slice written by SegmentWriter::write_slice
+    fn read_slice_position<T: Pod>(&mut self) -> io::Result<(usize, usize)> {
+        let len = self.read_len()?;
+        self.advance(padding(self.pos - self.start))?;
+        let size = len.checked_mul(std::mem::size_of::<T>()).ok_or_else(|| invalid_data("length out of range"))?;
+        Ok((self.advance(size)?, len))
+    }
+
+    pub fn read_slice<T: Pod>(&mut self) -> io::Result<Buffer<T>> {
+        let (pos, len) = self.read_slice_position::<T>()?;
+        Buffer::mapped(self.file, pos, len)
+    }
+
+    pub fn read_bytes(&mut self) -> io::Result<&'a [u8]> {
+        let (pos, len) = self.read_slice_position::<u8>()?;
+        let file: &'a MappedFile = self.file;
+        Ok(&file.as_slice()[pos..pos + len])
+    }
+
+    fn read_string(&mut self) -> io::Result<String> {
+        String::from_utf8(self.read_bytes()?.to_vec()).map_err(|_| invalid_data("invalid column name"))
+    }
+}
+
+// values of a column that is not stored as fixed-size values, each one preceded by a NULL flag byte (like in LZ4 blocks)
+pub fn write_values<T: ValueType>(values: &[Option<T>], writer: &mut SegmentWriter) {
+    let mut buf = Vec::new();
+    for val in values {
+        match val {
+            Some(val) => {
+                buf.push(1);
+                val.write_bytes(&mut buf);
+            },
+            None => buf.push(0)
+        }
+    }
+    writer.write_len(values.len());
+    writer.write_bytes(&buf);
+}
+
+pub fn read_values<T: ValueType>(reader: &mut SegmentReader) -> io::Result<Vec<Option<T>>> {
+    let len = reader.read_len()?;
+    let buf = reader.read_bytes()?;
+    let mut values = Vec::with_capacity(std::cmp::min(len, buf.len()));
+    let mut offset = 0;
+    for _ in 0..len {
+        let is_null = *buf.get(offset).ok_or_else(|| invalid_data("unexpected end of values"))? == 0;
+        offset += 1;
+        values.push(if is_null { None } else { Some(T::read_bytes(buf, &mut offset)?) });
+    }
+    Ok(values)
+}
+
+fn encoding_tag(encoding: Encoding) -> u8 {
+    match encoding {
+        Encoding::Dictionary => 0,
+        Encoding::Unencoded => 1,
+        Encoding::RunLength => 2,
+        Encoding::FrameOfReference => 3,
+        Encoding::Lz4 => 4,
+        Encoding::UnsortedDictionary => panic!("delta stores are merged before they are written")
+    }
+}
+
+fn encoding_from_tag(tag: u8) -> io::Result<Encoding> {
+    match tag {
+        0 => Ok(Encoding::Dictionary),
+        1 => Ok(Encoding::Unencoded),
+        2 => Ok(Encoding::RunLength),
+        3 => Ok(Encoding::FrameOfReference),
+        4 => Ok(Encoding::Lz4),
+        _ => Err(invalid_data("unknown encoding"))
+    }
+}
+
+// a tag followed by the two (possibly unused) type parameters
+fn write_data_type(data_type: DataType, writer: &mut SegmentWriter) {
+    let (tag, first, second) = match data_type {
+        DataType::BigInt => (0, 0, 0),
+        DataType::Double => (1, 0, 0),
+        DataType::Char(len) => (2, len, 0),
+        DataType::Varchar(Some(len)) => (3, len, 0),
+        DataType::Varchar(None) => (4, 0, 0),
+        DataType::Text => (5, 0, 0),
+        DataType::Date => (6, 0, 0),
+        DataType::Decimal(precision, scale) => (7, precision, scale),
+        DataType::Boolean => (8, 0, 0)
+    };
+    writer.write_u8(tag);
+    writer.write_u32(first);
+    writer.write_u32(second);
+}
+
+fn read_data_type(reader: &mut SegmentReader) -> io::Result<DataType> {
+    let tag = reader.read_u8()?;
+    let first = reader.read_u32()?;
+    let second = reader.read_u32()?;
+    match tag {
+        0 => Ok(DataType::BigInt),
+        1 => Ok(DataType::Double),
+        2 => Ok(DataType::Char(first)),
+        3 => Ok(DataType::Varchar(Some(first))),
+        4 => Ok(DataType::Varchar(None)),
+        5 => Ok(DataType::Text),
+        6 => Ok(DataType::Date),
+        7 => Ok(DataType::Decimal(first, second)),
+        8 => Ok(DataType::Boolean),
+        _ => Err(invalid_data("unknown data type"))
+    }
+}
+
+fn write_container<T: ValueType>(avc: &dyn DynAttributeValueContainer, statistics: &ColumnStatistics, writer: &mut SegmentWriter) {
+    statistics.write::<T>(writer);
+    fn downcast<C: 'static>(avc: &dyn DynAttributeValueContainer) -> &C {
+        avc.as_any().downcast_ref::<C>().expect("container does not match the column type")
+    }
+    match avc.encoding() {
+        Encoding::Dictionary => downcast::<MainAttributeValueContainer<T>>(avc).write(writer),
+        Encoding::Unencoded => downcast::<UnencodedAttributeValueContainer<T>>(avc).write(writer),
+        Encoding::RunLength => downcast::<RunLengthAttributeValueContainer<T>>(avc).write(writer),
+        Encoding::FrameOfReference => downcast::<FrameOfReferenceAttributeValueContainer<T>>(avc).write(writer),
+        Encoding::Lz4 => downcast::<Lz4AttributeValueContainer<T>>(avc).write(writer),
+        Encoding::UnsortedDictionary => panic!("delta stores are merged before they are written")
+    }
+}
+
+// the statistics are computed from the container for files of version 1
+fn read_container<T: ValueType>(version: u32, encoding: Encoding, reader: &mut SegmentReader) -> io::Result<(Box<dyn DynAttributeValueContainer + Send + Sync>, ColumnStatistics)> {
+    let statistics = if version >= 2 { Some(ColumnStatistics::read::<T>(reader)?) } else { None };
+    let avc: Box<dyn DynAttributeValueContainer + Send + Sync> = match encoding {
+        Encoding::Dictionary => Box::new(MainAttributeValueContainer::<T>::read(reader)?),
+        Encoding::Unencoded => Box::new(UnencodedAttributeValueContainer::<T>::read(reader)?),
+        Encoding::RunLength => Box::new(RunLengthAttributeValueContainer::<T>::read(reader)?),
+        Encoding::FrameOfReference => Box::new(FrameOfReferenceAttributeValueContainer::<T>::read(reader)?),
+        Encoding::Lz4 => Box::new(Lz4AttributeValueContainer::<T>::read(reader)?),
+        Encoding::UnsortedDictionary => return Err(invalid_data("unknown encoding"))
+    };
+    let statistics = match statistics {
+        Some(statistics) => statistics,
+        None => avc.statistics()?
+    };
+    Ok((avc, statistics))
+}
+
+fn write_segment(data_type: DataType, avc: &dyn DynAttributeValueContainer, statistics: &ColumnStatistics, writer: &mut SegmentWriter) {
+    match data_type {
+        DataType::BigInt => write_container::<i64>(avc, statistics, writer),
+        DataType::Double => write_container::<f64>(avc, statistics, writer),
+        DataType::Char(_) | DataType::Varchar(_) | DataType::Text => write_container::<String>(avc, statistics, writer),
+        DataType::Date => write_container::<Date>(avc, statistics, writer),
+        DataType::Decimal(_, _) => write_container::<Decimal>(avc, statistics, writer),
+        DataType::Boolean => write_container::<bool>(avc, statistics, writer)
+    }
+}
+
+type Segment = (Box<dyn DynAttributeValueContainer + Send + Sync>, ColumnStatistics);
+
+fn read_segment(version: u32, data_type: DataType, encoding: Encoding, reader: &mut SegmentReader) -> io::Result<Segment> {
+    match data_type {
+        DataType::BigInt => read_container::<i64>(version, encoding, reader),
+        DataType::Double => read_container::<f64>(version, encoding, reader),
+        DataType::Char(_) | DataType::Varchar(_) | DataType::Text => read_container::<String>(version, encoding, reader),
+        DataType::Date => read_container::<Date>(version, encoding, reader),
+        DataType::Decimal(_, _) => read_container::<Decimal>(version, encoding, reader),
+        DataType::Boolean => read_container::<bool>(version, encoding, reader)
+    }
+}
+
+// writes the main stores of all chunks to a column file, chunks with a delta store are written as if they were merged
+//  the file is written under a temporary name first and then renamed, so that an existing file is replaced atomically
+pub fn write_table(table: &Table, path: &Path) -> io::Result<()> {
+    let temp_path = path.with_extension("tmp");
+    let mut output = BufWriter::new(File::create(&temp_path)?);
+    output.write_all(MAGIC)?;
+    output.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    output.write_all(&0u32.to_le_bytes())?;
+    let mut pos = HEADER_SIZE;
+
+    let mut footer = SegmentWriter::new();
+    footer.write_len(table.target_chunk_size());
+    footer.write_len(table.column_count());
+    for column in table.column_definitions() {
+        footer.write_bytes(column.name.as_bytes());
+        write_data_type(column.data_type, &mut footer);
+        footer.write_u8(column.nullable as u8);
+    }
+    footer.write_len(table.chunk_count());
+    for chunk in table.chunks() {
+        let merged = if chunk.delta_len() > 0 { Some(chunk.merge_delta(chunk.delta_len())?) } else { None };
+        footer.write_len(chunk.len());
+        for (column_id, column) in table.column_definitions().iter().enumerate() {
+            let (avc, statistics) = match &merged {
+                Some(merged) => (merged[column_id].as_ref() as &dyn DynAttributeValueContainer, merged[column_id].statistics()?),
+                None => (chunk.column(column_id), chunk.statistics(column_id).clone())
+            };
+            let mut segment = SegmentWriter::new();
+            write_segment(column.data_type, avc, &statistics, &mut segment);
+            footer.write_u8(encoding_tag(avc.encoding()));
+            footer.write_len(pos);
+            footer.write_len(segment.buf.len());
+            footer.write_u32(crc32c(&segment.buf));
+            output.write_all(&segment.buf)?;
+            output.write_all(&[0; ALIGNMENT][..padding(segment.buf.len())])?;
+            pos += segment.buf.len() + padding(segment.buf.len());
+        }
+    }
+    footer.write_len(table.indexes().len());
+    for index in table.indexes() {
+        footer.write_bytes(index.name.as_bytes());
+        footer.write_len(index.column_id);
+    }
+
+    output.write_all(&footer.buf)?;
+    output.write_all(&(pos as u64).to_le_bytes())?;
+    output.write_all(&(footer.buf.len() as u64).to_le_bytes())?;
+    output.write_all(&crc32c(&footer.buf).to_le_bytes())?;
+    output.write_all(TRAILER_MAGIC)?;
+    output.into_inner()?.sync_all()?;
+    fs::rename(temp_path, path)
+}
+
+// maps a column file and creates a table from it, the containers refer to the mapping instead of copying the data where
+//  possible (so that loading is fast and the data can be held by the page cache)
+//  the checksums of the footer and of each segment are verified before they are decoded, so the containers never see
+//  corrupted data (a file with a mismatch is not loaded at all)
+pub fn read_table(path: &Path) -> io::Result<Table> {
+    if cfg!(target_endian = "big") {
+        return Err(invalid_data("column files are only supported on little endian machines"))
+    }
+    let file = Arc::new(MappedFile::open(path)?);
+    let data = file.as_slice();
+    if data.len() < HEADER_SIZE + TRAILER_SIZE || &data[0..8] != MAGIC || &data[data.len() - 4..] != TRAILER_MAGIC {
+        return Err(invalid_data("not a column file"))
+    }
+    let version = SegmentReader::new(&file, MAGIC.len(), HEADER_SIZE).read_u32()?;
+    if version == 0 || version > FORMAT_VERSION {
+        return Err(invalid_data(&format!("unsupported column file version {} (expected at most {})", version, FORMAT_VERSION)))
+    }
+
+    let mut trailer = SegmentReader::new(&file, data.len() - TRAILER_SIZE, data.len());
+    let footer_pos = trailer.read_len()?;
+    let footer_len = trailer.read_len()?;
+    let footer_checksum = trailer.read_u32()?;
+    let footer_end = footer_pos.checked_add(footer_len).filter(|end| *end <= data.len() - TRAILER_SIZE && footer_pos >= HEADER_SIZE)
+        .ok_or_else(|| invalid_data("invalid footer position"))?;
+    if crc32c(&data[footer_pos..footer_end]) != footer_checksum {
+        return Err(invalid_data("checksum mismatch in footer"))
+    }
+
+    let mut footer = SegmentReader::new(&file, footer_pos, footer_end);
+    let target_chunk_size = footer.read_len()?;
+    let column_count = footer.read_len()?;
+    let mut columns = Vec::new();
+    for _ in 0..column_count {
+        let name = footer.read_string()?;
+        let data_type = read_data_type(&mut footer)?;
+        let nullable = footer.read_u8()? != 0;
+        columns.push(ColumnDefinition { name, data_type, nullable });
+    }
+    if target_chunk_size == 0 {
+        return Err(invalid_data("invalid target chunk size"))
+    }
+    let mut table = Table::new(columns.clone(), target_chunk_size);
+    let chunk_count = footer.read_len()?;
+    for chunk_id in 0..chunk_count {
+        let row_count = footer.read_len()?;
+        if row_count > target_chunk_size {
+            return Err(invalid_data(&format!("chunk {} exceeds the target chunk size", chunk_id)))
+        }
+        let mut containers = Vec::with_capacity(columns.len());
+        let mut statistics = Vec::with_capacity(columns.len());
+        for column in columns.iter() {
+            let encoding = encoding_from_tag(footer.read_u8()?)?;
+            let segment_pos = footer.read_len()?;
+            let segment_len = footer.read_len()?;
+            let checksum = footer.read_u32()?;
+            let segment_end = segment_pos.checked_add(segment_len).filter(|end| *end <= footer_pos && segment_pos >= HEADER_SIZE && segment_pos.is_multiple_of(ALIGNMENT))
+                .ok_or_else(|| invalid_data("invalid segment position"))?;
+            if crc32c(&data[segment_pos..segment_end]) != checksum {
+                return Err(invalid_data(&format!("checksum mismatch in column \"{}\" of chunk {}", column.name, chunk_id)))
+            }
+            let (avc, column_statistics) = read_segment(version, column.data_type, encoding, &mut SegmentReader::new(&file, segment_pos, segment_end))?;
+            if avc.len() != row_count {
+                return Err(invalid_data(&format!("column \"{}\" of chunk {} has {} instead of {} rows", column.name, chunk_id, avc.len(), row_count)))
+            }
+            containers.push(avc);
+            statistics.push(column_statistics);
+        }
+        table.append_chunk(Chunk::with_statistics(containers, statistics))?;
+    }
+    if version >= 3 {
+        for _ in 0..footer.read_len()? {
+            let name = footer.read_string()?;
+            let column_id = footer.read_len()?;
+            if column_id >= columns.len() || table.index_id(&name).is_some() {
+                return Err(invalid_data(&format!("invalid index \"{}\"", name)))
+            }
+            table.create_index(&name, column_id)?;
+        }
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use super::super::avc::{AttributeValueContainer, Dict};
+    use super::super::front_coded_dict::FrontCodedDict;
+    use super::super::scan::ScanPredicate;
+    use crate::core::Value;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rdbms-column-file-{}-{}.col", std::process::id(), name))
+    }
+
+    // reads a segment from a file holding nothing else
+    fn read_file<T>(name: &str, buf: &[u8], read: impl FnOnce(&mut SegmentReader) -> io::Result<T>) -> io::Result<T> {
+        let path = temp_path(name);
+        fs::write(&path, buf).unwrap();
+        let file = Arc::new(MappedFile::open(&path).unwrap());
+        let result = read(&mut SegmentReader::new(&file, 0, buf.len()));
+        fs::remove_file(path).unwrap();
+        result
+    }
+
+    fn test_table() -> Table {
+        let mut table = Table::new(vec![
+            ColumnDefinition::new("id", DataType::BigInt, false),
+            ColumnDefinition::new("name", DataType::Text, true)
+        ], 100);
+        table.insert((0..250).map(|i| vec![Value::BigInt(i), if i % 7 == 0 { Value::Null } else { Value::Text(format!("name {}", i % 13)) }]).collect()).unwrap();
+        table
+    }
+
+    fn rows(table: &Table) -> Vec<Vec<Value>> {
+        table.chunks().iter().flat_map(|chunk| {
+            (0..chunk.len()).map(move |i| (0..chunk.column_count()).map(|column_id| chunk.get_value(column_id, i).unwrap()).collect::<Vec<_>>())
+        }).collect()
+    }
+
+    #[test]
+    fn tables_are_written_and_read() {
+        let path = temp_path("round-trip");
+        let table = test_table();
+        write_table(&table, &path).unwrap();
+        let read = read_table(&path).unwrap();
+        assert_eq!(read.chunk_count(), 3);
+        assert_eq!(rows(&read), rows(&table));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn corrupted_files_are_not_loaded() {
+        let path = temp_path("valid");
+        write_table(&test_table(), &path).unwrap();
+        let mut data = fs::read(&path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        // a new file for each corruption, since a mapped file must not be truncated
+        let path = temp_path("corrupt-segment");
+        data[HEADER_SIZE + 40] ^= 0x10;
+        fs::write(&path, &data).unwrap();
+        assert_eq!(read_table(&path).err().unwrap().to_string(), "checksum mismatch in column \"id\" of chunk 0");
+        fs::remove_file(path).unwrap();
+
+        let path = temp_path("corrupt-footer");
+        let last = data.len() - TRAILER_SIZE - 1;
+        data[last] ^= 0x10;
+        fs::write(&path, &data).unwrap();
+        assert_eq!(read_table(&path).err().unwrap().to_string(), "checksum mismatch in footer");
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn invalid_values_are_errors() {
+        let mut buf = Vec::new();
+        String::from("name").write_bytes(&mut buf);
+        assert_eq!(String::read_bytes(&buf, &mut 0).unwrap(), "name");
+        assert!(String::read_bytes(&buf[..buf.len() - 1], &mut 0).is_err());
+        let last = buf.len() - 1;
+        buf[last] = 0xff;
+        assert!(String::read_bytes(&buf, &mut 0).is_err());
+        assert!(i64::read_bytes(&[0; 7], &mut 0).is_err());
+        assert!(i64::read_bytes(&[0; 8], &mut 1).is_err());
+        let mut buf = Vec::new();
+        Decimal::new(15, 1).write_bytes(&mut buf);
+        assert_eq!(Decimal::read_bytes(&buf, &mut 0).unwrap(), Decimal::new(15, 1));
+        buf[16] = 200;
+        assert!(Decimal::read_bytes(&buf, &mut 0).is_err());
+    }
+
+    #[test]
+    fn invalid_string_dictionaries_are_errors() {
+        let entries: Vec<String> = (0..40).map(|i| format!("entry {:02}", i)).collect();
+        let mut writer = SegmentWriter::new();
+        FrontCodedDict::new(&entries).write(&mut writer);
+        let read = |name: &str, buf: &[u8]| read_file(name, buf, FrontCodedDict::read);
+        let dict = read("dict", &writer.buf).unwrap();
+        assert_eq!((0..40).map(|vid| dict.lookup(vid)).collect::<Vec<_>>(), entries);
+        // the length of the dictionary and of its data are followed by the length of the first entry and the entry itself
+        let mut buf = writer.buf.clone();
+        buf[17] = 0xff;
+        assert!(read("dict-utf8", &buf).is_err());
+        let mut buf = writer.buf.clone();
+        buf[16] = 0x7f;
+        assert!(read("dict-length", &buf).is_err());
+    }
+
+    #[test]
+    fn invalid_lz4_blocks_are_errors() {
+        let values: Vec<Option<String>> = (0..3000).map(|i| if i % 11 == 0 { None } else { Some(format!("value {}", i % 97)) }).collect();
+        let mut writer = SegmentWriter::new();
+        Lz4AttributeValueContainer::new(&values).write(&mut writer);
+        let read = |name: &str, buf: &[u8]| read_file(name, buf, Lz4AttributeValueContainer::<String>::read);
+        let avc = read("lz4", &writer.buf).unwrap();
+        assert_eq!(avc.lookup(2999).unwrap(), values[2999]);
+        // the number of values is followed by the distinct count and the uncompressed length of the first block
+        let mut buf = writer.buf.clone();
+        buf[..8].copy_from_slice(&(u64::MAX / 2).to_le_bytes());
+        assert!(read("lz4-length", &buf).is_err());
+        // blocks are decompressed when they are accessed, so the other blocks can still be read
+        let mut buf = writer.buf.clone();
+        buf[16] ^= 1;
+        let avc = read("lz4-block", &buf).unwrap();
+        assert!(avc.lookup(0).is_err());
+        assert!(avc.get_value(1).is_err());
+        assert!(avc.scan(&ScanPredicate::IsNull).is_err());
+        assert!(avc.statistics().is_err());
+        assert_eq!(avc.lookup(2999).unwrap(), values[2999]);
+    }
+}
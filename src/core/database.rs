@@ -1,8 +1,13 @@
 use std::sync::{RwLock, Mutex};
+use crate::checkpoint::Checkpointer;
+use crate::threadpool::ThreadPool;
 use crate::transaction::TransactionManager;
-use super::avc::AttributeValueContainer;
+use super::catalog::Catalog;
 
-pub struct Database<'a> {
+pub struct Database {
     pub transaction_manager: Mutex<TransactionManager>,
-    pub avc: RwLock<Box<dyn AttributeValueContainer<i64> + 'a + Send + Sync>>
-}
\ No newline at end of file
+    pub catalog: RwLock<Catalog>,
+    pub checkpointer: Checkpointer,
+    // parsing and encoding of loaded rows (e.g., of COPY FROM), shared by all connections
+    pub loader_pool: ThreadPool
+}
@@ -0,0 +1,100 @@
+use std::fmt;
+use std::ops::{Add, Sub};
+
+// days between 1970-01-01 and 2000-01-01 (the epoch of the PostgreSQL binary format)
+const POSTGRES_EPOCH_DAYS: i32 = 10957;
+
+// calendar date, stored as the number of days since 1970-01-01 (proleptic Gregorian calendar)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Date(pub i32);
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 => if is_leap_year(year) { 29 } else { 28 },
+        _ => 0
+    }
+}
+
+impl Date {
+    pub fn from_ymd(year: i32, month: u32, day: u32) -> Option<Date> {
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None
+        }
+        // see http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+        let year = if month <= 2 { year - 1 } else { year } as i64;
+        let era = if year >= 0 { year } else { year - 399 } / 400;
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        Some(Date((era * 146097 + day_of_era - 719468) as i32))
+    }
+
+    // (year, month, day)
+    pub fn to_ymd(self) -> (i32, u32, u32) {
+        // see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let days = self.0 as i64 + 719468;
+        let era = if days >= 0 { days } else { days - 146096 } / 146097;
+        let day_of_era = days - era * 146097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (year_of_era + era * 400) as i32 + if month <= 2 { 1 } else { 0 };
+        (year, month, day)
+    }
+
+    // parses dates in ISO 8601 format (YYYY-MM-DD)
+    pub fn parse(s: &str) -> Option<Date> {
+        let mut parts = s.trim().splitn(3, '-');
+        let year = parts.next()?.parse::<i32>().ok()?;
+        let month = parts.next()?.parse::<u32>().ok()?;
+        let day = parts.next()?.parse::<u32>().ok()?;
+        Date::from_ymd(year, month, day)
+    }
+
+    // number of days since 2000-01-01, as used by the binary wire format
+    pub fn to_postgres_days(self) -> i32 {
+        self.0 - POSTGRES_EPOCH_DAYS
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = self.to_ymd();
+        f.pad(&format!("{:04}-{:02}-{:02}", year, month, day))
+    }
+}
+
+// adding or subtracting a number of days
+impl Add<i32> for Date {
+    type Output = Date;
+
+    fn add(self, days: i32) -> Date {
+        Date(self.0 + days)
+    }
+}
+
+impl Sub<i32> for Date {
+    type Output = Date;
+
+    fn sub(self, days: i32) -> Date {
+        Date(self.0 - days)
+    }
+}
+
+// the difference between two dates in days
+impl Sub<Date> for Date {
+    type Output = i32;
+
+    fn sub(self, other: Date) -> i32 {
+        self.0 - other.0
+    }
+}
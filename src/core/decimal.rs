@@ -0,0 +1,221 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Neg;
+
+// maximum supported precision (number of significant decimal digits) of DECIMAL columns
+pub const MAX_DECIMAL_PRECISION: u32 = 38;
+// minimum number of fractional digits of the result of a division
+pub const MIN_DIVISION_SCALE: u32 = 6;
+
+// exact fixed-point number: value * 10^(-scale)
+//  values with different scales compare equal if they represent the same number (e.g., 1.5 and 1.50)
+#[derive(Debug, Clone, Copy)]
+pub struct Decimal {
+    value: i128,
+    scale: u32
+}
+
+fn pow10(exp: u32) -> i128 {
+    10i128.pow(exp)
+}
+
+impl Decimal {
+    pub fn new(value: i128, scale: u32) -> Self {
+        Decimal { value, scale }
+    }
+
+    pub fn from_i64(value: i64) -> Self {
+        Decimal { value: value as i128, scale: 0 }
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    // the value multiplied by 10^scale
+    pub fn unscaled_value(&self) -> i128 {
+        self.value
+    }
+
+    // number of significant digits in front of the decimal point plus the number of fractional digits
+    pub fn precision(&self) -> u32 {
+        let mut digits = 1;
+        let mut value = self.value.abs() / 10;
+        while value > 0 {
+            digits += 1;
+            value /= 10;
+        }
+        std::cmp::max(digits, self.scale)
+    }
+
+    // parses numbers like "-12.345" (the scale is the number of digits after the decimal point)
+    pub fn parse(s: &str) -> Option<Decimal> {
+        let s = s.trim();
+        let (negative, s) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s)
+        };
+        let (integer, fraction) = match s.find('.') {
+            Some(pos) => (&s[..pos], &s[pos + 1..]),
+            None => (s, "")
+        };
+        if integer.is_empty() && fraction.is_empty() {
+            return None
+        }
+        let mut value: i128 = 0;
+        for c in integer.chars().chain(fraction.chars()) {
+            let digit = c.to_digit(10)? as i128;
+            value = value.checked_mul(10)?.checked_add(digit)?;
+        }
+        let scale = fraction.len() as u32;
+        if scale > MAX_DECIMAL_PRECISION {
+            return None
+        }
+        Some(Decimal { value: if negative { -value } else { value }, scale })
+    }
+
+    // changes the number of fractional digits, rounding half away from zero if digits are removed
+    pub fn rescale(&self, scale: u32) -> Option<Decimal> {
+        if scale >= self.scale {
+            let value = self.value.checked_mul(pow10(scale - self.scale))?;
+            Some(Decimal { value, scale })
+        } else {
+            let divisor = pow10(self.scale - scale);
+            let mut value = self.value / divisor;
+            let remainder = self.value % divisor;
+            if remainder.abs() * 2 >= divisor {
+                value += self.value.signum();
+            }
+            Some(Decimal { value, scale })
+        }
+    }
+
+    // quotient with at least MIN_DIVISION_SCALE fractional digits, None on division by zero or overflow
+    pub fn checked_div(&self, other: &Decimal) -> Option<Decimal> {
+        if other.value == 0 {
+            return None
+        }
+        let scale = std::cmp::max(MIN_DIVISION_SCALE, std::cmp::max(self.scale, other.scale));
+        // one additional digit for rounding
+        let shift = scale + other.scale + 1 - self.scale;
+        let quotient = self.value.checked_mul(pow10(shift))? / other.value;
+        Decimal { value: quotient, scale: scale + 1 }.rescale(scale)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.value as f64 / pow10(self.scale) as f64
+    }
+
+    // encoding of the binary wire format of the PostgreSQL numeric type:
+    //  number of base-10000 digits, weight of the first digit, sign, display scale and the digits themselves (all 16 bit)
+    pub fn to_postgres_binary(self) -> Vec<u8> {
+        // align the fractional digits to whole base-10000 digits
+        let fraction_groups = self.scale.div_ceil(4);
+        let mut value = self.value.abs() * pow10(fraction_groups * 4 - self.scale);
+        let mut digits = Vec::new();
+        while value > 0 {
+            digits.push((value % 10000) as i16);
+            value /= 10000;
+        }
+        while digits.len() < fraction_groups as usize {
+            digits.push(0);
+        }
+        digits.reverse();
+        let mut weight = digits.len() as i16 - fraction_groups as i16 - 1;
+        while digits.first() == Some(&0) {
+            digits.remove(0);
+            weight -= 1;
+        }
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        if digits.is_empty() {
+            weight = 0;
+        }
+        let sign: u16 = if self.value < 0 { 0x4000 } else { 0 };
+
+        let mut buf = Vec::with_capacity(8 + digits.len() * 2);
+        buf.extend_from_slice(&(digits.len() as i16).to_be_bytes());
+        buf.extend_from_slice(&weight.to_be_bytes());
+        buf.extend_from_slice(&sign.to_be_bytes());
+        buf.extend_from_slice(&(self.scale as i16).to_be_bytes());
+        for digit in digits {
+            buf.extend_from_slice(&digit.to_be_bytes());
+        }
+        buf
+    }
+
+    // sum, difference and product, None on overflow
+    pub fn checked_add(&self, other: &Decimal) -> Option<Decimal> {
+        let (a, b, scale) = self.aligned(other)?;
+        Some(Decimal { value: a.checked_add(b)?, scale })
+    }
+
+    pub fn checked_sub(&self, other: &Decimal) -> Option<Decimal> {
+        let (a, b, scale) = self.aligned(other)?;
+        Some(Decimal { value: a.checked_sub(b)?, scale })
+    }
+
+    pub fn checked_mul(&self, other: &Decimal) -> Option<Decimal> {
+        Some(Decimal { value: self.value.checked_mul(other.value)?, scale: self.scale + other.scale })
+    }
+
+    // both values converted to the larger of both scales, None if one of them does not fit
+    fn aligned(&self, other: &Decimal) -> Option<(i128, i128, u32)> {
+        let scale = std::cmp::max(self.scale, other.scale);
+        Some((self.value.checked_mul(pow10(scale - self.scale))?, other.value.checked_mul(pow10(scale - other.scale))?, scale))
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Decimal) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Decimal) -> Ordering {
+        if let Some((a, b, _)) = self.aligned(other) {
+            return a.cmp(&b)
+        }
+        // the integer parts differ if one of the values does not fit with the larger scale, otherwise the fractions
+        //  (which have the sign of the value) decide
+        let (a, b) = (pow10(self.scale), pow10(other.scale));
+        (self.value / a).cmp(&(other.value / b)).then_with(|| {
+            let scale = std::cmp::max(self.scale, other.scale);
+            ((self.value % a) * pow10(scale - self.scale)).cmp(&((other.value % b) * pow10(scale - other.scale)))
+        })
+    }
+}
+
+impl Neg for Decimal {
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal {
+        Decimal { value: -self.value, scale: self.scale }
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let divisor = pow10(self.scale);
+        let integer = self.value.abs() / divisor;
+        let sign = if self.value < 0 { "-" } else { "" };
+        let result = if self.scale == 0 {
+            format!("{}{}", sign, integer)
+        } else {
+            let fraction = self.value.abs() % divisor;
+            format!("{}{}.{:0width$}", sign, integer, fraction, width = self.scale as usize)
+        };
+        f.pad(&result)
+    }
+}
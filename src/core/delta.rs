@@ -0,0 +1,235 @@
+use std::any::Any;
+use std::cmp::Ordering;
+use std::io;
+
+use super::avc::{AttributeValueContainer, DynAttributeValueContainer, Encoding, MainAttributeValueContainer, ValueId};
+use super::encoding::encode_column;
+use super::statistics::ColumnStatistics;
+use super::value::{Value, ValueType};
+
+// dictionary of the delta store: new values are appended (so existing value ids stay valid),
+//  the index holds all value ids ordered by their value to allow for value lookups using binary search
+//  (not a Dict, as the value ids are not ordered like the values)
+pub struct UnsortedDict<T> {
+    entries: Vec<T>,
+    index: Vec<ValueId>
+}
+
+impl<T: ValueType> UnsortedDict<T> {
+    pub fn new() -> Self {
+        UnsortedDict { entries: Vec::new(), index: Vec::new() }
+    }
+
+    // Ok(position in the index) if the value is contained, Err(insert position in the index) otherwise
+    fn search(&self, value: &T) -> Result<usize, usize> {
+        self.index.binary_search_by(|vid| self.entries[*vid as usize].partial_cmp(value).unwrap_or(Ordering::Equal))
+    }
+
+    #[allow(dead_code)]
+    pub fn find(&self, value: &T) -> Option<ValueId> {
+        self.search(value).ok().map(|pos| self.index[pos])
+    }
+
+    pub fn get_or_insert(&mut self, value: T) -> ValueId {
+        match self.search(&value) {
+            Ok(pos) => self.index[pos],
+            Err(pos) => {
+                let vid = self.entries.len() as ValueId;
+                self.entries.push(value);
+                self.index.insert(pos, vid);
+                vid
+            }
+        }
+    }
+
+    // all value ids of the dictionary, ordered by their value
+    pub fn sorted_value_ids(&self) -> &[ValueId] {
+        &self.index
+    }
+
+    pub fn lookup(&self, i: ValueId) -> T {
+        self.entries[i as usize].clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        let heap_size: usize = self.entries.iter().map(|entry| entry.heap_size()).sum();
+        self.entries.len() * std::mem::size_of::<T>() + heap_size + self.index.len() * std::mem::size_of::<ValueId>()
+    }
+}
+
+// write-optimized attribute value container: appending a value never changes existing value ids
+//  NULL is represented by ValueId::MAX, as the dictionary keeps growing
+pub struct DeltaAttributeValueContainer<T> {
+    pub data: Vec<ValueId>,
+    pub dict: UnsortedDict<T>
+}
+
+impl<T: ValueType> DeltaAttributeValueContainer<T> {
+    pub fn new() -> Self {
+        DeltaAttributeValueContainer { data: Vec::new(), dict: UnsortedDict::new() }
+    }
+
+    pub fn null_value_id(&self) -> ValueId {
+        ValueId::MAX
+    }
+
+    pub fn append(&mut self, value: Option<T>) {
+        let vid = match value {
+            Some(value) => self.dict.get_or_insert(value),
+            None => self.null_value_id()
+        };
+        self.data.push(vid);
+    }
+
+    // the delta store is in memory, so unlike lookup, reading its values cannot fail
+    pub fn get(&self, i: usize) -> Option<T> {
+        let vid = self.data[i];
+        if vid == self.null_value_id() {
+            None
+        } else {
+            Some(self.dict.lookup(vid))
+        }
+    }
+}
+
+impl<T: ValueType> DynAttributeValueContainer for DeltaAttributeValueContainer<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    fn distinct_count(&self) -> usize {
+        self.dict.len()
+    }
+
+    fn get_value(&self, i: usize) -> io::Result<Value> {
+        Ok(match self.get(i) {
+            Some(val) => val.into_value(),
+            None => Value::Null
+        })
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding::UnsortedDictionary
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.data.len() * std::mem::size_of::<ValueId>() + self.dict.memory_usage()
+    }
+
+    fn statistics(&self) -> io::Result<ColumnStatistics> {
+        Ok(ColumnStatistics::from_values((0..self.len()).map(|i| self.get(i)), self.distinct_count()))
+    }
+}
+
+impl<T: ValueType> AttributeValueContainer<T> for DeltaAttributeValueContainer<T> {
+    fn lookup(&self, i: usize) -> io::Result<Option<T>> {
+        Ok(self.get(i))
+    }
+}
+
+// type-independent write access to delta stores
+pub trait DynDeltaAttributeValueContainer: DynAttributeValueContainer {
+    // returns false (without appending anything) if the value does not match the type of the container
+    fn append_value(&mut self, value: &Value) -> bool;
+    // merges the first `rows` rows of the delta into the given main store, creating a new main store
+    //  fails if the values of the main store cannot be read
+    fn merge(&self, main: &dyn DynAttributeValueContainer, rows: usize) -> io::Result<Box<dyn DynAttributeValueContainer + Send + Sync>>;
+    // creates a new delta store holding all rows starting at `from`
+    fn split_off(&self, from: usize) -> Box<dyn DynDeltaAttributeValueContainer + Send + Sync>;
+}
+
+impl<T: ValueType> DynDeltaAttributeValueContainer for DeltaAttributeValueContainer<T> {
+    fn append_value(&mut self, value: &Value) -> bool {
+        if value.is_null() {
+            self.append(None);
+            return true
+        }
+        match T::from_value(value) {
+            Some(value) => {
+                self.append(Some(value));
+                true
+            },
+            None => false
+        }
+    }
+
+    fn merge(&self, main: &dyn DynAttributeValueContainer, rows: usize) -> io::Result<Box<dyn DynAttributeValueContainer + Send + Sync>> {
+        let main = match main.as_any().downcast_ref::<MainAttributeValueContainer<T>>() {
+            Some(main) => main,
+            None => {
+                // other encodings are decoded and encoded again, together with the delta rows
+                let mut column: Vec<Option<T>> = Vec::with_capacity(main.len() + rows);
+                for i in 0..main.len() {
+                    column.push(T::from_value(&main.get_value(i)?));
+                }
+                column.extend((0..rows).map(|i| self.get(i)));
+                return Ok(encode_column(&column))
+            }
+        };
+
+        // merge the sorted main dictionary with the (sorted) delta index, creating mappings from old to new value ids
+        //  (values only used by rows after the merged ones are left out)
+        let main_dict_len = main.dict.len();
+        let mut used = vec![false; self.dict.len()];
+        for vid in self.data[0..rows].iter() {
+            if *vid != self.null_value_id() {
+                used[*vid as usize] = true;
+            }
+        }
+        let delta_vids: Vec<ValueId> = self.dict.sorted_value_ids().iter().filter(|vid| used[**vid as usize]).cloned().collect();
+        let mut entries: Vec<T> = Vec::with_capacity(main_dict_len + delta_vids.len());
+        let mut main_mapping: Vec<ValueId> = Vec::with_capacity(main_dict_len);
+        let mut delta_mapping: Vec<ValueId> = vec![0; self.dict.len()];
+        let mut main_pos = 0;
+        let mut delta_pos = 0;
+        while main_pos < main_dict_len || delta_pos < delta_vids.len() {
+            let main_val = if main_pos < main_dict_len { Some(main.dict.lookup(main_pos as ValueId)) } else { None };
+            let delta_val = if delta_pos < delta_vids.len() { Some(self.dict.lookup(delta_vids[delta_pos])) } else { None };
+            let take_main = match (&main_val, &delta_val) {
+                (Some(m), Some(d)) => m.partial_cmp(d) != Some(Ordering::Greater),
+                (Some(_), None) => true,
+                _ => false
+            };
+            let val = if take_main { main_val.unwrap() } else { delta_val.unwrap() };
+            if entries.last() != Some(&val) {
+                entries.push(val);
+            }
+            let new_vid = (entries.len() - 1) as ValueId;
+            if take_main {
+                main_mapping.push(new_vid);
+                main_pos += 1;
+            } else {
+                delta_mapping[delta_vids[delta_pos] as usize] = new_vid;
+                delta_pos += 1;
+            }
+        }
+
+        // remap the value ids of both stores
+        let new_null_value_id = entries.len() as ValueId;
+        let main_null_value_id = main.null_value_id();
+        let mut data = Vec::with_capacity(main.data.len() + rows);
+        for vid in main.data.iter() {
+            data.push(if vid == main_null_value_id { new_null_value_id } else { main_mapping[vid as usize] });
+        }
+        for vid in self.data[0..rows].iter() {
+            data.push(if *vid == self.null_value_id() { new_null_value_id } else { delta_mapping[*vid as usize] });
+        }
+        Ok(Box::new(MainAttributeValueContainer::from_value_ids(&data, T::create_dict(entries))))
+    }
+
+    fn split_off(&self, from: usize) -> Box<dyn DynDeltaAttributeValueContainer + Send + Sync> {
+        let mut result = DeltaAttributeValueContainer::<T>::new();
+        for i in from..self.data.len() {
+            result.append(self.get(i));
+        }
+        Box::new(result)
+    }
+}
@@ -0,0 +1,71 @@
+use super::avc::{create_avc, DynAttributeValueContainer, Encoding};
+use super::frame_of_reference_avc::FrameOfReferenceAttributeValueContainer;
+use super::lz4_avc::Lz4AttributeValueContainer;
+use super::run_length_avc::RunLengthAttributeValueContainer;
+use super::unencoded_avc::UnencodedAttributeValueContainer;
+use super::value::ValueType;
+
+// the encoding selector tries all encodings on a sample of the column: up to SAMPLE_SIZE rows, taken as SAMPLE_RUNS contiguous
+//  ranges spread over the column (so that runs and sortedness are preserved)
+const SAMPLE_SIZE: usize = 4096;
+const SAMPLE_RUNS: usize = 4;
+// main store encodings, ordered by access speed (fastest first)
+const CANDIDATES: [Encoding; 5] = [Encoding::Unencoded, Encoding::Dictionary, Encoding::FrameOfReference, Encoding::RunLength, Encoding::Lz4];
+// a faster encoding is chosen over the smallest one if it is at most this much larger
+const SIZE_TOLERANCE: f64 = 1.1;
+
+// number of distinct non-NULL values
+pub fn count_distinct<T: ValueType>(column: &[Option<T>]) -> usize {
+    let mut values: Vec<&T> = column.iter().flatten().collect();
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    values.dedup_by(|a, b| a == b);
+    values.len()
+}
+
+// creates a container with the given encoding, None if the encoding is not applicable to the column
+pub fn encode_as<T: ValueType>(encoding: Encoding, column: &[Option<T>]) -> Option<Box<dyn DynAttributeValueContainer + Send + Sync>> {
+    match encoding {
+        Encoding::Dictionary => Some(Box::new(create_avc(column))),
+        Encoding::Unencoded => Some(Box::new(UnencodedAttributeValueContainer::new(column))),
+        Encoding::RunLength => Some(Box::new(RunLengthAttributeValueContainer::new(column))),
+        Encoding::FrameOfReference => {
+            FrameOfReferenceAttributeValueContainer::new(column).map(|avc| Box::new(avc) as Box<dyn DynAttributeValueContainer + Send + Sync>)
+        },
+        Encoding::Lz4 => Some(Box::new(Lz4AttributeValueContainer::new(column))),
+        // only used by delta stores
+        Encoding::UnsortedDictionary => None
+    }
+}
+
+fn sample<T: ValueType>(column: &[Option<T>]) -> Vec<Option<T>> {
+    if column.len() <= SAMPLE_SIZE {
+        return column.to_vec()
+    }
+    let run_len = SAMPLE_SIZE / SAMPLE_RUNS;
+    let distance = column.len() / SAMPLE_RUNS;
+    let mut result = Vec::with_capacity(SAMPLE_SIZE);
+    for run in 0..SAMPLE_RUNS {
+        let start = run * distance;
+        result.extend_from_slice(&column[start..start + run_len]);
+    }
+    result
+}
+
+// the encoding the column should be stored in: the fastest one whose (extrapolated) size on the sample is close to the smallest
+pub fn select_encoding<T: ValueType>(column: &[Option<T>]) -> Encoding {
+    if column.is_empty() {
+        return Encoding::Dictionary
+    }
+    let sample = sample(column);
+    let scale = column.len() as f64 / sample.len() as f64;
+    let sizes: Vec<(Encoding, f64)> = CANDIDATES.iter()
+        .filter_map(|encoding| encode_as(*encoding, &sample).map(|avc| (*encoding, avc.memory_usage() as f64 * scale)))
+        .collect();
+    let smallest = sizes.iter().map(|(_, size)| *size).fold(f64::INFINITY, f64::min);
+    sizes.iter().find(|(_, size)| *size <= smallest * SIZE_TOLERANCE).unwrap().0
+}
+
+// encodes a column of a main store using the encoding chosen by select_encoding
+pub fn encode_column<T: ValueType>(column: &[Option<T>]) -> Box<dyn DynAttributeValueContainer + Send + Sync> {
+    encode_as(select_encoding(column), column).unwrap()
+}
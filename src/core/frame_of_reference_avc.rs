@@ -0,0 +1,158 @@
+use std::any::Any;
+use std::io;
+use std::marker::PhantomData;
+
+use super::avc::{AttributeValueContainer, DynAttributeValueContainer, Encoding};
+use super::bit_packed_vector::BitPackedVector;
+use super::buffer::Buffer;
+use super::column_file::{invalid_data, SegmentReader, SegmentWriter};
+use super::encoding::count_distinct;
+use super::statistics::ColumnStatistics;
+use super::value::{Value, ValueType};
+
+// number of values sharing a reference value
+const BLOCK_SIZE: usize = 2048;
+
+// integer values stored as bit-packed offsets to the minimum of their block
+//  only applicable to types with an integer representation (see ValueType::to_integer) and blocks with a range of at most 32 bits
+pub struct FrameOfReferenceAttributeValueContainer<T> {
+    references: Buffer<i64>,
+    offsets: Vec<BitPackedVector>,
+    // one bit per value, None if the column contains no NULLs
+    nulls: Option<BitPackedVector>,
+    len: usize,
+    distinct_count: usize,
+    value_type: PhantomData<T>
+}
+
+impl<T: ValueType> FrameOfReferenceAttributeValueContainer<T> {
+    // returns None if the type has no integer representation or the values of a block are too far apart
+    pub fn new(column: &[Option<T>]) -> Option<Self> {
+        let mut integers = Vec::with_capacity(column.len());
+        for val in column {
+            integers.push(match val {
+                Some(val) => Some(val.to_integer()?),
+                None => None
+            });
+        }
+
+        let mut references = Vec::with_capacity(column.len() / BLOCK_SIZE + 1);
+        let mut offsets = Vec::with_capacity(column.len() / BLOCK_SIZE + 1);
+        for block in integers.chunks(BLOCK_SIZE) {
+            let min = block.iter().flatten().min().cloned().unwrap_or(0);
+            let max = block.iter().flatten().max().cloned().unwrap_or(0);
+            let range = max.checked_sub(min)?;
+            if range > u32::MAX as i64 {
+                return None
+            }
+            // NULLs are stored as offset 0
+            let block_offsets: Vec<u32> = block.iter().map(|val| val.map_or(0, |val| (val - min) as u32)).collect();
+            references.push(min);
+            offsets.push(BitPackedVector::from_value_ids(&block_offsets, range as u32));
+        }
+        let nulls = if column.iter().any(|val| val.is_none()) {
+            let null_flags: Vec<u32> = column.iter().map(|val| val.is_none() as u32).collect();
+            Some(BitPackedVector::from_value_ids(&null_flags, 1))
+        } else {
+            None
+        };
+        Some(FrameOfReferenceAttributeValueContainer {
+            references: references.into(),
+            offsets,
+            nulls,
+            len: column.len(),
+            distinct_count: count_distinct(column),
+            value_type: PhantomData
+        })
+    }
+
+    pub fn write(&self, writer: &mut SegmentWriter) {
+        writer.write_len(self.len);
+        writer.write_len(self.distinct_count);
+        writer.write_slice(&self.references);
+        for offsets in self.offsets.iter() {
+            offsets.write(writer);
+        }
+        writer.write_u8(self.nulls.is_some() as u8);
+        if let Some(nulls) = &self.nulls {
+            nulls.write(writer);
+        }
+    }
+
+    // the references and bit-packed offsets are used directly from the mapped file
+    pub fn read(reader: &mut SegmentReader) -> io::Result<Self> {
+        let len = reader.read_len()?;
+        let distinct_count = reader.read_len()?;
+        let references: Buffer<i64> = reader.read_slice()?;
+        if references.len() != len.div_ceil(BLOCK_SIZE) {
+            return Err(invalid_data("invalid frame-of-reference encoding"))
+        }
+        let mut offsets = Vec::with_capacity(references.len());
+        for block in 0..references.len() {
+            let block_offsets = BitPackedVector::read(reader)?;
+            if block_offsets.len() != std::cmp::min(BLOCK_SIZE, len - block * BLOCK_SIZE) {
+                return Err(invalid_data("invalid frame-of-reference encoding"))
+            }
+            offsets.push(block_offsets);
+        }
+        let nulls = if reader.read_u8()? != 0 { Some(BitPackedVector::read(reader)?) } else { None };
+        if nulls.as_ref().is_some_and(|nulls| nulls.len() != len) {
+            return Err(invalid_data("invalid frame-of-reference encoding"))
+        }
+        Ok(FrameOfReferenceAttributeValueContainer { references, offsets, nulls, len, distinct_count, value_type: PhantomData })
+    }
+
+    // the offsets are checked when the container is read, so unlike lookup, this cannot fail
+    fn get(&self, i: usize) -> Option<T> {
+        assert!(i < self.len, "Index out of bounds");
+        if let Some(nulls) = &self.nulls {
+            if nulls.get(i) == 1 {
+                return None
+            }
+        }
+        let block = i / BLOCK_SIZE;
+        let offset = self.offsets[block].get(i % BLOCK_SIZE);
+        T::from_integer(self.references[block] + offset as i64)
+    }
+}
+
+impl<T: ValueType> DynAttributeValueContainer for FrameOfReferenceAttributeValueContainer<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn distinct_count(&self) -> usize {
+        self.distinct_count
+    }
+
+    fn get_value(&self, i: usize) -> io::Result<Value> {
+        Ok(match self.get(i) {
+            Some(val) => val.into_value(),
+            None => Value::Null
+        })
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding::FrameOfReference
+    }
+
+    fn memory_usage(&self) -> usize {
+        let offsets_size: usize = self.offsets.iter().map(|offsets| offsets.memory_usage() + std::mem::size_of::<BitPackedVector>()).sum();
+        let nulls_size = self.nulls.as_ref().map_or(0, |nulls| nulls.memory_usage());
+        self.references.len() * std::mem::size_of::<i64>() + offsets_size + nulls_size
+    }
+
+    fn statistics(&self) -> io::Result<ColumnStatistics> {
+        Ok(ColumnStatistics::from_values((0..self.len()).map(|i| self.get(i)), self.distinct_count()))
+    }
+}
+
+impl<T: ValueType> AttributeValueContainer<T> for FrameOfReferenceAttributeValueContainer<T> {
+    fn lookup(&self, i: usize) -> io::Result<Option<T>> {
+        Ok(self.get(i))
+    }
+}
@@ -0,0 +1,214 @@
+use std::io;
+
+use super::avc::{Dict, ValueId};
+use super::buffer::Buffer;
+use super::column_file::{invalid_data, SegmentReader, SegmentWriter};
+
+// number of strings per block, only the first string of a block is stored completely
+const BLOCK_SIZE: usize = 16;
+
+// sorted string dictionary using front coding: each string (except for the first of a block) is stored as the length of the prefix
+//  it shares with its predecessor followed by the remaining suffix
+//  the first strings of the blocks serve as headers, allowing for a binary search over the blocks
+pub struct FrontCodedDict {
+    data: Buffer<u8>,
+    // byte offset of each block in data
+    block_offsets: Buffer<u64>,
+    len: usize
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+// None if the bytes end before the value or the value does not fit into usize
+fn read_varint(buf: &[u8], offset: &mut usize) -> Option<usize> {
+    let mut result: usize = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *buf.get(*offset)?;
+        *offset += 1;
+        let bits = ((byte & 0x7f) as usize).checked_shl(shift).filter(|bits| bits >> shift == (byte & 0x7f) as usize)?;
+        result |= bits;
+        if byte & 0x80 == 0 {
+            return Some(result)
+        }
+        shift += 7;
+    }
+}
+
+// the next len bytes
+fn read_bytes<'a>(buf: &'a [u8], offset: &mut usize, len: usize) -> Option<&'a [u8]> {
+    let bytes = buf.get(*offset..offset.checked_add(len)?)?;
+    *offset += len;
+    Some(bytes)
+}
+
+impl FrontCodedDict {
+    // entries need to be sorted and free of duplicates
+    pub fn new(entries: &[String]) -> Self {
+        let mut data = Vec::new();
+        let mut block_offsets = Vec::with_capacity(entries.len() / BLOCK_SIZE + 1);
+        let mut previous: &[u8] = &[];
+        for (i, entry) in entries.iter().enumerate() {
+            let entry = entry.as_bytes();
+            if i % BLOCK_SIZE == 0 {
+                block_offsets.push(data.len() as u64);
+                write_varint(&mut data, entry.len());
+                data.extend_from_slice(entry);
+            } else {
+                let prefix_len = previous.iter().zip(entry.iter()).take_while(|(a, b)| a == b).count();
+                write_varint(&mut data, prefix_len);
+                write_varint(&mut data, entry.len() - prefix_len);
+                data.extend_from_slice(&entry[prefix_len..]);
+            }
+            previous = entry;
+        }
+        data.shrink_to_fit();
+        FrontCodedDict { data: data.into(), block_offsets: block_offsets.into(), len: entries.len() }
+    }
+
+    pub fn write(&self, writer: &mut SegmentWriter) {
+        writer.write_len(self.len);
+        writer.write_bytes(&self.data);
+        writer.write_slice(&self.block_offsets);
+    }
+
+    // the strings are decoded from the mapped file on lookup, so all of them are decoded once to check that they are valid
+    pub fn read(reader: &mut SegmentReader) -> io::Result<Self> {
+        let len = reader.read_len()?;
+        let data = reader.read_slice()?;
+        let block_offsets: Buffer<u64> = reader.read_slice()?;
+        if block_offsets.len() != len.div_ceil(BLOCK_SIZE) || block_offsets.iter().any(|offset| *offset as usize >= data.len()) {
+            return Err(invalid_data("invalid string dictionary"))
+        }
+        let dict = FrontCodedDict { data, block_offsets, len };
+        for block in 0..dict.block_offsets.len() {
+            let mut valid = true;
+            let decoded = dict.try_decode_block(block, |_, entry| {
+                valid = std::str::from_utf8(entry).is_ok();
+                valid
+            });
+            if decoded.is_none() || !valid {
+                return Err(invalid_data("invalid string dictionary"))
+            }
+        }
+        Ok(dict)
+    }
+
+    fn block_header(&self, block: usize) -> &[u8] {
+        let mut offset = self.block_offsets[block] as usize;
+        read_varint(&self.data, &mut offset).and_then(|len| read_bytes(&self.data, &mut offset, len)).expect("invalid string dictionary")
+    }
+
+    // dictionaries are valid when they are created or read
+    fn decode_block<F: FnMut(usize, &[u8]) -> bool>(&self, block: usize, f: F) {
+        self.try_decode_block(block, f).expect("invalid string dictionary")
+    }
+
+    // decodes the strings of a block, calling f with the index and bytes of each string until it returns false
+    //  None if the block is invalid
+    fn try_decode_block<F: FnMut(usize, &[u8]) -> bool>(&self, block: usize, mut f: F) -> Option<()> {
+        let mut offset = self.block_offsets[block] as usize;
+        let len = read_varint(&self.data, &mut offset)?;
+        let mut current = read_bytes(&self.data, &mut offset, len)?.to_vec();
+        let first = block * BLOCK_SIZE;
+        let end = std::cmp::min(first + BLOCK_SIZE, self.len);
+        if !f(first, &current) {
+            return Some(())
+        }
+        for i in first + 1..end {
+            let prefix_len = read_varint(&self.data, &mut offset)?;
+            let suffix_len = read_varint(&self.data, &mut offset)?;
+            if prefix_len > current.len() {
+                return None
+            }
+            current.truncate(prefix_len);
+            current.extend_from_slice(read_bytes(&self.data, &mut offset, suffix_len)?);
+            if !f(i, &current) {
+                return Some(())
+            }
+        }
+        Some(())
+    }
+
+    // index of the first entry that is greater than value (if inclusive) or not less than value (otherwise)
+    fn bound(&self, value: &str, inclusive: bool) -> ValueId {
+        let value = value.as_bytes();
+        let before_bound = |entry: &[u8]| if inclusive { entry <= value } else { entry < value };
+        // binary search for the number of blocks whose header is before the bound
+        //  the last of these blocks is the only one that may contain the bound (besides the start of the next block)
+        let mut low = 0;
+        let mut high = self.block_offsets.len();
+        while low < high {
+            let mid = (low + high) / 2;
+            if before_bound(self.block_header(mid)) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        let block = low;
+        if block == 0 {
+            return 0
+        }
+        let mut result = std::cmp::min(block * BLOCK_SIZE, self.len);
+        self.decode_block(block - 1, |i, entry| {
+            if before_bound(entry) {
+                true
+            } else {
+                result = i;
+                false
+            }
+        });
+        result as ValueId
+    }
+}
+
+impl Dict<String> for FrontCodedDict {
+    fn lookup(&self, i: ValueId) -> String {
+        let i = i as usize;
+        assert!(i < self.len, "Invalid value id");
+        let mut result = Vec::new();
+        self.decode_block(i / BLOCK_SIZE, |j, entry| {
+            if j == i {
+                result = entry.to_vec();
+                false
+            } else {
+                true
+            }
+        });
+        // the entries are checked to be valid UTF-8 when the dictionary is read
+        String::from_utf8(result).expect("invalid string dictionary")
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    // size of the compressed dictionary in bytes
+    fn memory_usage(&self) -> usize {
+        self.data.len() + self.block_offsets.len() * std::mem::size_of::<u64>()
+    }
+
+    fn lower_bound(&self, value: &String) -> ValueId {
+        self.bound(value, false)
+    }
+
+    fn upper_bound(&self, value: &String) -> ValueId {
+        self.bound(value, true)
+    }
+
+    fn find(&self, value: &String) -> Option<ValueId> {
+        let vid = self.lower_bound(value);
+        if (vid as usize) < self.len && self.lookup(vid) == *value {
+            Some(vid)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::cmp::Ordering;
+use std::io;
+
+use super::avc::{DynAttributeValueContainer, MainAttributeValueContainer};
+use super::date::Date;
+use super::decimal::Decimal;
+use super::scan::{typed_container, ScanPredicate};
+use super::table::{ColumnId, DataType, Table};
+use super::value::{Value, ValueType};
+
+// number of buckets of the histograms created by ANALYZE
+pub const HISTOGRAM_BUCKETS: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct HistogramBucket {
+    // smallest and largest value of the bucket (both inclusive)
+    pub lower: Value,
+    pub upper: Value,
+    pub row_count: usize,
+    pub distinct_count: usize
+}
+
+// equi-depth histogram of a column of a table: the buckets cover ranges of values holding about the same number of rows
+//  (the rows of a value are never split between buckets, so frequent values can make a bucket larger)
+#[derive(Debug, Clone)]
+pub struct ColumnHistogram {
+    pub null_count: usize,
+    pub buckets: Vec<HistogramBucket>
+}
+
+// the number of rows of each distinct value of a container, in ascending order of the values
+//  dictionary-encoded containers count the value ids, as their dictionary is sorted already
+fn chunk_frequencies<T: ValueType>(avc: &dyn DynAttributeValueContainer, null_count: &mut usize) -> io::Result<Vec<(T, usize)>> {
+    if let Some(avc) = avc.as_any().downcast_ref::<MainAttributeValueContainer<T>>() {
+        let null_value_id = avc.null_value_id() as usize;
+        let mut counts = vec![0; null_value_id + 1];
+        for vid in avc.data.iter() {
+            counts[vid as usize] += 1;
+        }
+        *null_count += counts[null_value_id];
+        return Ok(counts[..null_value_id].iter().enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(vid, count)| (avc.dict.lookup(vid as u32), *count))
+            .collect())
+    }
+    let typed = typed_container::<T>(avc);
+    let mut values = Vec::with_capacity(avc.len());
+    for i in 0..avc.len() {
+        match typed.lookup(i)? {
+            Some(value) => values.push((value, 1)),
+            None => *null_count += 1
+        }
+    }
+    Ok(merge_frequencies(values))
+}
+
+// sorts the values and adds up the rows of equal values
+fn merge_frequencies<T: ValueType>(mut frequencies: Vec<(T, usize)>) -> Vec<(T, usize)> {
+    frequencies.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let mut result: Vec<(T, usize)> = Vec::with_capacity(frequencies.len());
+    for (value, count) in frequencies {
+        match result.last_mut() {
+            Some((last, last_count)) if *last == value => *last_count += count,
+            _ => result.push((value, count))
+        }
+    }
+    result
+}
+
+fn analyze<T: ValueType>(table: &Table, column_id: ColumnId, bucket_count: usize) -> io::Result<ColumnHistogram> {
+    let mut null_count = 0;
+    let mut frequencies = Vec::new();
+    for chunk in table.chunks() {
+        frequencies.extend(chunk_frequencies::<T>(chunk.column(column_id), &mut null_count)?);
+        if let Some(delta) = chunk.delta_column(column_id) {
+            frequencies.extend(chunk_frequencies::<T>(delta, &mut null_count)?);
+        }
+    }
+    Ok(ColumnHistogram::build(merge_frequencies(frequencies), null_count, bucket_count))
+}
+
+// position of a value between two others as a number between 0 and 1, 0.5 for values that are not numeric
+fn interpolate(value: &Value, lower: &Value, upper: &Value) -> f64 {
+    fn to_f64(value: &Value) -> Option<f64> {
+        match value {
+            Value::BigInt(val) => Some(*val as f64),
+            Value::Double(val) => Some(*val),
+            Value::Decimal(val) => Some(val.to_f64()),
+            Value::Date(val) => Some(val.0 as f64),
+            _ => None
+        }
+    }
+    match (to_f64(value), to_f64(lower), to_f64(upper)) {
+        (Some(value), Some(lower), Some(upper)) if upper > lower => ((value - lower) / (upper - lower)).clamp(0.0, 1.0),
+        _ => 0.5
+    }
+}
+
+impl ColumnHistogram {
+    // creates the histogram from the number of rows of each distinct value, in ascending order of the values
+    pub fn build<T: ValueType>(frequencies: Vec<(T, usize)>, null_count: usize, bucket_count: usize) -> Self {
+        let row_count: usize = frequencies.iter().map(|(_, count)| count).sum();
+        let depth = std::cmp::max(1, row_count.div_ceil(std::cmp::max(1, bucket_count)));
+        let mut buckets = Vec::new();
+        let mut bucket: Option<HistogramBucket> = None;
+        for (value, count) in frequencies {
+            let value = value.into_value();
+            let current = bucket.get_or_insert_with(|| HistogramBucket { lower: value.clone(), upper: Value::Null, row_count: 0, distinct_count: 0 });
+            current.upper = value;
+            current.row_count += count;
+            current.distinct_count += 1;
+            if current.row_count >= depth {
+                buckets.push(bucket.take().unwrap());
+            }
+        }
+        buckets.extend(bucket);
+        ColumnHistogram { null_count, buckets }
+    }
+
+    // rows including NULLs
+    pub fn row_count(&self) -> usize {
+        self.null_count + self.non_null_count()
+    }
+
+    pub fn non_null_count(&self) -> usize {
+        self.buckets.iter().map(|bucket| bucket.row_count).sum()
+    }
+
+    pub fn distinct_count(&self) -> usize {
+        self.buckets.iter().map(|bucket| bucket.distinct_count).sum()
+    }
+
+    // estimated number of non-NULL rows with values below the given one (or equal to it if inclusive), None if the value is
+    //  not comparable to the values of the column
+    //  within a bucket, the values are assumed to be distributed uniformly and each distinct value to have the same number of rows
+    fn rows_below(&self, value: &Value, inclusive: bool) -> Option<f64> {
+        let mut rows = 0.0;
+        for bucket in &self.buckets {
+            let rows_per_value = bucket.row_count as f64 / bucket.distinct_count as f64;
+            let equal_rows = if inclusive { rows_per_value } else { 0.0 };
+            match (value.compare(&bucket.lower)?, value.compare(&bucket.upper)?) {
+                (Ordering::Less, _) => break,
+                (_, Ordering::Greater) => rows += bucket.row_count as f64,
+                (Ordering::Equal, _) => {
+                    rows += equal_rows;
+                    break
+                },
+                (_, Ordering::Equal) => {
+                    rows += bucket.row_count as f64 - rows_per_value + equal_rows;
+                    break
+                },
+                _ => {
+                    rows += (bucket.row_count as f64 - rows_per_value) * interpolate(value, &bucket.lower, &bucket.upper) + equal_rows;
+                    break
+                }
+            }
+        }
+        Some(rows)
+    }
+
+    // estimated fraction of all rows (including NULLs) matching the predicate, None if the values of the predicate are not
+    //  comparable to the values of the column
+    pub fn selectivity(&self, predicate: &ScanPredicate<Value>) -> Option<f64> {
+        let row_count = self.row_count() as f64;
+        if row_count == 0.0 {
+            return Some(0.0)
+        }
+        let non_null_count = self.non_null_count() as f64;
+        let rows = match predicate {
+            ScanPredicate::Equals(value) => {
+                let bucket = self.buckets.iter().find(|bucket| {
+                    value.compare(&bucket.lower) != Some(Ordering::Less) && value.compare(&bucket.upper) != Some(Ordering::Greater)
+                });
+                match bucket {
+                    Some(bucket) => bucket.row_count as f64 / bucket.distinct_count as f64,
+                    None => {
+                        value.compare(&self.buckets.first()?.lower)?;
+                        0.0
+                    }
+                }
+            },
+            ScanPredicate::LessThan(value) => self.rows_below(value, false)?,
+            ScanPredicate::LessThanEquals(value) => self.rows_below(value, true)?,
+            ScanPredicate::GreaterThan(value) => non_null_count - self.rows_below(value, true)?,
+            ScanPredicate::GreaterThanEquals(value) => non_null_count - self.rows_below(value, false)?,
+            ScanPredicate::Between(low, high) => self.rows_below(high, true)? - self.rows_below(low, false)?,
+            ScanPredicate::IsNull => self.null_count as f64,
+            ScanPredicate::IsNotNull => non_null_count
+        };
+        Some((rows / row_count).clamp(0.0, 1.0))
+    }
+}
+
+// creates the histogram of a column from the values of all chunks (main and delta stores)
+//  fails if the values of a chunk cannot be read
+pub fn analyze_column(table: &Table, column_id: ColumnId, bucket_count: usize) -> io::Result<ColumnHistogram> {
+    match table.column_definition(column_id).data_type {
+        DataType::BigInt => analyze::<i64>(table, column_id, bucket_count),
+        DataType::Double => analyze::<f64>(table, column_id, bucket_count),
+        DataType::Char(_) | DataType::Varchar(_) | DataType::Text => analyze::<String>(table, column_id, bucket_count),
+        DataType::Date => analyze::<Date>(table, column_id, bucket_count),
+        DataType::Decimal(_, _) => analyze::<Decimal>(table, column_id, bucket_count),
+        DataType::Boolean => analyze::<bool>(table, column_id, bucket_count)
+    }
+}
@@ -0,0 +1,378 @@
+use std::any::Any;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::io;
+use std::ops::Bound;
+
+use super::avc::{create_avc, value_id_range, Dict, DynAttributeValueContainer, MainAttributeValueContainer, ValueId};
+use super::bit_packed_vector::BitPackedVector;
+use super::date::Date;
+use super::decimal::Decimal;
+use super::scan::{typed_container, ChunkOffset, ScanPredicate};
+use super::table::{ColumnId, DataType};
+use super::value::{Value, ValueType};
+
+// secondary indexes on a single column, which return the positions of the rows matching a predicate without scanning the column
+//  every chunk has its own index: a group-key index of its main store and a B-tree of its delta store
+//  the group-key index maps each value id of the dictionary of the main store to the positions of its rows, main stores that are
+//  not dictionary-encoded get a dictionary of their own
+//  the index of the main store is rebuilt when the delta is merged, the B-tree is updated on every insert
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexDefinition {
+    pub name: String,
+    pub column_id: ColumnId
+}
+
+pub struct GroupKeyIndex<T> {
+    // sorted distinct values of the main store, None if the index uses the dictionary of the (dictionary-encoded) main store
+    dict: Option<Box<dyn Dict<T> + Send + Sync>>,
+    // start of the postings of each value id, the NULL value id and the end of the postings
+    offsets: Vec<u32>,
+    // positions of the rows, grouped by value id (ascending within each group)
+    postings: Vec<ChunkOffset>
+}
+
+impl<T: ValueType> GroupKeyIndex<T> {
+    // fails if the values of a main store that is not dictionary-encoded cannot be read
+    pub fn new(avc: &dyn DynAttributeValueContainer) -> io::Result<Self> {
+        if let Some(main) = avc.as_any().downcast_ref::<MainAttributeValueContainer<T>>() {
+            let (offsets, postings) = GroupKeyIndex::<T>::group(&main.data, main.null_value_id());
+            return Ok(GroupKeyIndex { dict: None, offsets, postings })
+        }
+        let typed = typed_container::<T>(avc);
+        let values = (0..avc.len()).map(|i| typed.lookup(i)).collect::<io::Result<Vec<_>>>()?;
+        let main = create_avc(&values);
+        let (offsets, postings) = GroupKeyIndex::<T>::group(&main.data, main.null_value_id());
+        Ok(GroupKeyIndex { dict: Some(main.dict), offsets, postings })
+    }
+
+    // index of an empty dictionary-encoded main store
+    fn empty() -> Self {
+        GroupKeyIndex { dict: None, offsets: vec![0, 0], postings: Vec::new() }
+    }
+
+    // counting sort of the positions by value id
+    fn group(value_ids: &BitPackedVector, null_value_id: ValueId) -> (Vec<u32>, Vec<ChunkOffset>) {
+        let mut offsets = vec![0u32; null_value_id as usize + 2];
+        for vid in value_ids.iter() {
+            offsets[vid as usize + 1] += 1;
+        }
+        for i in 1..offsets.len() {
+            offsets[i] += offsets[i - 1];
+        }
+        let mut next = offsets.clone();
+        let mut postings = vec![0; *offsets.last().unwrap() as usize];
+        for (offset, vid) in value_ids.iter().enumerate() {
+            postings[next[vid as usize] as usize] = offset as ChunkOffset;
+            next[vid as usize] += 1;
+        }
+        (offsets, postings)
+    }
+
+    // positions of the rows of the given main store (the one the index was created for) matching the predicate, ascending
+    pub fn lookup(&self, main: &dyn DynAttributeValueContainer, predicate: &ScanPredicate<T>) -> Vec<ChunkOffset> {
+        let dict = match &self.dict {
+            Some(dict) => dict.as_ref(),
+            None => {
+                let main = main.as_any().downcast_ref::<MainAttributeValueContainer<T>>().expect("index does not match the main store");
+                main.dict.as_ref()
+            }
+        };
+        let range = value_id_range(dict, predicate);
+        if range.start >= range.end {
+            return Vec::new()
+        }
+        let mut result = self.postings[self.offsets[range.start as usize] as usize..self.offsets[range.end as usize] as usize].to_vec();
+        result.sort_unstable();
+        result
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        let dict_size = self.dict.as_ref().map_or(0, |dict| dict.memory_usage());
+        dict_size + (self.offsets.len() + self.postings.len()) * std::mem::size_of::<u32>()
+    }
+}
+
+// values of the column type ordered like in the dictionaries (values that are not comparable, i.e., NaN, are treated as equal)
+struct IndexKey<T>(T);
+
+impl<T: PartialOrd> Ord for IndexKey<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for IndexKey<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: PartialOrd> PartialEq for IndexKey<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: PartialOrd> Eq for IndexKey<T> {}
+
+// B-tree of the delta store, mapping each value to the positions of its rows within the delta (in insertion order)
+pub struct DeltaIndex<T> {
+    tree: BTreeMap<IndexKey<T>, Vec<ChunkOffset>>,
+    nulls: Vec<ChunkOffset>
+}
+
+impl<T: ValueType> DeltaIndex<T> {
+    pub fn new() -> Self {
+        DeltaIndex { tree: BTreeMap::new(), nulls: Vec::new() }
+    }
+
+    pub fn insert(&mut self, value: Option<T>, offset: ChunkOffset) {
+        match value {
+            Some(value) => self.tree.entry(IndexKey(value)).or_default().push(offset),
+            None => self.nulls.push(offset)
+        }
+    }
+
+    // positions of the rows of the delta store matching the predicate, ascending
+    pub fn lookup(&self, predicate: &ScanPredicate<T>) -> Vec<ChunkOffset> {
+        let key = |value: &T| IndexKey(value.clone());
+        let range = match predicate {
+            ScanPredicate::Equals(value) => (Bound::Included(key(value)), Bound::Included(key(value))),
+            ScanPredicate::LessThan(value) => (Bound::Unbounded, Bound::Excluded(key(value))),
+            ScanPredicate::LessThanEquals(value) => (Bound::Unbounded, Bound::Included(key(value))),
+            ScanPredicate::GreaterThan(value) => (Bound::Excluded(key(value)), Bound::Unbounded),
+            ScanPredicate::GreaterThanEquals(value) => (Bound::Included(key(value)), Bound::Unbounded),
+            // BTreeMap::range panics on reversed bounds
+            ScanPredicate::Between(low, high) if key(low) > key(high) => return Vec::new(),
+            ScanPredicate::Between(low, high) => (Bound::Included(key(low)), Bound::Included(key(high))),
+            ScanPredicate::IsNull => return self.nulls.clone(),
+            ScanPredicate::IsNotNull => (Bound::Unbounded, Bound::Unbounded)
+        };
+        let mut result: Vec<ChunkOffset> = self.tree.range(range).flat_map(|(_, offsets)| offsets.iter().cloned()).collect();
+        result.sort_unstable();
+        result
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        let heap_size: usize = self.tree.iter().map(|(key, offsets)| key.0.heap_size() + offsets.len() * std::mem::size_of::<ChunkOffset>()).sum();
+        self.tree.len() * std::mem::size_of::<(T, Vec<ChunkOffset>)>() + heap_size + self.nulls.len() * std::mem::size_of::<ChunkOffset>()
+    }
+}
+
+// the index of a column of type T within a chunk
+pub struct ChunkIndex<T> {
+    pub main: GroupKeyIndex<T>,
+    pub delta: DeltaIndex<T>
+}
+
+// type-independent access to the index of a chunk, used to maintain it
+pub trait DynChunkIndex {
+    fn as_any(&self) -> &dyn Any;
+    // adds a row appended to the delta store at the given position within the delta
+    fn insert_delta(&mut self, offset: ChunkOffset, value: &Value);
+    fn memory_usage(&self) -> usize;
+}
+
+impl<T: ValueType> DynChunkIndex for ChunkIndex<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn insert_delta(&mut self, offset: ChunkOffset, value: &Value) {
+        self.delta.insert(T::from_value(value), offset);
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.main.memory_usage() + self.delta.memory_usage()
+    }
+}
+
+fn create_typed_index<T: ValueType>(main: &dyn DynAttributeValueContainer, delta: Option<&dyn DynAttributeValueContainer>) -> io::Result<Box<dyn DynChunkIndex + Send + Sync>> {
+    let mut index = ChunkIndex { main: GroupKeyIndex::<T>::new(main)?, delta: DeltaIndex::new() };
+    if let Some(delta) = delta {
+        let typed = typed_container::<T>(delta);
+        for i in 0..delta.len() {
+            index.delta.insert(typed.lookup(i)?, i as ChunkOffset);
+        }
+    }
+    Ok(Box::new(index))
+}
+
+fn empty_typed_index<T: ValueType>() -> Box<dyn DynChunkIndex + Send + Sync> {
+    Box::new(ChunkIndex { main: GroupKeyIndex::<T>::empty(), delta: DeltaIndex::new() })
+}
+
+// creates the index of a column of a chunk without any rows (see Chunk::with_delta), which reads no values and cannot fail
+pub fn create_empty_chunk_index(data_type: DataType) -> Box<dyn DynChunkIndex + Send + Sync> {
+    match data_type {
+        DataType::BigInt => empty_typed_index::<i64>(),
+        DataType::Double => empty_typed_index::<f64>(),
+        DataType::Char(_) | DataType::Varchar(_) | DataType::Text => empty_typed_index::<String>(),
+        DataType::Date => empty_typed_index::<Date>(),
+        DataType::Decimal(_, _) => empty_typed_index::<Decimal>(),
+        DataType::Boolean => empty_typed_index::<bool>()
+    }
+}
+
+// creates the index of a column of a chunk from its main store and delta store (if any)
+pub fn create_chunk_index(data_type: DataType, main: &dyn DynAttributeValueContainer, delta: Option<&dyn DynAttributeValueContainer>) -> io::Result<Box<dyn DynChunkIndex + Send + Sync>> {
+    match data_type {
+        DataType::BigInt => create_typed_index::<i64>(main, delta),
+        DataType::Double => create_typed_index::<f64>(main, delta),
+        DataType::Char(_) | DataType::Varchar(_) | DataType::Text => create_typed_index::<String>(main, delta),
+        DataType::Date => create_typed_index::<Date>(main, delta),
+        DataType::Decimal(_, _) => create_typed_index::<Decimal>(main, delta),
+        DataType::Boolean => create_typed_index::<bool>(main, delta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::RwLock;
+
+    use super::*;
+    use super::super::avc::Encoding;
+    use super::super::encoding::encode_as;
+    use super::super::scan::{index_scan, scan_table};
+    use super::super::table::{merge_chunk, ColumnDefinition, Table};
+
+    const CHUNK_SIZE: usize = 1000;
+
+    // every variant with values below, within and above the ones of the column (and a reversed range)
+    fn predicates<T: Clone>(values: &[T]) -> Vec<ScanPredicate<T>> {
+        let mut predicates = vec![ScanPredicate::IsNull, ScanPredicate::IsNotNull];
+        for (i, value) in values.iter().enumerate() {
+            predicates.push(ScanPredicate::Equals(value.clone()));
+            predicates.push(ScanPredicate::LessThan(value.clone()));
+            predicates.push(ScanPredicate::LessThanEquals(value.clone()));
+            predicates.push(ScanPredicate::GreaterThan(value.clone()));
+            predicates.push(ScanPredicate::GreaterThanEquals(value.clone()));
+            predicates.push(ScanPredicate::Between(value.clone(), values[(i + 2) % values.len()].clone()));
+        }
+        predicates
+    }
+
+    fn integer_predicates() -> Vec<ScanPredicate<i64>> {
+        predicates(&[-1, 0, 17, 50, 99, 100, 250])
+    }
+
+    fn value(i: usize) -> Option<i64> {
+        if i.is_multiple_of(11) { None } else { Some((i * 7919 % 100) as i64) }
+    }
+
+    fn rows(range: std::ops::Range<usize>) -> Vec<Vec<Value>> {
+        range.map(|i| vec![value(i).map_or(Value::Null, Value::BigInt), Value::BigInt((i % 250) as i64)]).collect()
+    }
+
+    fn table() -> Table {
+        let columns = vec![ColumnDefinition::new("a", DataType::BigInt, true), ColumnDefinition::new("b", DataType::BigInt, false)];
+        let mut table = Table::new(columns, CHUNK_SIZE);
+        table.create_index("a_index", 0).unwrap();
+        table
+    }
+
+    fn assert_index_matches_scan(table: &Table) {
+        for (index_id, index) in table.indexes().iter().enumerate() {
+            for predicate in integer_predicates() {
+                let expected = scan_table(table, index.column_id, &predicate).unwrap();
+                let actual = index_scan(table, index_id, &predicate);
+                assert_eq!(actual.matches, expected.matches, "{} {:?}", index.name, predicate);
+                assert_eq!(actual.pruned_chunks, expected.pruned_chunks, "{} {:?}", index.name, predicate);
+            }
+        }
+    }
+
+    fn assert_group_key_index_matches_scan<T: ValueType + Debug>(values: &[Option<T>], predicates: &[ScanPredicate<T>]) {
+        for encoding in [Encoding::Dictionary, Encoding::Unencoded, Encoding::RunLength, Encoding::FrameOfReference, Encoding::Lz4] {
+            let avc = match encode_as(encoding, values) {
+                Some(avc) => avc,
+                None => continue
+            };
+            let index = GroupKeyIndex::<T>::new(avc.as_ref()).unwrap();
+            for predicate in predicates {
+                let expected = typed_container::<T>(avc.as_ref()).scan(predicate).unwrap();
+                assert_eq!(index.lookup(avc.as_ref(), predicate), expected, "{:?} {:?}", encoding, predicate);
+            }
+        }
+    }
+
+    #[test]
+    fn group_key_indexes_match_scans_of_every_encoding() {
+        let values: Vec<Option<i64>> = (0..3000).map(value).collect();
+        assert_group_key_index_matches_scan(&values, &integer_predicates());
+        let strings: Vec<Option<String>> = values.iter().map(|value| value.map(|value| format!("value {}", value))).collect();
+        let bounds: Vec<String> = ["", "value 1", "value 17", "value 5", "value 99", "w"].iter().map(|s| s.to_string()).collect();
+        assert_group_key_index_matches_scan(&strings, &predicates(&bounds));
+        assert_group_key_index_matches_scan::<i64>(&[], &integer_predicates());
+        assert_group_key_index_matches_scan(&[None, None], &integer_predicates());
+    }
+
+    #[test]
+    fn delta_indexes_match_the_values() {
+        let mut index = DeltaIndex::new();
+        let values: Vec<Option<i64>> = (0..3000).map(value).collect();
+        for (offset, value) in values.iter().enumerate() {
+            index.insert(*value, offset as ChunkOffset);
+        }
+        for predicate in integer_predicates() {
+            let expected: Vec<ChunkOffset> = (0..values.len()).filter(|i| predicate.matches(values[*i].as_ref())).map(|i| i as ChunkOffset).collect();
+            assert_eq!(index.lookup(&predicate), expected, "{:?}", predicate);
+        }
+    }
+
+    #[test]
+    fn index_scans_match_table_scans() {
+        let table = RwLock::new(table());
+        table.write().unwrap().insert(rows(0..2500)).unwrap();
+        // delta stores only
+        assert_index_matches_scan(&table.read().unwrap());
+
+        merge_chunk(&table, 0).unwrap();
+        merge_chunk(&table, 2).unwrap();
+        // main store only, delta store only and main store of a chunk that still takes rows
+        assert_index_matches_scan(&table.read().unwrap());
+
+        table.write().unwrap().insert(rows(2500..2700)).unwrap();
+        table.write().unwrap().create_index("b_index", 1).unwrap();
+        table.write().unwrap().insert(rows(2700..3300)).unwrap();
+        // main and delta store
+        assert_index_matches_scan(&table.read().unwrap());
+
+        let chunks = table.read().unwrap().chunks_to_merge(1);
+        for chunk_id in chunks {
+            merge_chunk(&table, chunk_id).unwrap();
+        }
+        assert_index_matches_scan(&table.read().unwrap());
+    }
+
+    #[test]
+    fn index_scans_match_table_scans_with_inserts_during_merges() {
+        let table = RwLock::new(table());
+        let inserting = AtomicBool::new(true);
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                while inserting.load(Ordering::Relaxed) {
+                    let chunks = table.read().unwrap().chunks_to_merge(1);
+                    for chunk_id in chunks {
+                        merge_chunk(&table, chunk_id).unwrap();
+                    }
+                }
+            });
+            for batch in 0..100 {
+                table.write().unwrap().insert(rows(batch * 50..(batch + 1) * 50)).unwrap();
+                if batch == 50 {
+                    // the indexes of merges in progress are created again
+                    table.write().unwrap().create_index("b_index", 1).unwrap();
+                }
+                assert_index_matches_scan(&table.read().unwrap());
+            }
+            inserting.store(false, Ordering::Relaxed);
+        });
+        assert_eq!(table.read().unwrap().row_count(), 5000);
+        assert_index_matches_scan(&table.read().unwrap());
+    }
+}
@@ -0,0 +1,202 @@
+use std::io;
+
+// block compression in the LZ4 block format: a sequence of tokens, each consisting of literals that are copied as they are
+//  followed by a match (a copy of earlier output given by offset and length)
+//  token byte: high nibble = number of literals, low nibble = match length - MIN_MATCH, 15 means more length bytes follow
+
+const MIN_MATCH: usize = 4;
+// the last match has to start at least this many bytes before the end of the input
+const MATCH_LIMIT: usize = 12;
+// the last bytes of the input are always literals
+const LAST_LITERALS: usize = 5;
+const MAX_OFFSET: usize = 65535;
+const HASH_BITS: u32 = 12;
+// output reserved up front at most, uncompressed lengths come from files and larger outputs grow while they are decompressed
+const MAX_RESERVED_OUTPUT: usize = 1 << 20;
+
+fn read_u32(input: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes([input[pos], input[pos + 1], input[pos + 2], input[pos + 3]])
+}
+
+fn hash(sequence: u32) -> usize {
+    (sequence.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+}
+
+fn write_length(output: &mut Vec<u8>, mut len: usize) {
+    while len >= 255 {
+        output.push(255);
+        len -= 255;
+    }
+    output.push(len as u8);
+}
+
+fn write_sequence(output: &mut Vec<u8>, literals: &[u8], match_info: Option<(usize, usize)>) {
+    let literal_nibble = std::cmp::min(literals.len(), 15);
+    let match_nibble = match match_info {
+        Some((_, len)) => std::cmp::min(len - MIN_MATCH, 15),
+        None => 0
+    };
+    output.push((literal_nibble << 4 | match_nibble) as u8);
+    if literals.len() >= 15 {
+        write_length(output, literals.len() - 15);
+    }
+    output.extend_from_slice(literals);
+    if let Some((offset, len)) = match_info {
+        output.extend_from_slice(&(offset as u16).to_le_bytes());
+        if len - MIN_MATCH >= 15 {
+            write_length(output, len - MIN_MATCH - 15);
+        }
+    }
+}
+
+pub fn compress(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() / 2 + 16);
+    // position of the last occurrence of each hashed 4 byte sequence (plus one, zero means none)
+    let mut table = vec![0usize; 1 << HASH_BITS];
+    let mut anchor = 0;
+    let mut pos = 0;
+    while input.len() >= MATCH_LIMIT && pos + MATCH_LIMIT <= input.len() {
+        let sequence = read_u32(input, pos);
+        let slot = hash(sequence);
+        let candidate = table[slot];
+        table[slot] = pos + 1;
+        if candidate == 0 || pos - (candidate - 1) > MAX_OFFSET || read_u32(input, candidate - 1) != sequence {
+            pos += 1;
+            continue
+        }
+        let candidate = candidate - 1;
+        let max_len = input.len() - LAST_LITERALS - pos;
+        let mut len = MIN_MATCH;
+        while len < max_len && input[candidate + len] == input[pos + len] {
+            len += 1;
+        }
+        write_sequence(&mut output, &input[anchor..pos], Some((pos - candidate, len)));
+        pos += len;
+        anchor = pos;
+    }
+    write_sequence(&mut output, &input[anchor..], None);
+    output
+}
+
+fn corrupt(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("corrupt LZ4 block: {}", message))
+}
+
+// uncompressed_len is the exact size of the original input
+//  the input may come from a file, so all lengths and offsets are checked against the input and the output
+pub fn decompress(input: &[u8], uncompressed_len: usize) -> io::Result<Vec<u8>> {
+    let mut output: Vec<u8> = Vec::with_capacity(std::cmp::min(uncompressed_len, MAX_RESERVED_OUTPUT));
+    let mut pos = 0;
+    let read_length = |pos: &mut usize, mut len: usize| -> io::Result<usize> {
+        loop {
+            let byte = *input.get(*pos).ok_or_else(|| corrupt("length exceeds the input"))?;
+            *pos += 1;
+            len += byte as usize;
+            if byte != 255 {
+                return Ok(len)
+            }
+        }
+    };
+    while pos < input.len() {
+        let token = input[pos];
+        pos += 1;
+        let mut literal_len = (token >> 4) as usize;
+        if literal_len == 15 {
+            literal_len = read_length(&mut pos, literal_len)?;
+        }
+        let literals = pos.checked_add(literal_len).and_then(|end| input.get(pos..end)).ok_or_else(|| corrupt("literals exceed the input"))?;
+        if output.len() + literal_len > uncompressed_len {
+            return Err(corrupt("output exceeds the uncompressed length"))
+        }
+        output.extend_from_slice(literals);
+        pos += literal_len;
+        // the last sequence has no match
+        if pos >= input.len() {
+            break
+        }
+        let offset = match input.get(pos..pos + 2) {
+            Some(bytes) => u16::from_le_bytes([bytes[0], bytes[1]]) as usize,
+            None => return Err(corrupt("match offset exceeds the input"))
+        };
+        pos += 2;
+        let mut match_len = (token & 0x0f) as usize;
+        if match_len == 15 {
+            match_len = read_length(&mut pos, match_len)?;
+        }
+        match_len += MIN_MATCH;
+        if offset == 0 || offset > output.len() {
+            return Err(corrupt("match offset before the start of the output"))
+        }
+        if output.len() + match_len > uncompressed_len {
+            return Err(corrupt("output exceeds the uncompressed length"))
+        }
+        // matches may overlap with their own output (e.g., runs of a single byte), so copy byte by byte
+        let start = output.len() - offset;
+        for i in 0..match_len {
+            let byte = output[start + i];
+            output.push(byte);
+        }
+    }
+    if output.len() != uncompressed_len {
+        return Err(corrupt("output is shorter than the uncompressed length"))
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(input: &[u8]) {
+        let compressed = compress(input);
+        assert_eq!(decompress(&compressed, input.len()).unwrap(), input);
+    }
+
+    #[test]
+    fn compresses_and_decompresses() {
+        round_trip(b"");
+        round_trip(b"abc");
+        round_trip(&[7; 1000]);
+        let text: Vec<u8> = (0..5000).flat_map(|i: u32| format!("value {} of {};", i % 37, i % 5).into_bytes()).collect();
+        round_trip(&text);
+        let mut state = 1u32;
+        let random: Vec<u8> = (0..5000).map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            (state >> 24) as u8
+        }).collect();
+        round_trip(&random);
+    }
+
+    #[test]
+    fn rejects_corrupt_blocks() {
+        // match before the start of the output
+        assert!(decompress(&[0x00, 0x05, 0x00], 4).is_err());
+        // literals beyond the end of the input
+        assert!(decompress(&[0x50, b'a', b'b'], 5).is_err());
+        // length bytes beyond the end of the input
+        assert!(decompress(&[0xf0, 255, 255], 600).is_err());
+        // truncated match offset
+        assert!(decompress(&[0x10, b'a', 0x01], 5).is_err());
+        // offset 0
+        assert!(decompress(&[0x10, b'a', 0x00, 0x00, 0x00], 5).is_err());
+        // output longer or shorter than expected
+        assert!(decompress(&[0x30, b'a', b'b', b'c'], 2).is_err());
+        assert!(decompress(&[0x30, b'a', b'b', b'c'], 4).is_err());
+        // uncompressed length that could not be allocated
+        assert!(decompress(&[0x30, b'a', b'b', b'c'], usize::MAX).is_err());
+    }
+
+    #[test]
+    fn truncated_and_modified_blocks_do_not_panic() {
+        let input: Vec<u8> = (0..2000).flat_map(|i: u32| format!("{};", i % 100).into_bytes()).collect();
+        let compressed = compress(&input);
+        for len in 0..compressed.len() {
+            let _ = decompress(&compressed[..len], input.len());
+        }
+        for pos in 0..compressed.len() {
+            let mut modified = compressed.clone();
+            modified[pos] ^= 0xa5;
+            let _ = decompress(&modified, input.len());
+        }
+    }
+}
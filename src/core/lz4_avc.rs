@@ -0,0 +1,219 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::io;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::avc::{AttributeValueContainer, DynAttributeValueContainer, Encoding};
+use super::buffer::Buffer;
+use super::column_file::{invalid_data, SegmentReader, SegmentWriter};
+use super::encoding::count_distinct;
+use super::lz4;
+use super::scan::{ChunkOffset, ScanPredicate};
+use super::statistics::ColumnStatistics;
+use super::value::{Value, ValueType};
+
+// number of values compressed together
+const BLOCK_SIZE: usize = 1024;
+
+// ids of the containers, which identify them in the block caches (addresses could be reused by later containers)
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+// container id, block number and values (Vec<Option<T>>) of a decompressed block
+type CachedBlock = (u64, usize, Box<dyn Any>);
+
+thread_local! {
+    // the block most recently decompressed by lookups of this thread, so that sequential access only decompresses each
+    //  block once without the threads sharing (and locking) a cache
+    static BLOCK_CACHE: RefCell<Option<CachedBlock>> = RefCell::new(None);
+}
+
+struct CompressedBlock {
+    data: Buffer<u8>,
+    uncompressed_len: usize
+}
+
+// serialized values compressed block-wise using LZ4, best compression ratio for long strings but slow access
+//  each value is serialized as a NULL flag byte, followed by the value itself if it is not NULL
+pub struct Lz4AttributeValueContainer<T> {
+    blocks: Vec<CompressedBlock>,
+    len: usize,
+    distinct_count: usize,
+    // see BLOCK_CACHE
+    id: u64,
+    marker: PhantomData<T>
+}
+
+impl<T: ValueType> Lz4AttributeValueContainer<T> {
+    pub fn new(column: &[Option<T>]) -> Self {
+        let mut blocks = Vec::with_capacity(column.len() / BLOCK_SIZE + 1);
+        let mut buf = Vec::new();
+        for block in column.chunks(BLOCK_SIZE) {
+            buf.clear();
+            for val in block {
+                match val {
+                    Some(val) => {
+                        buf.push(1);
+                        val.write_bytes(&mut buf);
+                    },
+                    None => buf.push(0)
+                }
+            }
+            let mut data = lz4::compress(&buf);
+            data.shrink_to_fit();
+            blocks.push(CompressedBlock { data: data.into(), uncompressed_len: buf.len() });
+        }
+        Lz4AttributeValueContainer { blocks, len: column.len(), distinct_count: count_distinct(column), id: NEXT_ID.fetch_add(1, Ordering::Relaxed), marker: PhantomData }
+    }
+
+    pub fn write(&self, writer: &mut SegmentWriter) {
+        writer.write_len(self.len);
+        writer.write_len(self.distinct_count);
+        for block in self.blocks.iter() {
+            writer.write_len(block.uncompressed_len);
+            writer.write_bytes(&block.data);
+        }
+    }
+
+    // the compressed blocks are used directly from the mapped file
+    pub fn read(reader: &mut SegmentReader) -> io::Result<Self> {
+        let len = reader.read_len()?;
+        let distinct_count = reader.read_len()?;
+        // each block takes at least its uncompressed length and the length of its data (see write)
+        let block_count = len.div_ceil(BLOCK_SIZE);
+        if block_count > reader.remaining() / 16 {
+            return Err(invalid_data("invalid LZ4 encoding"))
+        }
+        let mut blocks = Vec::with_capacity(block_count);
+        for _ in 0..block_count {
+            let uncompressed_len = reader.read_len()?;
+            blocks.push(CompressedBlock { data: reader.read_slice()?, uncompressed_len });
+        }
+        Ok(Lz4AttributeValueContainer { blocks, len, distinct_count, id: NEXT_ID.fetch_add(1, Ordering::Relaxed), marker: PhantomData })
+    }
+
+    // the blocks of column files are only decompressed on access, so an invalid block is an error of the lookup or scan
+    //  reading it rather than of loading the file
+    fn decompress_block(&self, block: usize) -> io::Result<Vec<Option<T>>> {
+        let value_count = std::cmp::min(BLOCK_SIZE, self.len - block * BLOCK_SIZE);
+        let block = &self.blocks[block];
+        let buf = lz4::decompress(&block.data, block.uncompressed_len)?;
+        let mut values = Vec::with_capacity(value_count);
+        let mut offset = 0;
+        while offset < buf.len() {
+            let is_null = buf[offset] == 0;
+            offset += 1;
+            values.push(if is_null { None } else { Some(T::read_bytes(&buf, &mut offset)?) });
+        }
+        if values.len() != value_count {
+            return Err(invalid_data("invalid LZ4 block: unexpected number of values"))
+        }
+        Ok(values)
+    }
+}
+
+impl<T: ValueType> DynAttributeValueContainer for Lz4AttributeValueContainer<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn distinct_count(&self) -> usize {
+        self.distinct_count
+    }
+
+    fn get_value(&self, i: usize) -> io::Result<Value> {
+        Ok(match self.lookup(i)? {
+            Some(val) => val.into_value(),
+            None => Value::Null
+        })
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding::Lz4
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.blocks.iter().map(|block| block.data.len() + std::mem::size_of::<CompressedBlock>()).sum()
+    }
+
+    fn statistics(&self) -> io::Result<ColumnStatistics> {
+        let mut values = Vec::with_capacity(self.len);
+        for block in 0..self.blocks.len() {
+            values.extend(self.decompress_block(block)?);
+        }
+        Ok(ColumnStatistics::from_values(values, self.distinct_count()))
+    }
+}
+
+impl<T: ValueType> AttributeValueContainer<T> for Lz4AttributeValueContainer<T> {
+    fn lookup(&self, i: usize) -> io::Result<Option<T>> {
+        assert!(i < self.len, "Index out of bounds");
+        let block = i / BLOCK_SIZE;
+        BLOCK_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if !matches!(&*cache, Some((id, cached_block, _)) if *id == self.id && *cached_block == block) {
+                *cache = Some((self.id, block, Box::new(self.decompress_block(block)?)));
+            }
+            let values = cache.as_ref().unwrap().2.downcast_ref::<Vec<Option<T>>>().unwrap();
+            Ok(values[i % BLOCK_SIZE].clone())
+        })
+    }
+
+    // each block is decompressed into a buffer of the scan
+    fn scan(&self, predicate: &ScanPredicate<T>) -> io::Result<Vec<ChunkOffset>> {
+        let mut result = Vec::new();
+        for block in 0..self.blocks.len() {
+            let offset = block * BLOCK_SIZE;
+            for (i, value) in self.decompress_block(block)?.iter().enumerate() {
+                if predicate.matches(value.as_ref()) {
+                    result.push((offset + i) as ChunkOffset);
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(len: usize, modulo: i64) -> Vec<Option<i64>> {
+        (0..len).map(|i| if i % 7 == 0 { None } else { Some(i as i64 % modulo) }).collect()
+    }
+
+    #[test]
+    fn lookups_and_scans_match_the_values() {
+        let values = column(3 * BLOCK_SIZE + 17, 100);
+        let avc = Lz4AttributeValueContainer::new(&values);
+        for (i, value) in values.iter().enumerate() {
+            assert_eq!(avc.lookup(i).unwrap(), *value);
+        }
+        let predicate = ScanPredicate::Between(10, 20);
+        let expected: Vec<ChunkOffset> = (0..values.len()).filter(|i| predicate.matches(values[*i].as_ref())).map(|i| i as ChunkOffset).collect();
+        assert_eq!(avc.scan(&predicate).unwrap(), expected);
+    }
+
+    #[test]
+    fn threads_and_containers_do_not_share_cached_blocks() {
+        let first_values = column(4 * BLOCK_SIZE, 50);
+        let second_values = column(4 * BLOCK_SIZE, 1000);
+        let first = Lz4AttributeValueContainer::new(&first_values);
+        let second = Lz4AttributeValueContainer::new(&second_values);
+        std::thread::scope(|scope| {
+            for thread in 0..4 {
+                let (first, second, first_values, second_values) = (&first, &second, &first_values, &second_values);
+                scope.spawn(move || {
+                    for i in (thread..first_values.len()).step_by(3) {
+                        assert_eq!(first.lookup(i).unwrap(), first_values[i]);
+                        assert_eq!(second.lookup(i).unwrap(), second_values[i]);
+                    }
+                });
+            }
+        });
+    }
+}
@@ -0,0 +1,110 @@
+use std::any::Any;
+use std::io;
+
+use super::avc::{AttributeValueContainer, DynAttributeValueContainer, Encoding};
+use super::buffer::Buffer;
+use super::column_file::{invalid_data, read_values, write_values, SegmentReader, SegmentWriter};
+use super::encoding::count_distinct;
+use super::statistics::ColumnStatistics;
+use super::value::{Value, ValueType};
+
+// consecutive equal values (runs) are stored once, together with the position after the run
+//  well suited for sorted columns with few distinct values, e.g., a sorted key column with several rows per key
+pub struct RunLengthAttributeValueContainer<T> {
+    values: Vec<Option<T>>,
+    // exclusive end position of each run, in ascending order
+    run_ends: Buffer<u32>,
+    distinct_count: usize
+}
+
+impl<T: ValueType> RunLengthAttributeValueContainer<T> {
+    pub fn new(column: &[Option<T>]) -> Self {
+        let mut values: Vec<Option<T>> = Vec::new();
+        let mut run_ends: Vec<u32> = Vec::new();
+        for (i, val) in column.iter().enumerate() {
+            if values.last() == Some(val) {
+                *run_ends.last_mut().unwrap() = i as u32 + 1;
+            } else {
+                values.push(val.clone());
+                run_ends.push(i as u32 + 1);
+            }
+        }
+        values.shrink_to_fit();
+        run_ends.shrink_to_fit();
+        RunLengthAttributeValueContainer { distinct_count: count_distinct(&values), values, run_ends: run_ends.into() }
+    }
+
+    pub fn write(&self, writer: &mut SegmentWriter) {
+        writer.write_len(self.distinct_count);
+        write_values(&self.values, writer);
+        writer.write_slice(&self.run_ends);
+    }
+
+    // the values of the runs are deserialized, the run ends are used directly from the mapped file
+    pub fn read(reader: &mut SegmentReader) -> io::Result<Self> {
+        let distinct_count = reader.read_len()?;
+        let values = read_values(reader)?;
+        let run_ends: Buffer<u32> = reader.read_slice()?;
+        if values.len() != run_ends.len() || run_ends.windows(2).any(|ends| ends[0] >= ends[1]) {
+            return Err(invalid_data("invalid run-length encoding"))
+        }
+        Ok(RunLengthAttributeValueContainer { values, run_ends, distinct_count })
+    }
+
+    pub fn run_count(&self) -> usize {
+        self.run_ends.len()
+    }
+}
+
+impl<T: ValueType> DynAttributeValueContainer for RunLengthAttributeValueContainer<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn len(&self) -> usize {
+        self.run_ends.last().map_or(0, |end| *end as usize)
+    }
+
+    fn distinct_count(&self) -> usize {
+        self.distinct_count
+    }
+
+    fn get_value(&self, i: usize) -> io::Result<Value> {
+        Ok(match self.lookup(i)? {
+            Some(val) => val.into_value(),
+            None => Value::Null
+        })
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding::RunLength
+    }
+
+    fn memory_usage(&self) -> usize {
+        let heap_size: usize = self.values.iter().flatten().map(|val| val.heap_size()).sum();
+        self.run_count() * (std::mem::size_of::<Option<T>>() + std::mem::size_of::<u32>()) + heap_size
+    }
+
+    // min and max only depend on the values of the runs, the NULL count on their lengths
+    fn statistics(&self) -> io::Result<ColumnStatistics> {
+        let mut statistics = ColumnStatistics::from_values(self.values.iter().cloned(), self.distinct_count);
+        let mut start = 0;
+        statistics.null_count = 0;
+        for (value, end) in self.values.iter().zip(self.run_ends.iter()) {
+            if value.is_none() {
+                statistics.null_count += (*end - start) as usize;
+            }
+            start = *end;
+        }
+        Ok(statistics)
+    }
+}
+
+impl<T: ValueType> AttributeValueContainer<T> for RunLengthAttributeValueContainer<T> {
+    fn lookup(&self, i: usize) -> io::Result<Option<T>> {
+        assert!(i < self.len(), "Index out of bounds");
+        // the run containing i is the first one ending after i
+        let run = self.run_ends.partition_point(|end| *end as usize <= i);
+        Ok(self.values[run].clone())
+    }
+}
@@ -0,0 +1,352 @@
+use std::io;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use super::avc::{AttributeValueContainer, DynAttributeValueContainer, Encoding, MainAttributeValueContainer, ValueId};
+use super::bit_packed_vector::BitPackedVector;
+use super::delta::DeltaAttributeValueContainer;
+use super::frame_of_reference_avc::FrameOfReferenceAttributeValueContainer;
+use super::index::ChunkIndex;
+use super::lz4_avc::Lz4AttributeValueContainer;
+use super::run_length_avc::RunLengthAttributeValueContainer;
+use super::table::{ChunkId, ColumnId, Table};
+use super::unencoded_avc::UnencodedAttributeValueContainer;
+use super::value::ValueType;
+
+// position of a row within a chunk
+pub type ChunkOffset = u32;
+
+// predicates that can be evaluated on a single column
+//  each one corresponds to a single range of value ids in a sorted dictionary (NOT EQUALS is the complement of EQUALS)
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanPredicate<T> {
+    Equals(T),
+    LessThan(T),
+    LessThanEquals(T),
+    GreaterThan(T),
+    GreaterThanEquals(T),
+    // both bounds are inclusive
+    Between(T, T),
+    IsNull,
+    IsNotNull
+}
+
+impl<T: PartialOrd> ScanPredicate<T> {
+    // evaluation on a decoded value (None is NULL), which never matches any comparison
+    pub fn matches(&self, value: Option<&T>) -> bool {
+        match (self, value) {
+            (ScanPredicate::IsNull, value) => value.is_none(),
+            (ScanPredicate::IsNotNull, value) => value.is_some(),
+            (_, None) => false,
+            (ScanPredicate::Equals(search), Some(value)) => value == search,
+            (ScanPredicate::LessThan(search), Some(value)) => value < search,
+            (ScanPredicate::LessThanEquals(search), Some(value)) => value <= search,
+            (ScanPredicate::GreaterThan(search), Some(value)) => value > search,
+            (ScanPredicate::GreaterThanEquals(search), Some(value)) => value >= search,
+            (ScanPredicate::Between(low, high), Some(value)) => value >= low && value <= high
+        }
+    }
+}
+
+// number of value ids unpacked from the bit-packed vector at once, before the block is scanned
+const SCAN_BLOCK_SIZE: usize = 1024;
+
+// appends the positions of all value ids in the block with vid - start < width (unsigned) to result, offset being the
+//  position of the first value id of the block
+type ScanKernel = fn(block: &[ValueId], start: ValueId, width: ValueId, offset: ChunkOffset, result: &mut Vec<ChunkOffset>);
+
+fn scan_block_scalar(block: &[ValueId], start: ValueId, width: ValueId, offset: ChunkOffset, result: &mut Vec<ChunkOffset>) {
+    for (i, vid) in block.iter().enumerate() {
+        if vid.wrapping_sub(start) < width {
+            result.push(offset + i as ChunkOffset);
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+fn scan_block_avx2(block: &[ValueId], start: ValueId, width: ValueId, offset: ChunkOffset, result: &mut Vec<ChunkOffset>) {
+    // only selected if the CPU supports AVX2 (see scan_kernel)
+    unsafe { scan_block_avx2_impl(block, start, width, offset, result) }
+}
+
+// compares 8 value ids at once, the resulting bit mask gives the matching positions
+//  AVX2 only has signed comparisons, so both sides are shifted into the signed range by flipping the sign bit
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn scan_block_avx2_impl(block: &[ValueId], start: ValueId, width: ValueId, offset: ChunkOffset, result: &mut Vec<ChunkOffset>) {
+    use std::arch::x86_64::*;
+
+    let sign_bit = _mm256_set1_epi32(i32::MIN);
+    let start_vec = _mm256_set1_epi32(start as i32);
+    let width_vec = _mm256_xor_si256(_mm256_set1_epi32(width as i32), sign_bit);
+    let lanes = 8;
+    let full_len = block.len() / lanes * lanes;
+    let mut i = 0;
+    while i < full_len {
+        let vids = _mm256_loadu_si256(block.as_ptr().add(i) as *const __m256i);
+        let shifted = _mm256_xor_si256(_mm256_sub_epi32(vids, start_vec), sign_bit);
+        let matches = _mm256_cmpgt_epi32(width_vec, shifted);
+        let mut mask = _mm256_movemask_ps(_mm256_castsi256_ps(matches)) as u32;
+        while mask != 0 {
+            result.push(offset + (i as u32 + mask.trailing_zeros()) as ChunkOffset);
+            mask &= mask - 1;
+        }
+        i += lanes;
+    }
+    scan_block_scalar(&block[full_len..], start, width, offset + full_len as ChunkOffset, result);
+}
+
+// the fastest kernel supported by the CPU, detected once
+fn scan_kernel() -> ScanKernel {
+    static KERNEL: OnceLock<ScanKernel> = OnceLock::new();
+    *KERNEL.get_or_init(|| {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                return scan_block_avx2
+            }
+        }
+        scan_block_scalar
+    })
+}
+
+// positions of all value ids within the range
+//  a single unsigned comparison per row suffices: vid - start wraps around to a large number for vids below the start
+pub fn scan_value_id_range(value_ids: &BitPackedVector, range: Range<ValueId>) -> Vec<ChunkOffset> {
+    let mut result = Vec::new();
+    if range.start >= range.end {
+        return result
+    }
+    let width = range.end - range.start;
+    let kernel = scan_kernel();
+    let mut block = [0; SCAN_BLOCK_SIZE];
+    let mut vids = value_ids.iter();
+    let mut offset = 0;
+    while offset < value_ids.len() {
+        let block_len = std::cmp::min(SCAN_BLOCK_SIZE, value_ids.len() - offset);
+        for (slot, vid) in block[..block_len].iter_mut().zip(&mut vids) {
+            *slot = vid;
+        }
+        kernel(&block[..block_len], range.start, width, offset as ChunkOffset, &mut result);
+        offset += block_len;
+    }
+    result
+}
+
+// typed access to a container of a column of type T
+pub fn typed_container<T: ValueType>(avc: &dyn DynAttributeValueContainer) -> &dyn AttributeValueContainer<T> {
+    fn downcast<C: 'static>(avc: &dyn DynAttributeValueContainer) -> &C {
+        avc.as_any().downcast_ref::<C>().expect("container does not match the column type")
+    }
+    match avc.encoding() {
+        Encoding::Dictionary => downcast::<MainAttributeValueContainer<T>>(avc),
+        Encoding::UnsortedDictionary => downcast::<DeltaAttributeValueContainer<T>>(avc),
+        Encoding::Unencoded => downcast::<UnencodedAttributeValueContainer<T>>(avc),
+        Encoding::RunLength => downcast::<RunLengthAttributeValueContainer<T>>(avc),
+        Encoding::FrameOfReference => downcast::<FrameOfReferenceAttributeValueContainer<T>>(avc),
+        Encoding::Lz4 => downcast::<Lz4AttributeValueContainer<T>>(avc)
+    }
+}
+
+#[allow(dead_code)]
+pub struct TableScanResult {
+    // positions of the matching rows per chunk, chunks without matches are omitted
+    pub matches: Vec<(ChunkId, Vec<ChunkOffset>)>,
+    // chunks that were skipped because their statistics ruled out any match
+    pub pruned_chunks: usize
+}
+
+// scans a column of type T of all chunks of a table (main and delta store), skipping the chunks that cannot contain
+//  matching rows according to their statistics (see Chunk::may_match), fails if the values of a chunk cannot be read
+#[allow(dead_code)]
+pub fn scan_table<T: ValueType>(table: &Table, column_id: ColumnId, predicate: &ScanPredicate<T>) -> io::Result<TableScanResult> {
+    let mut result = TableScanResult { matches: Vec::new(), pruned_chunks: 0 };
+    for (chunk_id, chunk) in table.chunks().iter().enumerate() {
+        if !chunk.may_match(column_id, predicate) {
+            result.pruned_chunks += 1;
+            continue
+        }
+        let mut positions = typed_container::<T>(chunk.column(column_id)).scan(predicate)?;
+        if let Some(delta) = chunk.delta_column(column_id) {
+            let main_len = chunk.main_len() as ChunkOffset;
+            positions.extend(typed_container::<T>(delta).scan(predicate)?.into_iter().map(|offset| main_len + offset));
+        }
+        if !positions.is_empty() {
+            result.matches.push((chunk_id, positions));
+        }
+    }
+    Ok(result)
+}
+
+// scans a column of type T of all chunks of a table using the given index of the table (see Table::indexes), skipping the
+//  chunks that cannot contain matching rows like scan_table
+#[allow(dead_code)]
+pub fn index_scan<T: ValueType>(table: &Table, index_id: usize, predicate: &ScanPredicate<T>) -> TableScanResult {
+    let column_id = table.indexes()[index_id].column_id;
+    let mut result = TableScanResult { matches: Vec::new(), pruned_chunks: 0 };
+    for (chunk_id, chunk) in table.chunks().iter().enumerate() {
+        if !chunk.may_match(column_id, predicate) {
+            result.pruned_chunks += 1;
+            continue
+        }
+        let index = chunk.index(index_id).as_any().downcast_ref::<ChunkIndex<T>>().expect("index does not match the column type");
+        let mut positions = index.main.lookup(chunk.column(column_id), predicate);
+        let main_len = chunk.main_len() as ChunkOffset;
+        positions.extend(index.delta.lookup(predicate).into_iter().map(|offset| main_len + offset));
+        if !positions.is_empty() {
+            result.matches.push((chunk_id, positions));
+        }
+    }
+    result
+}
+
+// positions of the rows whose indexed column equals the given value, i.e., the inner side of an index nested loop join
+#[allow(dead_code)]
+pub fn index_lookup<T: ValueType>(table: &Table, index_id: usize, value: &T) -> Vec<(ChunkId, ChunkOffset)> {
+    let predicate = ScanPredicate::Equals(value.clone());
+    index_scan(table, index_id, &predicate).matches.into_iter()
+        .flat_map(|(chunk_id, positions)| positions.into_iter().map(move |offset| (chunk_id, offset)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::core::bit_packed_vector::bits_needed;
+
+    // xorshift, so that failures can be reproduced
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: u64) -> u64 {
+            self.next() % bound
+        }
+    }
+
+    fn random_value_ids(random: &mut Random, len: usize, bits: u32) -> Vec<ValueId> {
+        let max = if bits == 32 { u32::MAX as u64 + 1 } else { 1 << bits };
+        (0..len).map(|_| random.below(max) as ValueId).collect()
+    }
+
+    fn run_kernel(kernel: ScanKernel, value_ids: &[ValueId], range: &Range<ValueId>) -> Vec<ChunkOffset> {
+        let mut result = Vec::new();
+        for (block_id, block) in value_ids.chunks(SCAN_BLOCK_SIZE).enumerate() {
+            kernel(block, range.start, range.end - range.start, (block_id * SCAN_BLOCK_SIZE) as ChunkOffset, &mut result);
+        }
+        result
+    }
+
+    #[test]
+    fn scalar_kernel_matches_the_range() {
+        let mut random = Random(0x9e3779b97f4a7c15);
+        for _ in 0..200 {
+            let bits = 1 + random.below(32) as u32;
+            let len = random.below(3000) as usize;
+            let value_ids = random_value_ids(&mut random, len, bits);
+            let start = *value_ids.first().unwrap_or(&0);
+            let end = start.saturating_add(1 + random.below(1 << std::cmp::min(bits, 16)) as ValueId);
+            let expected: Vec<ChunkOffset> = (0..value_ids.len()).filter(|i| (start..end).contains(&value_ids[*i])).map(|i| i as ChunkOffset).collect();
+            assert_eq!(run_kernel(scan_block_scalar, &value_ids, &(start..end)), expected);
+        }
+    }
+
+    // random bit widths, block tails (lengths that are not multiples of the 8 lanes) and ranges, including ranges at the
+    //  ends of the value id domain, where the subtraction wraps around and the sign bit is set
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn avx2_kernel_matches_scalar_kernel() {
+        if !is_x86_feature_detected!("avx2") {
+            println!("AVX2 is not supported, skipping");
+            return
+        }
+        let mut random = Random(0x2545f4914f6cdd1d);
+        for iteration in 0..2000 {
+            let bits = 1 + random.below(32) as u32;
+            let len = match iteration % 3 {
+                0 => random.below(17) as usize,
+                1 => random.below(SCAN_BLOCK_SIZE as u64 * 3) as usize,
+                _ => SCAN_BLOCK_SIZE * (1 + random.below(3) as usize) + random.below(8) as usize
+            };
+            let value_ids = random_value_ids(&mut random, len, bits);
+            let domain = if bits == 32 { u32::MAX as u64 } else { (1u64 << bits) - 1 };
+            let (mut start, mut end) = (random.below(domain + 1) as ValueId, random.below(domain + 1) as ValueId);
+            match iteration % 5 {
+                // point range on an existing value id (e.g., equality)
+                0 if !value_ids.is_empty() => {
+                    start = value_ids[random.below(value_ids.len() as u64) as usize];
+                    end = start.saturating_add(1);
+                },
+                // everything above a value id, up to the largest one
+                1 => end = u32::MAX,
+                // everything below a value id
+                2 => start = 0,
+                _ => {}
+            }
+            if start > end {
+                std::mem::swap(&mut start, &mut end);
+            }
+            let range = start..end;
+            assert_eq!(run_kernel(scan_block_avx2, &value_ids, &range), run_kernel(scan_block_scalar, &value_ids, &range),
+                "bits {}, {} value ids, range {:?}", bits, len, range);
+        }
+    }
+
+    #[test]
+    fn value_id_range_scan_matches_the_range() {
+        let mut random = Random(0x853c49e6748fea9b);
+        for _ in 0..50 {
+            let bits = 1 + random.below(20) as u32;
+            let len = random.below(5000) as usize;
+            let value_ids = random_value_ids(&mut random, len, bits);
+            let max_value_id = (1u64 << bits) as ValueId - 1;
+            let packed = BitPackedVector::from_value_ids(&value_ids, max_value_id);
+            assert_eq!(packed.bits(), bits_needed(max_value_id));
+            let start = random.below(max_value_id as u64 + 1) as ValueId;
+            let end = start + random.below((max_value_id - start) as u64 + 2) as ValueId;
+            let expected: Vec<ChunkOffset> = (0..value_ids.len()).filter(|i| (start..end).contains(&value_ids[*i])).map(|i| i as ChunkOffset).collect();
+            assert_eq!(scan_value_id_range(&packed, start..end), expected);
+        }
+    }
+
+    // timing of the kernels on unpacked value ids, run with cargo test --release -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn kernel_speedup() {
+        let mut random = Random(0xda942042e4dd58b5);
+        let value_ids = random_value_ids(&mut random, 1 << 24, 17);
+        let mut kernels: Vec<(&str, ScanKernel)> = vec![("scalar", scan_block_scalar)];
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                kernels.push(("avx2", scan_block_avx2));
+            }
+        }
+        for selectivity in [0.001, 0.01, 0.1, 0.5] {
+            let range = 0..((1 << 17) as f64 * selectivity) as ValueId;
+            let mut timings = Vec::new();
+            for (name, kernel) in kernels.iter() {
+                let start = Instant::now();
+                let mut matches = 0;
+                for _ in 0..5 {
+                    matches += run_kernel(*kernel, &value_ids, &range).len();
+                }
+                let elapsed = start.elapsed() / 5;
+                println!("selectivity {}: {} kernel {:.2?} ({} matches)", selectivity, name, elapsed, matches / 5);
+                timings.push(elapsed);
+            }
+            if let [scalar, avx2] = timings.as_slice() {
+                println!("selectivity {}: speedup {:.2}", selectivity, scalar.as_secs_f64() / avx2.as_secs_f64());
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::convert::TryInto;
+use std::io;
+
+use super::column_file::{invalid_data, read_values, write_values, SegmentReader, SegmentWriter};
+use super::scan::ScanPredicate;
+use super::value::{Value, ValueType};
+
+// statistics of the main store of a column within a chunk, computed when the container is created and stored with the
+//  chunk, so that scans can skip chunks that cannot contain matching rows
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStatistics {
+    // smallest and largest non-NULL value, NULL if there is none
+    pub min: Value,
+    pub max: Value,
+    pub null_count: usize,
+    pub distinct_count: usize
+}
+
+impl ColumnStatistics {
+    // statistics of an empty container
+    pub fn empty() -> Self {
+        ColumnStatistics { min: Value::Null, max: Value::Null, null_count: 0, distinct_count: 0 }
+    }
+
+    // computes the statistics from all values of a container, whose number of distinct values is known already
+    pub fn from_values<T: ValueType>(values: impl IntoIterator<Item = Option<T>>, distinct_count: usize) -> Self {
+        let mut min: Option<T> = None;
+        let mut max: Option<T> = None;
+        let mut null_count = 0;
+        for value in values {
+            match value {
+                Some(value) => {
+                    if min.as_ref().is_none_or(|min| value < *min) {
+                        min = Some(value.clone());
+                    }
+                    if max.as_ref().is_none_or(|max| value > *max) {
+                        max = Some(value);
+                    }
+                },
+                None => null_count += 1
+            }
+        }
+        let into_value = |value: Option<T>| value.map_or(Value::Null, T::into_value);
+        ColumnStatistics { min: into_value(min), max: into_value(max), null_count, distinct_count }
+    }
+
+    // false if no row of the container can match the predicate, true if some may (the statistics are not precise enough
+    //  to tell whether one does)
+    pub fn may_match<T: ValueType>(&self, predicate: &ScanPredicate<T>) -> bool {
+        let range = match (T::from_value(&self.min), T::from_value(&self.max)) {
+            (Some(min), Some(max)) => Some((min, max)),
+            _ => None
+        };
+        match (predicate, range) {
+            (ScanPredicate::IsNull, _) => self.null_count > 0,
+            (ScanPredicate::IsNotNull, range) => range.is_some(),
+            // only NULLs (or no rows at all), which never match a comparison
+            (_, None) => false,
+            (ScanPredicate::Equals(value), Some((min, max))) => min <= *value && *value <= max,
+            (ScanPredicate::LessThan(value), Some((min, _))) => min < *value,
+            (ScanPredicate::LessThanEquals(value), Some((min, _))) => min <= *value,
+            (ScanPredicate::GreaterThan(value), Some((_, max))) => max > *value,
+            (ScanPredicate::GreaterThanEquals(value), Some((_, max))) => max >= *value,
+            (ScanPredicate::Between(low, high), Some((min, max))) => *low <= max && *high >= min
+        }
+    }
+
+    // min and max as values of the column type (see write_values), followed by the NULL and distinct count
+    pub fn write<T: ValueType>(&self, writer: &mut SegmentWriter) {
+        write_values(&[T::from_value(&self.min), T::from_value(&self.max)], writer);
+        writer.write_len(self.null_count);
+        writer.write_len(self.distinct_count);
+    }
+
+    pub fn read<T: ValueType>(reader: &mut SegmentReader) -> io::Result<Self> {
+        let values: Vec<Option<T>> = read_values(reader)?;
+        let [min, max]: [Option<T>; 2] = values.try_into().map_err(|_| invalid_data("invalid column statistics"))?;
+        let into_value = |value: Option<T>| value.map_or(Value::Null, T::into_value);
+        Ok(ColumnStatistics { min: into_value(min), max: into_value(max), null_count: reader.read_len()?, distinct_count: reader.read_len()? })
+    }
+}
@@ -0,0 +1,583 @@
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex, RwLock};
+
+use super::avc::{DynAttributeValueContainer, MainAttributeValueContainer};
+use super::date::Date;
+use super::decimal::Decimal;
+use super::delta::{DeltaAttributeValueContainer, DynDeltaAttributeValueContainer};
+use super::histogram::ColumnHistogram;
+use super::index::{create_chunk_index, create_empty_chunk_index, DynChunkIndex, IndexDefinition};
+use super::scan::{ChunkOffset, ScanPredicate};
+use super::statistics::ColumnStatistics;
+use super::value::{Value, ValueType};
+
+pub type ColumnId = usize;
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataType {
+    BigInt,
+    Double,
+    // fixed-length character type (values are not padded)
+    Char(u32),
+    // variable-length character type with an optional maximum length
+    Varchar(Option<u32>),
+    Text,
+    Date,
+    // fixed-point number with (precision, scale)
+    Decimal(u32, u32),
+    Boolean
+}
+
+impl DataType {
+    // type OID as defined in the pg_type catalog of PostgreSQL
+    pub fn type_oid(&self) -> u32 {
+        match self {
+            DataType::BigInt => 20,
+            DataType::Double => 701,
+            DataType::Char(_) => 1042,
+            DataType::Varchar(_) => 1043,
+            DataType::Text => 25,
+            DataType::Date => 1082,
+            DataType::Decimal(_, _) => 1700,
+            DataType::Boolean => 16
+        }
+    }
+
+    // type size as reported in the RowDescription message (-1 for variable-length types)
+    pub fn type_len(&self) -> i16 {
+        match self {
+            DataType::BigInt => 8,
+            DataType::Double => 8,
+            DataType::Date => 4,
+            DataType::Boolean => 1,
+            DataType::Char(_) | DataType::Varchar(_) | DataType::Text | DataType::Decimal(_, _) => -1
+        }
+    }
+
+    // type modifier as reported in the RowDescription message
+    //  for character types, this is the maximum length plus the 4 byte header, for decimals the precision and scale plus the header
+    pub fn type_modifier(&self) -> i32 {
+        match self {
+            DataType::Char(len) | DataType::Varchar(Some(len)) => *len as i32 + 4,
+            DataType::Decimal(precision, scale) => ((*precision as i32) << 16 | *scale as i32) + 4,
+            _ => -1
+        }
+    }
+
+    // maximum number of characters of a value of this type
+    pub fn max_length(&self) -> Option<usize> {
+        match self {
+            DataType::Char(len) | DataType::Varchar(Some(len)) => Some(*len as usize),
+            _ => None
+        }
+    }
+
+    pub fn create_main_container(&self) -> Box<dyn DynAttributeValueContainer + Send + Sync> {
+        match self {
+            DataType::BigInt => Box::new(MainAttributeValueContainer::<i64>::new()),
+            DataType::Double => Box::new(MainAttributeValueContainer::<f64>::new()),
+            DataType::Char(_) | DataType::Varchar(_) | DataType::Text => Box::new(MainAttributeValueContainer::<String>::new()),
+            DataType::Date => Box::new(MainAttributeValueContainer::<Date>::new()),
+            DataType::Decimal(_, _) => Box::new(MainAttributeValueContainer::<Decimal>::new()),
+            DataType::Boolean => Box::new(MainAttributeValueContainer::<bool>::new())
+        }
+    }
+
+    pub fn create_delta_container(&self) -> Box<dyn DynDeltaAttributeValueContainer + Send + Sync> {
+        match self {
+            DataType::BigInt => Box::new(DeltaAttributeValueContainer::<i64>::new()),
+            DataType::Double => Box::new(DeltaAttributeValueContainer::<f64>::new()),
+            DataType::Char(_) | DataType::Varchar(_) | DataType::Text => Box::new(DeltaAttributeValueContainer::<String>::new()),
+            DataType::Date => Box::new(DeltaAttributeValueContainer::<Date>::new()),
+            DataType::Decimal(_, _) => Box::new(DeltaAttributeValueContainer::<Decimal>::new()),
+            DataType::Boolean => Box::new(DeltaAttributeValueContainer::<bool>::new())
+        }
+    }
+
+    // parses the text representation of a value of this type (e.g., a field of a .tbl file), the result still has to be
+    //  converted to the column representation using ColumnDefinition::coerce
+    pub fn parse(&self, input: &str) -> Result<Value, TableError> {
+        let invalid_input = || TableError::InvalidInput(*self, input.to_owned());
+        match self {
+            DataType::BigInt => input.trim().parse::<i64>().map(Value::BigInt).map_err(|_| invalid_input()),
+            DataType::Double => input.trim().parse::<f64>().map(Value::Double).map_err(|_| invalid_input()),
+            DataType::Char(_) | DataType::Varchar(_) | DataType::Text => Ok(Value::Text(input.to_owned())),
+            DataType::Date => Date::parse(input).map(Value::Date).ok_or_else(invalid_input),
+            DataType::Decimal(_, _) => Decimal::parse(input).map(Value::Decimal).ok_or_else(invalid_input),
+            DataType::Boolean => {
+                match input.trim().to_lowercase().as_str() {
+                    "t" | "true" | "yes" | "on" | "1" => Ok(Value::Boolean(true)),
+                    "f" | "false" | "no" | "off" | "0" => Ok(Value::Boolean(false)),
+                    _ => Err(invalid_input())
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataType::BigInt => write!(f, "bigint"),
+            DataType::Double => write!(f, "double precision"),
+            DataType::Char(len) => write!(f, "character({})", len),
+            DataType::Varchar(Some(len)) => write!(f, "character varying({})", len),
+            DataType::Varchar(None) => write!(f, "character varying"),
+            DataType::Text => write!(f, "text"),
+            DataType::Date => write!(f, "date"),
+            DataType::Decimal(precision, scale) => write!(f, "numeric({},{})", precision, scale),
+            DataType::Boolean => write!(f, "boolean")
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDefinition {
+    pub name: String,
+    pub data_type: DataType,
+    pub nullable: bool
+}
+
+impl ColumnDefinition {
+    pub fn new(name: &str, data_type: DataType, nullable: bool) -> Self {
+        ColumnDefinition { name: name.to_owned(), data_type, nullable }
+    }
+
+    // converts a value to the representation stored in this column (assignment cast), e.g., string literals to dates
+    //  or decimals to the scale of the column
+    pub fn coerce(&self, value: Value) -> Result<Value, TableError> {
+        let type_mismatch = || TableError::TypeMismatch(self.name.clone(), self.data_type);
+        match (self.data_type, value) {
+            (_, Value::Null) => {
+                if self.nullable {
+                    Ok(Value::Null)
+                } else {
+                    Err(TableError::NotNullViolation(self.name.clone()))
+                }
+            },
+            (DataType::BigInt, Value::BigInt(val)) => Ok(Value::BigInt(val)),
+            (DataType::Double, Value::BigInt(val)) => Ok(Value::Double(val as f64)),
+            (DataType::Double, Value::Double(val)) => Ok(Value::Double(val)),
+            (DataType::Double, Value::Decimal(val)) => Ok(Value::Double(val.to_f64())),
+            (DataType::Char(_), Value::Text(val)) | (DataType::Varchar(_), Value::Text(val)) | (DataType::Text, Value::Text(val)) => {
+                match self.data_type.max_length() {
+                    Some(max_length) if val.chars().count() > max_length => Err(TableError::ValueTooLong(self.data_type)),
+                    _ => Ok(Value::Text(val))
+                }
+            },
+            (DataType::Date, Value::Date(val)) => Ok(Value::Date(val)),
+            (DataType::Boolean, Value::Boolean(val)) => Ok(Value::Boolean(val)),
+            (DataType::Date, Value::Text(val)) => {
+                match Date::parse(&val) {
+                    Some(date) => Ok(Value::Date(date)),
+                    None => Err(TableError::InvalidInput(self.data_type, val))
+                }
+            },
+            (DataType::Decimal(precision, scale), value) => {
+                let decimal = match value {
+                    Value::BigInt(val) => Decimal::from_i64(val),
+                    Value::Decimal(val) => val,
+                    Value::Text(val) => Decimal::parse(&val).ok_or(TableError::InvalidInput(self.data_type, val))?,
+                    _ => return Err(type_mismatch())
+                };
+                match decimal.rescale(scale) {
+                    Some(decimal) if decimal.precision() <= precision => Ok(Value::Decimal(decimal)),
+                    _ => Err(TableError::NumericOverflow(self.data_type))
+                }
+            },
+            _ => Err(type_mismatch())
+        }
+    }
+}
+
+pub type ChunkId = usize;
+
+// default maximum number of rows per chunk
+pub const DEFAULT_CHUNK_SIZE: usize = 65536;
+
+// a horizontal partition of a table, holding one attribute value container per column
+//  every chunk is encoded independently, i.e., the containers of different chunks may use different dictionaries or encodings
+//  chunks that accept new rows additionally have a delta store per column, rows of the delta follow the rows of the main store
+pub struct Chunk {
+    columns: Vec<Box<dyn DynAttributeValueContainer + Send + Sync>>,
+    // of the main store of each column
+    statistics: Vec<ColumnStatistics>,
+    // empty if the chunk has no delta store
+    delta: Vec<Box<dyn DynDeltaAttributeValueContainer + Send + Sync>>,
+    // one per index of the table (in the same order), created when the chunk is added to the table
+    indexes: Vec<Box<dyn DynChunkIndex + Send + Sync>>
+}
+
+#[allow(dead_code)]
+impl Chunk {
+    // fails if the statistics cannot be computed because the values of a container cannot be read
+    pub fn new(columns: Vec<Box<dyn DynAttributeValueContainer + Send + Sync>>) -> io::Result<Self> {
+        if let Some(first) = columns.first() {
+            assert!(columns.iter().all(|avc| avc.len() == first.len()), "all columns of a chunk need to have the same length");
+        }
+        let statistics = columns.iter().map(|avc| avc.statistics()).collect::<io::Result<_>>()?;
+        Ok(Chunk { columns, statistics, delta: Vec::new(), indexes: Vec::new() })
+    }
+
+    // creates a chunk whose statistics were computed before (e.g., when it was written to a column file)
+    pub fn with_statistics(columns: Vec<Box<dyn DynAttributeValueContainer + Send + Sync>>, statistics: Vec<ColumnStatistics>) -> Self {
+        assert_eq!(columns.len(), statistics.len(), "every column needs statistics");
+        Chunk { columns, statistics, delta: Vec::new(), indexes: Vec::new() }
+    }
+
+    // creates an empty chunk that accepts new rows
+    pub fn with_delta(column_definitions: &[ColumnDefinition]) -> Self {
+        Chunk {
+            columns: column_definitions.iter().map(|column| column.data_type.create_main_container()).collect(),
+            statistics: column_definitions.iter().map(|_| ColumnStatistics::empty()).collect(),
+            delta: column_definitions.iter().map(|column| column.data_type.create_delta_container()).collect(),
+            indexes: Vec::new()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.main_len() + self.delta_len()
+    }
+
+    pub fn main_len(&self) -> usize {
+        match self.columns.first() {
+            Some(avc) => avc.len(),
+            None => 0
+        }
+    }
+
+    pub fn delta_len(&self) -> usize {
+        match self.delta.first() {
+            Some(avc) => avc.len(),
+            None => 0
+        }
+    }
+
+    pub fn has_delta(&self) -> bool {
+        !self.delta.is_empty()
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.columns.len()
+    }
+
+    // the main store of the given column
+    pub fn column(&self, column_id: ColumnId) -> &dyn DynAttributeValueContainer {
+        self.columns[column_id].as_ref()
+    }
+
+    // statistics of the main store of the given column
+    pub fn statistics(&self, column_id: ColumnId) -> &ColumnStatistics {
+        &self.statistics[column_id]
+    }
+
+    // false if no row of the chunk can match the predicate on the given column
+    //  the statistics only cover the main store, so chunks with rows in their delta store may always match
+    pub fn may_match<T: ValueType>(&self, column_id: ColumnId, predicate: &ScanPredicate<T>) -> bool {
+        self.delta_len() > 0 || self.statistics[column_id].may_match(predicate)
+    }
+
+    pub fn delta_column(&self, column_id: ColumnId) -> Option<&dyn DynAttributeValueContainer> {
+        self.delta.get(column_id).map(|avc| avc.as_ref() as &dyn DynAttributeValueContainer)
+    }
+
+    // the index of the chunk for the given index of the table
+    pub fn index(&self, index_id: usize) -> &dyn DynChunkIndex {
+        self.indexes[index_id].as_ref()
+    }
+
+    fn create_index(&self, data_type: DataType, column_id: ColumnId) -> io::Result<Box<dyn DynChunkIndex + Send + Sync>> {
+        create_chunk_index(data_type, self.column(column_id), self.delta_column(column_id))
+    }
+
+    // reads a value from the main or the delta store, depending on the offset
+    pub fn get_value(&self, column_id: ColumnId, offset: usize) -> io::Result<Value> {
+        let main_len = self.main_len();
+        if offset < main_len {
+            self.columns[column_id].get_value(offset)
+        } else {
+            self.delta[column_id].get_value(offset - main_len)
+        }
+    }
+
+    // merges the first `rows` rows of the delta store into new main stores (one per column)
+    pub fn merge_delta(&self, rows: usize) -> io::Result<Vec<Box<dyn DynAttributeValueContainer + Send + Sync>>> {
+        self.delta.iter().zip(self.columns.iter()).map(|(delta, main)| delta.merge(main.as_ref(), rows)).collect()
+    }
+}
+
+#[derive(Debug)]
+pub enum TableError {
+    ColumnCountMismatch(usize, usize),
+    NotNullViolation(String),
+    TypeMismatch(String, DataType),
+    ValueTooLong(DataType),
+    NumericOverflow(DataType),
+    InvalidInput(DataType, String),
+    // input that is not valid UTF-8
+    InvalidByteSequence,
+    // malformed input of a bulk load
+    BadCopyFormat(&'static str)
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableError::ColumnCountMismatch(expected, actual) => write!(f, "expected {} values, but got {}", expected, actual),
+            TableError::NotNullViolation(column) => write!(f, "null value in column \"{}\" violates not-null constraint", column),
+            TableError::TypeMismatch(column, data_type) => write!(f, "column \"{}\" is of type {}", column, data_type),
+            TableError::ValueTooLong(data_type) => write!(f, "value too long for type {}", data_type),
+            TableError::NumericOverflow(data_type) => write!(f, "numeric field overflow for type {}", data_type),
+            TableError::InvalidInput(data_type, input) => write!(f, "invalid input syntax for type {}: \"{}\"", data_type, input),
+            TableError::InvalidByteSequence => write!(f, "invalid byte sequence for encoding \"UTF8\""),
+            TableError::BadCopyFormat(msg) => write!(f, "{}", msg)
+        }
+    }
+}
+
+impl TableError {
+    pub fn sqlstate(&self) -> &'static str {
+        match self {
+            TableError::ColumnCountMismatch(_, _) => "42601",
+            TableError::NotNullViolation(_) => "23502",
+            TableError::TypeMismatch(_, _) => "42804",
+            TableError::ValueTooLong(_) => "22001",
+            TableError::NumericOverflow(_) => "22003",
+            TableError::InvalidInput(DataType::Date, _) => "22007",
+            TableError::InvalidInput(_, _) => "22P02",
+            TableError::InvalidByteSequence => "22021",
+            TableError::BadCopyFormat(_) => "22P04"
+        }
+    }
+}
+
+pub struct Table {
+    columns: Vec<ColumnDefinition>,
+    chunks: Vec<Chunk>,
+    target_chunk_size: usize,
+    // per column, created by ANALYZE (None if the column was not analyzed yet)
+    //  they are not updated by changes, so they can be outdated
+    histograms: Vec<Option<ColumnHistogram>>,
+    indexes: Vec<IndexDefinition>,
+    // held while the delta of a chunk is merged, so that merges do not run concurrently
+    merge_lock: Arc<Mutex<()>>
+}
+
+#[allow(dead_code)]
+impl Table {
+    pub fn new(columns: Vec<ColumnDefinition>, target_chunk_size: usize) -> Self {
+        assert!(target_chunk_size > 0);
+        let histograms = columns.iter().map(|_| None).collect();
+        Table { columns, chunks: Vec::new(), target_chunk_size, histograms, indexes: Vec::new(), merge_lock: Arc::new(Mutex::new(())) }
+    }
+
+    // fails (without appending the chunk) if the indexes of the chunk cannot be created because its values cannot be read
+    pub fn append_chunk(&mut self, mut chunk: Chunk) -> io::Result<()> {
+        assert_eq!(chunk.column_count(), self.columns.len(), "every column needs exactly one attribute value container");
+        assert!(chunk.len() <= self.target_chunk_size, "chunk exceeds the target chunk size of the table");
+        chunk.indexes = self.indexes.iter().map(|index| chunk.create_index(self.columns[index.column_id].data_type, index.column_id)).collect::<io::Result<_>>()?;
+        self.chunks.push(chunk);
+        Ok(())
+    }
+
+    // appends rows to the delta store of the last chunk (a new chunk is started if the last one is full or has no delta)
+    //  all rows are checked and converted to the column types before the first one is appended, so either all or none of the rows are inserted
+    pub fn insert(&mut self, rows: Vec<Vec<Value>>) -> Result<(), TableError> {
+        let rows = self.coerce_rows(rows)?;
+        self.append_rows(rows);
+        Ok(())
+    }
+
+    // checks the rows and converts their values to the column types (see ColumnDefinition::coerce)
+    pub fn coerce_rows(&self, rows: Vec<Vec<Value>>) -> Result<Vec<Vec<Value>>, TableError> {
+        let mut coerced_rows = Vec::with_capacity(rows.len());
+        for row in rows {
+            if row.len() != self.columns.len() {
+                return Err(TableError::ColumnCountMismatch(self.columns.len(), row.len()))
+            }
+            let mut coerced_row = Vec::with_capacity(row.len());
+            for (column, value) in self.columns.iter().zip(row) {
+                coerced_row.push(column.coerce(value)?);
+            }
+            coerced_rows.push(coerced_row);
+        }
+        Ok(coerced_rows)
+    }
+
+    // appends rows returned by coerce_rows (see insert)
+    pub fn append_rows(&mut self, rows: Vec<Vec<Value>>) {
+        for row in rows {
+            let needs_new_chunk = match self.chunks.last() {
+                Some(chunk) => !chunk.has_delta() || chunk.len() >= self.target_chunk_size,
+                None => true
+            };
+            if needs_new_chunk {
+                let mut chunk = Chunk::with_delta(&self.columns);
+                chunk.indexes = self.indexes.iter().map(|index| create_empty_chunk_index(self.columns[index.column_id].data_type)).collect();
+                self.chunks.push(chunk);
+            }
+            let chunk = self.chunks.last_mut().unwrap();
+            let offset = chunk.delta_len() as ChunkOffset;
+            for (delta, value) in chunk.delta.iter_mut().zip(row.iter()) {
+                let appended = delta.append_value(value);
+                debug_assert!(appended);
+            }
+            for (chunk_index, index) in chunk.indexes.iter_mut().zip(self.indexes.iter()) {
+                chunk_index.insert_delta(offset, &row[index.column_id]);
+            }
+        }
+    }
+
+    // chunks whose delta store holds at least `threshold` rows or that are full and still have a delta store
+    pub fn chunks_to_merge(&self, threshold: usize) -> Vec<ChunkId> {
+        self.chunks.iter().enumerate()
+            .filter(|(_, chunk)| chunk.delta_len() >= threshold || (chunk.has_delta() && chunk.len() >= self.target_chunk_size))
+            .map(|(chunk_id, _)| chunk_id)
+            .collect()
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.len()).sum()
+    }
+
+    pub fn target_chunk_size(&self) -> usize {
+        self.target_chunk_size
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn get_chunk(&self, chunk_id: ChunkId) -> &Chunk {
+        &self.chunks[chunk_id]
+    }
+
+    pub fn chunks(&self) -> &Vec<Chunk> {
+        &self.chunks
+    }
+
+    pub fn into_chunks(self) -> Vec<Chunk> {
+        self.chunks
+    }
+
+    pub fn column_definitions(&self) -> &Vec<ColumnDefinition> {
+        &self.columns
+    }
+
+    pub fn column_definition(&self, column_id: ColumnId) -> &ColumnDefinition {
+        &self.columns[column_id]
+    }
+
+    pub fn column_id(&self, name: &str) -> Option<ColumnId> {
+        self.columns.iter().position(|column| column.name == name)
+    }
+
+    pub fn histogram(&self, column_id: ColumnId) -> Option<&ColumnHistogram> {
+        self.histograms[column_id].as_ref()
+    }
+
+    pub fn set_histogram(&mut self, column_id: ColumnId, histogram: ColumnHistogram) {
+        self.histograms[column_id] = Some(histogram);
+    }
+
+    pub fn indexes(&self) -> &Vec<IndexDefinition> {
+        &self.indexes
+    }
+
+    pub fn index_id(&self, name: &str) -> Option<usize> {
+        self.indexes.iter().position(|index| index.name == name)
+    }
+
+    // the first index on the given column
+    pub fn column_index(&self, column_id: ColumnId) -> Option<usize> {
+        self.indexes.iter().position(|index| index.column_id == column_id)
+    }
+
+    // creates the index of every chunk, the name has to be unique (see Catalog::create_index)
+    //  fails (without creating the index) if the values of a chunk cannot be read
+    pub fn create_index(&mut self, name: &str, column_id: ColumnId) -> io::Result<()> {
+        let data_type = self.columns[column_id].data_type;
+        let indexes = self.chunks.iter().map(|chunk| chunk.create_index(data_type, column_id)).collect::<io::Result<Vec<_>>>()?;
+        for (chunk, index) in self.chunks.iter_mut().zip(indexes) {
+            chunk.indexes.push(index);
+        }
+        self.indexes.push(IndexDefinition { name: name.to_owned(), column_id });
+        Ok(())
+    }
+
+    // bytes used by the given index in all chunks
+    pub fn index_memory_usage(&self, index_id: usize) -> usize {
+        self.chunks.iter().map(|chunk| chunk.indexes[index_id].memory_usage()).sum()
+    }
+
+    pub fn drop_index(&mut self, name: &str) -> bool {
+        match self.index_id(name) {
+            Some(index_id) => {
+                self.indexes.remove(index_id);
+                for chunk in self.chunks.iter_mut() {
+                    chunk.indexes.remove(index_id);
+                }
+                true
+            },
+            None => false
+        }
+    }
+}
+
+// merges the delta store of a chunk into its main store
+//  the new main store is built while only holding a read lock, so queries and inserts are blocked only while swapping in the result
+//  rows inserted in the meantime remain in the (new) delta store
+//  fails (leaving the chunk as it is) if the values of the main store cannot be read
+pub fn merge_chunk(table: &RwLock<Table>, chunk_id: ChunkId) -> io::Result<()> {
+    let merge_lock = table.read().unwrap().merge_lock.clone();
+    let _merge_guard = merge_lock.lock().unwrap();
+    let (main, statistics, mut definitions, mut indexes, rows) = {
+        let table = table.read().unwrap();
+        let chunk = table.get_chunk(chunk_id);
+        let rows = chunk.delta_len();
+        if rows == 0 {
+            return Ok(())
+        }
+        let main = chunk.merge_delta(rows)?;
+        let statistics = main.iter().map(|avc| avc.statistics()).collect::<io::Result<_>>()?;
+        // indexes of the new main stores, rows inserted in the meantime are added to them below
+        let indexes: Vec<_> = table.indexes.iter()
+            .map(|index| create_chunk_index(table.columns[index.column_id].data_type, main[index.column_id].as_ref(), None))
+            .collect::<io::Result<_>>()?;
+        (main, statistics, table.indexes.clone(), indexes, rows)
+    };
+
+    let mut table = table.write().unwrap();
+    let target_chunk_size = table.target_chunk_size;
+    if table.indexes != definitions {
+        // an index was created or dropped in the meantime
+        indexes = table.indexes.iter()
+            .map(|index| create_chunk_index(table.columns[index.column_id].data_type, main[index.column_id].as_ref(), None))
+            .collect::<io::Result<_>>()?;
+        definitions = table.indexes.clone();
+    }
+    let chunk = &mut table.chunks[chunk_id];
+    let delta: Vec<_> = chunk.delta.iter().map(|delta| delta.split_off(rows)).collect();
+    for (index, definition) in indexes.iter_mut().zip(definitions.iter()) {
+        let delta = &delta[definition.column_id];
+        for offset in 0..delta.len() {
+            index.insert_delta(offset as ChunkOffset, &delta.get_value(offset)?);
+        }
+    }
+    chunk.columns = main;
+    chunk.statistics = statistics;
+    chunk.delta = delta;
+    chunk.indexes = indexes;
+    if chunk.delta_len() == 0 && chunk.len() >= target_chunk_size {
+        // full chunks do not receive new rows anymore
+        chunk.delta = Vec::new();
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use std::any::Any;
+use std::io;
+
+use super::avc::{AttributeValueContainer, DynAttributeValueContainer, Encoding};
+use super::column_file::{read_values, write_values, SegmentReader, SegmentWriter};
+use super::encoding::count_distinct;
+use super::statistics::ColumnStatistics;
+use super::value::{Value, ValueType};
+
+// values stored as they are, fastest access but no compression
+pub struct UnencodedAttributeValueContainer<T> {
+    values: Vec<Option<T>>,
+    distinct_count: usize
+}
+
+impl<T: ValueType> UnencodedAttributeValueContainer<T> {
+    pub fn new(column: &[Option<T>]) -> Self {
+        UnencodedAttributeValueContainer { values: column.to_vec(), distinct_count: count_distinct(column) }
+    }
+
+    pub fn write(&self, writer: &mut SegmentWriter) {
+        writer.write_len(self.distinct_count);
+        write_values(&self.values, writer);
+    }
+
+    // the values are deserialized, as they are not stored in their in-memory layout
+    pub fn read(reader: &mut SegmentReader) -> io::Result<Self> {
+        let distinct_count = reader.read_len()?;
+        Ok(UnencodedAttributeValueContainer { values: read_values(reader)?, distinct_count })
+    }
+}
+
+impl<T: ValueType> DynAttributeValueContainer for UnencodedAttributeValueContainer<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn distinct_count(&self) -> usize {
+        self.distinct_count
+    }
+
+    fn get_value(&self, i: usize) -> io::Result<Value> {
+        Ok(match self.lookup(i)? {
+            Some(val) => val.into_value(),
+            None => Value::Null
+        })
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding::Unencoded
+    }
+
+    fn memory_usage(&self) -> usize {
+        let heap_size: usize = self.values.iter().flatten().map(|val| val.heap_size()).sum();
+        self.values.len() * std::mem::size_of::<Option<T>>() + heap_size
+    }
+
+    fn statistics(&self) -> io::Result<ColumnStatistics> {
+        Ok(ColumnStatistics::from_values(self.values.iter().cloned(), self.distinct_count))
+    }
+}
+
+impl<T: ValueType> AttributeValueContainer<T> for UnencodedAttributeValueContainer<T> {
+    fn lookup(&self, i: usize) -> io::Result<Option<T>> {
+        Ok(self.values[i].clone())
+    }
+}
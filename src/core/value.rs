@@ -0,0 +1,357 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::Debug;
+use std::io;
+
+use super::avc::{Dict, FixedSizeDict, ValueId};
+use super::buffer::Pod;
+use super::column_file::{invalid_data, SegmentReader, SegmentWriter};
+use super::date::Date;
+use super::decimal::{Decimal, MAX_DECIMAL_PRECISION};
+use super::front_coded_dict::FrontCodedDict;
+
+// a single, dynamically typed value (e.g., a literal of a statement or a value read from a column of unknown type)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    BigInt(i64),
+    Double(f64),
+    Text(String),
+    Date(Date),
+    Decimal(Decimal),
+    Boolean(bool)
+}
+
+impl Value {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    // comparison of values of compatible types (numbers are compared across types), None if any of them is NULL or
+    //  the types are not comparable
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::BigInt(a), Value::BigInt(b)) => Some(a.cmp(b)),
+            (Value::Decimal(a), Value::Decimal(b)) => Some(a.cmp(b)),
+            (Value::BigInt(a), Value::Decimal(b)) => Some(Decimal::from_i64(*a).cmp(b)),
+            (Value::Decimal(a), Value::BigInt(b)) => Some(a.cmp(&Decimal::from_i64(*b))),
+            (Value::Double(a), Value::Double(b)) => a.partial_cmp(b),
+            (Value::Double(a), Value::BigInt(b)) => a.partial_cmp(&(*b as f64)),
+            (Value::BigInt(a), Value::Double(b)) => (*a as f64).partial_cmp(b),
+            (Value::Double(a), Value::Decimal(b)) => a.partial_cmp(&b.to_f64()),
+            (Value::Decimal(a), Value::Double(b)) => a.to_f64().partial_cmp(b),
+            (Value::Text(a), Value::Text(b)) => Some(a.cmp(b)),
+            (Value::Date(a), Value::Date(b)) => Some(a.cmp(b)),
+            (Value::Boolean(a), Value::Boolean(b)) => Some(a.cmp(b)),
+            _ => None
+        }
+    }
+
+    // encoding for result columns requested in binary format (the text format is given by the Display implementation)
+    //  returns None for NULL, which is not encoded but sent as length -1
+    #[allow(dead_code)]
+    pub fn to_binary(&self) -> Option<Vec<u8>> {
+        match self {
+            Value::Null => None,
+            Value::BigInt(val) => Some(val.to_be_bytes().to_vec()),
+            Value::Double(val) => Some(val.to_bits().to_be_bytes().to_vec()),
+            Value::Text(val) => Some(val.as_bytes().to_vec()),
+            Value::Date(val) => Some(val.to_postgres_days().to_be_bytes().to_vec()),
+            Value::Decimal(val) => Some(val.to_postgres_binary()),
+            Value::Boolean(val) => Some(vec![*val as u8])
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.pad("NULL"),
+            Value::BigInt(val) => fmt::Display::fmt(val, f),
+            Value::Double(val) => fmt::Display::fmt(val, f),
+            Value::Text(val) => f.pad(val),
+            Value::Date(val) => fmt::Display::fmt(val, f),
+            Value::Decimal(val) => fmt::Display::fmt(val, f),
+            // text format of PostgreSQL
+            Value::Boolean(val) => f.pad(if *val { "t" } else { "f" })
+        }
+    }
+}
+
+// implemented by all types that can be stored in attribute value containers
+pub trait ValueType: 'static + Clone + PartialOrd + Send + Sync + Debug {
+    fn into_value(self) -> Value;
+    // returns None if the value cannot be represented by this type (NULL is handled by the caller)
+    fn from_value(value: &Value) -> Option<Self>;
+    // creates a dictionary from entries that are already sorted and free of duplicates
+    fn create_dict(entries: Vec<Self>) -> Box<dyn Dict<Self> + Send + Sync>;
+    // bytes allocated by the value in addition to its own size
+    fn heap_size(&self) -> usize {
+        0
+    }
+    // serialization, e.g., for block compression, reading fails if the bytes were not written by write_bytes
+    fn write_bytes(&self, buf: &mut Vec<u8>);
+    fn read_bytes(buf: &[u8], offset: &mut usize) -> io::Result<Self>;
+    // integer representation for frame-of-reference encoding, None for types that have none
+    fn to_integer(&self) -> Option<i64> {
+        None
+    }
+    fn from_integer(_value: i64) -> Option<Self> {
+        None
+    }
+    // persistence of dictionaries in column files, by default the entries are serialized one by one and read into a new
+    //  dictionary (types with a fixed-size in-memory layout map their entries instead, see write_fixed_size_dict)
+    fn write_dict(dict: &dyn Dict<Self>, writer: &mut SegmentWriter) {
+        let mut buf = Vec::new();
+        for vid in 0..dict.len() {
+            dict.lookup(vid as ValueId).write_bytes(&mut buf);
+        }
+        writer.write_len(dict.len());
+        writer.write_bytes(&buf);
+    }
+    fn read_dict(reader: &mut SegmentReader) -> io::Result<Box<dyn Dict<Self> + Send + Sync>> {
+        let len = reader.read_len()?;
+        let buf = reader.read_bytes()?;
+        let mut offset = 0;
+        let entries = (0..len).map(|_| Self::read_bytes(buf, &mut offset)).collect::<io::Result<Vec<_>>>()?;
+        Ok(Self::create_dict(entries))
+    }
+}
+
+fn write_fixed_size_dict<T: Pod>(dict: &dyn Dict<T>, writer: &mut SegmentWriter) {
+    let entries: Vec<T> = (0..dict.len()).map(|vid| dict.lookup(vid as ValueId)).collect();
+    writer.write_slice(&entries);
+}
+
+fn read_fixed_size_dict<T: Pod + PartialOrd>(reader: &mut SegmentReader) -> io::Result<Box<dyn Dict<T> + Send + Sync>> {
+    Ok(Box::new(FixedSizeDict { entries: reader.read_slice()? }))
+}
+
+// the next len bytes
+fn read_slice<'a>(buf: &'a [u8], offset: &mut usize, len: usize) -> io::Result<&'a [u8]> {
+    let bytes = offset.checked_add(len).and_then(|end| buf.get(*offset..end)).ok_or_else(|| invalid_data("unexpected end of value"))?;
+    *offset += len;
+    Ok(bytes)
+}
+
+fn read_array<const N: usize>(buf: &[u8], offset: &mut usize) -> io::Result<[u8; N]> {
+    let mut result = [0; N];
+    result.copy_from_slice(read_slice(buf, offset, N)?);
+    Ok(result)
+}
+
+impl ValueType for i64 {
+    fn into_value(self) -> Value {
+        Value::BigInt(self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::BigInt(val) => Some(*val),
+            _ => None
+        }
+    }
+
+    fn create_dict(entries: Vec<Self>) -> Box<dyn Dict<Self> + Send + Sync> {
+        Box::new(FixedSizeDict { entries: entries.into() })
+    }
+
+    fn write_dict(dict: &dyn Dict<Self>, writer: &mut SegmentWriter) {
+        write_fixed_size_dict(dict, writer);
+    }
+
+    fn read_dict(reader: &mut SegmentReader) -> io::Result<Box<dyn Dict<Self> + Send + Sync>> {
+        read_fixed_size_dict(reader)
+    }
+
+    fn write_bytes(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_bytes(buf: &[u8], offset: &mut usize) -> io::Result<Self> {
+        Ok(i64::from_le_bytes(read_array(buf, offset)?))
+    }
+
+    fn to_integer(&self) -> Option<i64> {
+        Some(*self)
+    }
+
+    fn from_integer(value: i64) -> Option<Self> {
+        Some(value)
+    }
+}
+
+impl ValueType for f64 {
+    fn into_value(self) -> Value {
+        Value::Double(self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::BigInt(val) => Some(*val as f64),
+            Value::Double(val) => Some(*val),
+            _ => None
+        }
+    }
+
+    fn create_dict(entries: Vec<Self>) -> Box<dyn Dict<Self> + Send + Sync> {
+        Box::new(FixedSizeDict { entries: entries.into() })
+    }
+
+    fn write_dict(dict: &dyn Dict<Self>, writer: &mut SegmentWriter) {
+        write_fixed_size_dict(dict, writer);
+    }
+
+    fn read_dict(reader: &mut SegmentReader) -> io::Result<Box<dyn Dict<Self> + Send + Sync>> {
+        read_fixed_size_dict(reader)
+    }
+
+    fn write_bytes(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_bytes(buf: &[u8], offset: &mut usize) -> io::Result<Self> {
+        Ok(f64::from_le_bytes(read_array(buf, offset)?))
+    }
+}
+
+impl ValueType for String {
+    fn into_value(self) -> Value {
+        Value::Text(self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Text(val) => Some(val.clone()),
+            _ => None
+        }
+    }
+
+    fn create_dict(entries: Vec<Self>) -> Box<dyn Dict<Self> + Send + Sync> {
+        Box::new(FrontCodedDict::new(&entries))
+    }
+
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+
+    // length (32 bit) followed by the UTF-8 bytes
+    fn write_dict(dict: &dyn Dict<Self>, writer: &mut SegmentWriter) {
+        let entries: Vec<String> = (0..dict.len()).map(|vid| dict.lookup(vid as ValueId)).collect();
+        FrontCodedDict::new(&entries).write(writer);
+    }
+
+    fn read_dict(reader: &mut SegmentReader) -> io::Result<Box<dyn Dict<Self> + Send + Sync>> {
+        Ok(Box::new(FrontCodedDict::read(reader)?))
+    }
+
+    fn write_bytes(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&(self.len() as u32).to_le_bytes());
+        buf.extend_from_slice(self.as_bytes());
+    }
+
+    fn read_bytes(buf: &[u8], offset: &mut usize) -> io::Result<Self> {
+        let len = u32::from_le_bytes(read_array(buf, offset)?) as usize;
+        String::from_utf8(read_slice(buf, offset, len)?.to_vec()).map_err(|_| invalid_data("invalid UTF-8 in string value"))
+    }
+}
+
+impl ValueType for Date {
+    fn into_value(self) -> Value {
+        Value::Date(self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Date(val) => Some(*val),
+            _ => None
+        }
+    }
+
+    fn create_dict(entries: Vec<Self>) -> Box<dyn Dict<Self> + Send + Sync> {
+        Box::new(FixedSizeDict { entries: entries.into() })
+    }
+
+    fn write_dict(dict: &dyn Dict<Self>, writer: &mut SegmentWriter) {
+        write_fixed_size_dict(dict, writer);
+    }
+
+    fn read_dict(reader: &mut SegmentReader) -> io::Result<Box<dyn Dict<Self> + Send + Sync>> {
+        read_fixed_size_dict(reader)
+    }
+
+    fn write_bytes(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.0.to_le_bytes());
+    }
+
+    fn read_bytes(buf: &[u8], offset: &mut usize) -> io::Result<Self> {
+        Ok(Date(i32::from_le_bytes(read_array(buf, offset)?)))
+    }
+
+    fn to_integer(&self) -> Option<i64> {
+        Some(self.0 as i64)
+    }
+
+    fn from_integer(value: i64) -> Option<Self> {
+        Some(Date(value as i32))
+    }
+}
+
+impl ValueType for Decimal {
+    fn into_value(self) -> Value {
+        Value::Decimal(self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::BigInt(val) => Some(Decimal::from_i64(*val)),
+            Value::Decimal(val) => Some(*val),
+            _ => None
+        }
+    }
+
+    fn create_dict(entries: Vec<Self>) -> Box<dyn Dict<Self> + Send + Sync> {
+        Box::new(FixedSizeDict { entries: entries.into() })
+    }
+
+    // unscaled value (128 bit) followed by the scale (8 bit)
+    fn write_bytes(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.unscaled_value().to_le_bytes());
+        buf.push(self.scale() as u8);
+    }
+
+    fn read_bytes(buf: &[u8], offset: &mut usize) -> io::Result<Self> {
+        let value = i128::from_le_bytes(read_array(buf, offset)?);
+        let [scale] = read_array(buf, offset)?;
+        if scale as u32 > MAX_DECIMAL_PRECISION {
+            return Err(invalid_data("invalid decimal scale"))
+        }
+        Ok(Decimal::new(value, scale as u32))
+    }
+}
+
+impl ValueType for bool {
+    fn into_value(self) -> Value {
+        Value::Boolean(self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Boolean(val) => Some(*val),
+            _ => None
+        }
+    }
+
+    fn create_dict(entries: Vec<Self>) -> Box<dyn Dict<Self> + Send + Sync> {
+        Box::new(FixedSizeDict { entries: entries.into() })
+    }
+
+    fn write_bytes(&self, buf: &mut Vec<u8>) {
+        buf.push(*self as u8);
+    }
+
+    fn read_bytes(buf: &[u8], offset: &mut usize) -> io::Result<Self> {
+        let [byte] = read_array(buf, offset)?;
+        Ok(byte != 0)
+    }
+}
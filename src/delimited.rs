@@ -0,0 +1,225 @@
+use std::borrow::Cow;
+
+use crate::core::{TableError, Value};
+
+// delimited text formats for bulk loading and export (COPY), following PostgreSQL's text and CSV formats
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    // special characters in values are escaped with backslashes
+    Text,
+    // values containing special characters are enclosed in quotes
+    Csv
+}
+
+#[derive(Debug, Clone)]
+pub struct DelimitedFormat {
+    pub format: Format,
+    pub delimiter: u8,
+    // representation of NULL (compared before unescaping, quoted values in CSV are never NULL)
+    pub null: String,
+    // whether the first line holds the column names
+    pub header: bool,
+    // only used by CSV
+    pub quote: u8,
+    pub escape: u8
+}
+
+impl DelimitedFormat {
+    // PostgreSQL defaults
+    pub fn text() -> Self {
+        DelimitedFormat { format: Format::Text, delimiter: b'\t', null: String::from("\\N"), header: false, quote: b'"', escape: b'"' }
+    }
+
+    pub fn csv() -> Self {
+        DelimitedFormat { format: Format::Csv, delimiter: b',', null: String::new(), header: false, quote: b'"', escape: b'"' }
+    }
+
+    // .tbl files of the TPC-H dbgen tool: fields separated (and terminated) by '|', no NULLs
+    pub fn tbl() -> Self {
+        DelimitedFormat { delimiter: b'|', null: String::new(), ..DelimitedFormat::text() }
+    }
+
+    // whether the input read so far forms a complete record, i.e., does not end within a quoted CSV value (which may contain
+    //  line breaks)
+    pub fn is_complete_record(&self, record: &[u8]) -> bool {
+        if self.format != Format::Csv {
+            return true
+        }
+        let mut in_quotes = false;
+        let mut i = 0;
+        while i < record.len() {
+            if in_quotes && record[i] == self.escape && record.get(i + 1).is_some_and(|next| *next == self.quote || *next == self.escape) {
+                i += 1;
+            } else if record[i] == self.quote {
+                in_quotes = !in_quotes;
+            }
+            i += 1;
+        }
+        !in_quotes
+    }
+
+    // splits a record (without line terminator) into its fields, None being NULL
+    pub fn split_record<'a>(&self, record: &'a [u8]) -> Result<Vec<Option<Cow<'a, [u8]>>>, TableError> {
+        match self.format {
+            Format::Text => Ok(self.split_text(record)),
+            Format::Csv => self.split_csv(record)
+        }
+    }
+
+    fn split_text<'a>(&self, record: &'a [u8]) -> Vec<Option<Cow<'a, [u8]>>> {
+        let mut fields = Vec::new();
+        let mut start = 0;
+        let mut escaped = false;
+        let mut i = 0;
+        while i <= record.len() {
+            if i == record.len() || record[i] == self.delimiter {
+                let field = &record[start..i];
+                if field == self.null.as_bytes() {
+                    fields.push(None);
+                } else if escaped {
+                    fields.push(Some(Cow::Owned(unescape(field))));
+                } else {
+                    fields.push(Some(Cow::Borrowed(field)));
+                }
+                start = i + 1;
+                escaped = false;
+            } else if record[i] == b'\\' {
+                // the escaped character cannot end the field
+                escaped = true;
+                i += 1;
+            }
+            i += 1;
+        }
+        fields
+    }
+
+    fn split_csv<'a>(&self, record: &'a [u8]) -> Result<Vec<Option<Cow<'a, [u8]>>>, TableError> {
+        let mut fields = Vec::new();
+        let mut i = 0;
+        loop {
+            // quoted and unquoted parts of a value may be mixed, e.g., a"b,c"d is the value ab,cd
+            let start = i;
+            let mut value = Vec::new();
+            let mut quoted = false;
+            while i < record.len() && record[i] != self.delimiter {
+                if record[i] == self.quote {
+                    quoted = true;
+                    i += 1;
+                    loop {
+                        if i == record.len() {
+                            return Err(TableError::BadCopyFormat("unterminated CSV quoted field"))
+                        }
+                        if record[i] == self.escape && record.get(i + 1).is_some_and(|next| *next == self.quote || *next == self.escape) {
+                            value.push(record[i + 1]);
+                            i += 2;
+                        } else if record[i] == self.quote {
+                            i += 1;
+                            break
+                        } else {
+                            value.push(record[i]);
+                            i += 1;
+                        }
+                    }
+                } else {
+                    value.push(record[i]);
+                    i += 1;
+                }
+            }
+            if !quoted && &record[start..i] == self.null.as_bytes() {
+                fields.push(None);
+            } else if quoted {
+                fields.push(Some(Cow::Owned(value)));
+            } else {
+                fields.push(Some(Cow::Borrowed(&record[start..i])));
+            }
+            if i == record.len() {
+                return Ok(fields)
+            }
+            // skip the delimiter
+            i += 1;
+        }
+    }
+
+    // appends a record (including the line terminator) to the output
+    pub fn write_record(&self, output: &mut Vec<u8>, values: &[Value]) {
+        for (i, value) in values.iter().enumerate() {
+            if i > 0 {
+                output.push(self.delimiter);
+            }
+            match value {
+                Value::Null => output.extend_from_slice(self.null.as_bytes()),
+                value => self.write_field(output, value.to_string().as_bytes())
+            }
+        }
+        output.push(b'\n');
+    }
+
+    // appends the column names as a header line
+    pub fn write_header(&self, output: &mut Vec<u8>, names: &[&str]) {
+        for (i, name) in names.iter().enumerate() {
+            if i > 0 {
+                output.push(self.delimiter);
+            }
+            self.write_field(output, name.as_bytes());
+        }
+        output.push(b'\n');
+    }
+
+    fn write_field(&self, output: &mut Vec<u8>, field: &[u8]) {
+        match self.format {
+            Format::Text => {
+                for byte in field {
+                    match *byte {
+                        b'\\' => output.extend_from_slice(b"\\\\"),
+                        b'\n' => output.extend_from_slice(b"\\n"),
+                        b'\r' => output.extend_from_slice(b"\\r"),
+                        b'\t' => output.extend_from_slice(b"\\t"),
+                        byte if byte == self.delimiter => output.extend_from_slice(&[b'\\', byte]),
+                        byte => output.push(byte)
+                    }
+                }
+            },
+            Format::Csv => {
+                // values that could be mistaken for NULL are quoted as well
+                let needs_quotes = field == self.null.as_bytes()
+                    || field.iter().any(|byte| *byte == self.delimiter || *byte == self.quote || *byte == b'\n' || *byte == b'\r');
+                if !needs_quotes {
+                    output.extend_from_slice(field);
+                    return
+                }
+                output.push(self.quote);
+                for byte in field {
+                    if *byte == self.quote || *byte == self.escape {
+                        output.push(self.escape);
+                    }
+                    output.push(*byte);
+                }
+                output.push(self.quote);
+            }
+        }
+    }
+}
+
+// resolves the backslash escapes of the text format (unknown escapes stand for the escaped character itself)
+fn unescape(field: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(field.len());
+    let mut bytes = field.iter();
+    while let Some(byte) = bytes.next() {
+        if *byte != b'\\' {
+            result.push(*byte);
+            continue
+        }
+        match bytes.next() {
+            Some(b'b') => result.push(0x08),
+            Some(b'f') => result.push(0x0c),
+            Some(b'n') => result.push(b'\n'),
+            Some(b'r') => result.push(b'\r'),
+            Some(b't') => result.push(b'\t'),
+            Some(b'v') => result.push(0x0b),
+            Some(byte) => result.push(*byte),
+            None => {}
+        }
+    }
+    result
+}
@@ -0,0 +1,271 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{mpsc, Arc};
+
+use crate::delimited::DelimitedFormat;
+use crate::threadpool::ThreadPool;
+use crate::core::{Chunk, ColumnDefinition, ColumnId, DataType, Date, Decimal, DynAttributeValueContainer, Table, TableError, Value, ValueType};
+
+// bulk loading of tables from delimited text files (e.g., .tbl files generated by the TPC-H dbgen tool or CSV)
+
+// column values collected while loading, before they are encoded chunk by chunk
+enum RawColumn {
+    BigInt(Vec<Option<i64>>),
+    Double(Vec<Option<f64>>),
+    Text(Vec<Option<String>>),
+    Date(Vec<Option<Date>>),
+    Decimal(Vec<Option<Decimal>>),
+    Boolean(Vec<Option<bool>>)
+}
+
+impl RawColumn {
+    fn new(data_type: DataType) -> Self {
+        match data_type {
+            DataType::BigInt => RawColumn::BigInt(Vec::new()),
+            DataType::Double => RawColumn::Double(Vec::new()),
+            DataType::Char(_) | DataType::Varchar(_) | DataType::Text => RawColumn::Text(Vec::new()),
+            DataType::Date => RawColumn::Date(Vec::new()),
+            DataType::Decimal(_, _) => RawColumn::Decimal(Vec::new()),
+            DataType::Boolean => RawColumn::Boolean(Vec::new())
+        }
+    }
+
+    // the value has to be converted to the column type before (see ColumnDefinition::coerce)
+    fn push(&mut self, value: &Value) {
+        match self {
+            RawColumn::BigInt(vec) => vec.push(ValueType::from_value(value)),
+            RawColumn::Double(vec) => vec.push(ValueType::from_value(value)),
+            RawColumn::Text(vec) => vec.push(ValueType::from_value(value)),
+            RawColumn::Date(vec) => vec.push(ValueType::from_value(value)),
+            RawColumn::Decimal(vec) => vec.push(ValueType::from_value(value)),
+            RawColumn::Boolean(vec) => vec.push(ValueType::from_value(value))
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            RawColumn::BigInt(vec) => vec.len(),
+            RawColumn::Double(vec) => vec.len(),
+            RawColumn::Text(vec) => vec.len(),
+            RawColumn::Date(vec) => vec.len(),
+            RawColumn::Decimal(vec) => vec.len(),
+            RawColumn::Boolean(vec) => vec.len()
+        }
+    }
+
+    // encodes the given rows (the encoding is chosen per chunk, see core::encode_column)
+    fn encode(&self, rows: Range<usize>) -> Box<dyn DynAttributeValueContainer + Send + Sync> {
+        match self {
+            RawColumn::BigInt(vec) => crate::core::encode_column(&vec[rows]),
+            RawColumn::Double(vec) => crate::core::encode_column(&vec[rows]),
+            RawColumn::Text(vec) => crate::core::encode_column(&vec[rows]),
+            RawColumn::Date(vec) => crate::core::encode_column(&vec[rows]),
+            RawColumn::Decimal(vec) => crate::core::encode_column(&vec[rows]),
+            RawColumn::Boolean(vec) => crate::core::encode_column(&vec[rows])
+        }
+    }
+}
+
+// an invalid row of the input, which is skipped while loading
+#[derive(Debug)]
+pub struct RowError {
+    // line number within the input (starting at 1)
+    pub line: usize,
+    // position (starting at 1) and name of the invalid field, None if the row as a whole is malformed
+    pub column: Option<(usize, String)>,
+    pub error: TableError
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.column {
+            Some((position, name)) => write!(f, "line {}, column {} ({}): {}", self.line, position, name, self.error),
+            None => write!(f, "line {}: {}", self.line, self.error)
+        }
+    }
+}
+
+// everything the workers need to parse the blocks of an input
+struct InputLayout {
+    columns: Vec<ColumnDefinition>,
+    format: DelimitedFormat,
+    // the column of each input field
+    fields: Vec<ColumnId>,
+    // columns that do not appear in the input (and are thus NULL)
+    missing: Vec<ColumnId>
+}
+
+fn parse_field(field: Option<&[u8]>, column: &ColumnDefinition) -> Result<Value, TableError> {
+    let field = match field {
+        Some(field) => std::str::from_utf8(field).map_err(|_| TableError::InvalidByteSequence)?,
+        None => return column.coerce(Value::Null)
+    };
+    column.coerce(column.data_type.parse(field)?)
+}
+
+// parses a record (without line terminator) into a table row
+//  on error, the position of the invalid field in the input is returned alongside the error (if the error concerns a single field)
+fn parse_row(record: &[u8], layout: &InputLayout) -> Result<Vec<Value>, (Option<usize>, TableError)> {
+    let mut fields = layout.format.split_record(record).map_err(|error| (None, error))?;
+    // a delimiter at the end of each record is tolerated, like in .tbl files
+    if fields.len() == layout.fields.len() + 1 && record.last() == Some(&layout.format.delimiter) {
+        fields.pop();
+    }
+    if fields.len() != layout.fields.len() {
+        return Err((None, TableError::ColumnCountMismatch(layout.fields.len(), fields.len())))
+    }
+    let mut row = vec![Value::Null; layout.columns.len()];
+    for (position, (field, column_id)) in fields.iter().zip(layout.fields.iter()).enumerate() {
+        row[*column_id] = parse_field(field.as_deref(), &layout.columns[*column_id]).map_err(|error| (Some(position), error))?;
+    }
+    for column_id in layout.missing.iter() {
+        row[*column_id] = layout.columns[*column_id].coerce(Value::Null).map_err(|error| (None, error))?;
+    }
+    Ok(row)
+}
+
+// a block of records, parsed and encoded as one chunk by a worker of the thread pool
+struct Block {
+    data: Vec<u8>,
+    // end offset (exclusive) and line number of each record
+    records: Vec<(usize, usize)>
+}
+
+// parses a block and encodes the valid rows as a chunk, returns None instead of a chunk if no row is valid
+//  the chunk is an error if its statistics cannot be computed from the encoded values
+fn load_block(block: &Block, layout: &InputLayout) -> (io::Result<Option<Chunk>>, Vec<RowError>) {
+    let mut raw_columns: Vec<RawColumn> = layout.columns.iter().map(|column| RawColumn::new(column.data_type)).collect();
+    let mut errors = Vec::new();
+    let mut start = 0;
+    for (end, line) in block.records.iter() {
+        let record = &block.data[start..*end];
+        let record = record.strip_suffix(b"\n").unwrap_or(record);
+        let record = record.strip_suffix(b"\r").unwrap_or(record);
+        start = *end;
+        match parse_row(record, layout) {
+            Ok(row) => {
+                for (column, value) in raw_columns.iter_mut().zip(row.iter()) {
+                    column.push(value);
+                }
+            },
+            Err((position, error)) => errors.push(RowError {
+                line: *line,
+                column: position.map(|position| (position + 1, layout.columns[layout.fields[position]].name.clone())),
+                error
+            })
+        }
+    }
+    let row_count = raw_columns.first().map_or(0, |column| column.len());
+    if row_count == 0 {
+        return (Ok(None), errors)
+    }
+    (Chunk::new(raw_columns.iter().map(|column| column.encode(0..row_count)).collect()).map(Some), errors)
+}
+
+// appends the next record (which may span several lines in CSV) to data, returns the number of lines read (0 at the end of
+//  the input)
+fn read_record<R: BufRead>(input: &mut R, data: &mut Vec<u8>, format: &DelimitedFormat) -> io::Result<usize> {
+    let start = data.len();
+    let mut lines = 0;
+    while input.read_until(b'\n', data)? > 0 {
+        lines += 1;
+        if format.is_complete_record(&data[start..]) {
+            break
+        }
+    }
+    Ok(lines)
+}
+
+// a line consisting of \. ends the data (as sent by older clients in COPY FROM STDIN), anything after it is ignored
+fn is_end_of_data(record: &[u8]) -> bool {
+    let record = record.strip_suffix(b"\n").unwrap_or(record);
+    record.strip_suffix(b"\r").unwrap_or(record) == b"\\."
+}
+
+// number of blocks per worker thread that may be read ahead of the encoding, limits the memory needed for loading
+const BLOCKS_IN_FLIGHT_PER_THREAD: usize = 2;
+
+// loads the records of the input into a new table, rows with invalid values are skipped and returned as errors
+//  fields holds the column of each input field (all columns in order if None), the other columns are NULL
+//  the input is read sequentially in blocks of chunk_size records, which are parsed and encoded into chunks by the thread pool
+//  (only the blocks currently in flight are held in memory in their unencoded form)
+pub fn load_table<R: BufRead>(pool: &ThreadPool, mut input: R, columns: Vec<ColumnDefinition>, fields: Option<Vec<ColumnId>>, format: &DelimitedFormat, chunk_size: usize) -> io::Result<(Table, Vec<RowError>)> {
+    let fields = fields.unwrap_or_else(|| (0..columns.len()).collect());
+    let missing = (0..columns.len()).filter(|column_id| !fields.contains(column_id)).collect();
+    let layout = Arc::new(InputLayout { columns, format: format.clone(), fields, missing });
+    let (sender, receiver) = mpsc::channel();
+    let max_in_flight = BLOCKS_IN_FLIGHT_PER_THREAD * pool.size();
+    let mut results = Vec::new();
+    let mut in_flight = 0;
+    let mut next_line = 1;
+    let mut read_result = Ok(());
+    let mut end_of_data = false;
+    if format.header {
+        match read_record(&mut input, &mut Vec::new(), format) {
+            Ok(lines) => next_line += lines,
+            Err(err) => read_result = Err(err)
+        }
+    }
+    for block_id in 0.. {
+        if read_result.is_err() {
+            break
+        }
+        let mut block = Block { data: Vec::new(), records: Vec::new() };
+        while block.records.len() < chunk_size && !end_of_data {
+            let start = block.data.len();
+            match read_record(&mut input, &mut block.data, format) {
+                Ok(0) => break,
+                Ok(_) if is_end_of_data(&block.data[start..]) => {
+                    block.data.truncate(start);
+                    end_of_data = true;
+                },
+                Ok(lines) => {
+                    block.records.push((block.data.len(), next_line));
+                    next_line += lines;
+                },
+                Err(err) => {
+                    read_result = Err(err);
+                    break
+                }
+            }
+        }
+        if block.records.is_empty() || read_result.is_err() {
+            break
+        }
+        if in_flight == max_in_flight {
+            results.push(receiver.recv().unwrap());
+            in_flight -= 1;
+        }
+        let sender = sender.clone();
+        let layout = layout.clone();
+        pool.execute(move || {
+            let (chunk, errors) = load_block(&block, &layout);
+            sender.send((block_id, chunk, errors)).unwrap();
+        });
+        in_flight += 1;
+    }
+    // blocks still in flight are awaited even if reading failed, so that no worker sends to a closed channel
+    for _ in 0..in_flight {
+        results.push(receiver.recv().unwrap());
+    }
+    read_result?;
+
+    results.sort_by_key(|(block_id, _, _)| *block_id);
+    let mut table = Table::new(layout.columns.clone(), chunk_size);
+    let mut errors = Vec::new();
+    for (_, chunk, block_errors) in results {
+        if let Some(chunk) = chunk? {
+            table.append_chunk(chunk)?;
+        }
+        errors.extend(block_errors);
+    }
+    Ok((table, errors))
+}
+
+// loads a .tbl file into a new table (see load_table)
+pub fn load_table_file(pool: &ThreadPool, path: &Path, columns: Vec<ColumnDefinition>, chunk_size: usize) -> io::Result<(Table, Vec<RowError>)> {
+    load_table(pool, BufReader::new(File::open(path)?), columns, None, &DelimitedFormat::tbl(), chunk_size)
+}
@@ -1,6 +1,10 @@
 use std::fmt;
 use sqlparser::ast::*;
 
+use crate::commands::{normalize_identifier, parse_number};
+use crate::core::{Catalog, CatalogError, ColumnDefinition, ColumnId, DataType, TableError, Value, MAX_DECIMAL_PRECISION, MIN_DIVISION_SCALE};
+use crate::planner::NodeMethod;
+
 // logical query plan nodes
 
 #[allow(dead_code)]
@@ -16,18 +20,25 @@ pub enum JoinMode {
 #[derive(Debug)]
 pub enum LQPNodeData {
     Projection,
+    // stored table, the columns are the ones of the table when the plan was created
     Table {
-        schema_name: Option<String>,
-        table_name: String
+        schema_name: String,
+        table_name: String,
+        alias: Option<String>,
+        columns: Vec<ColumnDefinition>
     },
+    // join predicates are the node expressions (none for cross joins), the left input is the first one
     Join(JoinMode),
-    // Filter (e.g., for SQL WHERE), predicates are the node expressions
-    Filter
+    // Filter (e.g., for SQL WHERE), predicates are the node expressions, rows have to match all of them (i.e., the
+    //  conjuncts of the WHERE clause)
+    Filter,
+    // node expressions are the group-by expressions followed by the aggregates, one row per group is output (a single row
+    //  without group-by expressions)
+    Aggregate
 }
 
 /*
 LQP NODE types in hyrise:
-  Aggregate,
   Alias,
   ChangeMetaTable,
   CreateTable,
@@ -57,43 +68,109 @@ LQP NODE types in hyrise:
 */
 
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum FunctionType {
     CurrentSchema,
     SessionUser
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArithmeticOperator {
+    Addition,
+    Subtraction,
+    Multiplication,
+    Division,
+    Modulo
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PredicateCondition {
+    Equals,
+    NotEquals,
+    LessThan,
+    LessThanEquals,
+    GreaterThan,
+    GreaterThanEquals,
+    // arguments are the value and the (inclusive) lower and upper bound
+    BetweenInclusive,
+    NotBetweenInclusive,
+    // arguments are the value and the elements of the list
+    In,
+    NotIn,
+    Like,
+    NotLike,
+    IsNull,
+    IsNotNull
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogicalOperator {
+    And,
+    Or
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DatePart {
+    Year,
+    Month,
+    Day
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AggregateFunction {
+    Count,
+    CountDistinct,
+    Sum,
+    Avg,
+    Min,
+    Max,
+    StandardDeviationSample
+}
+
+// the operands of an expression are its arguments
+#[derive(Debug, PartialEq)]
 pub enum LQPExpressionData {
-    Function(FunctionType)
+    Function(FunctionType),
+    // column of the output of a table node
+    Column {
+        node: usize,
+        column_id: ColumnId
+    },
+    Value(Value),
+    // parameter of a prepared statement, numbered from 1 like in $1
+    Placeholder(usize),
+    Arithmetic(ArithmeticOperator),
+    UnaryMinus,
+    Predicate(PredicateCondition),
+    Logical(LogicalOperator),
+    Not,
+    // arguments are pairs of condition and result, followed by the result if no condition is true
+    Case,
+    Cast(DataType),
+    Extract(DatePart),
+    // argument is the aggregated expression of the input of the aggregate node (none for COUNT(*))
+    Aggregate(AggregateFunction)
 }
 
 /*
 Expression types in Hyrise, but missing here:
-  Aggregate,
-  Arithmetic,
-  Cast,
-  Case,
   CorrelatedParameter,
   PQPColumn,
-  LQPColumn,
   Exists,
-  Extract,
   List,
-  Logical,
-  Placeholder,
-  Predicate,
   PQPSubquery,
-  LQPSubquery,
-  UnaryMinus,
-  Value
+  LQPSubquery
 */
 
 #[derive(Debug)]
 pub struct LQPExpression {
     // point into the expression vector of the parent LQP
     pub arguments: Vec<usize>,
-    pub data: LQPExpressionData
+    pub data: LQPExpressionData,
+    // type of the result, None if it is unknown (like for NULL, string literals and placeholders, which take the type of
+    //  the expressions they are used with)
+    pub data_type: Option<DataType>,
+    pub nullable: bool
 }
 
 #[derive(Debug)]
@@ -117,7 +194,27 @@ pub struct LQP {
 pub enum LQPError {
     Generic,
     NotSupported(&'static str),
-    ASTError(&'static str)
+    ASTError(&'static str),
+    Catalog(CatalogError),
+    // column reference as written in the query
+    UnknownColumn(String),
+    // column of USING that does not exist in the left or right table of the join
+    UnknownJoinColumn(String, &'static str),
+    AmbiguousColumn(String),
+    // qualifier of a column reference that matches no table of the FROM clause
+    MissingFromEntry(String),
+    // table name or alias used more than once in the FROM clause
+    DuplicateAlias(String),
+    WildcardWithoutFrom,
+    Table(TableError),
+    InvalidLiteral(String),
+    OperatorDoesNotExist(String),
+    DatatypeMismatch(String),
+    FunctionDoesNotExist(String),
+    CannotCast(DataType, DataType),
+    // aggregates in the wrong place or columns that are neither grouped nor aggregated
+    GroupingError(String),
+    InvalidColumnReference(String)
 }
 
 impl fmt::Display for LQPError {
@@ -125,46 +222,729 @@ impl fmt::Display for LQPError {
         match self {
             LQPError::Generic => write!(f, "Generic"),
             LQPError::NotSupported(msg) =>  write!(f, "Not supported: {}", msg),
-            LQPError::ASTError(msg) =>  write!(f, "AST Error: {}", msg)
+            LQPError::ASTError(msg) =>  write!(f, "AST Error: {}", msg),
+            LQPError::Catalog(err) => write!(f, "{}", err),
+            LQPError::UnknownColumn(name) => write!(f, "column \"{}\" does not exist", name),
+            LQPError::UnknownJoinColumn(name, side) => write!(f, "column \"{}\" specified in USING clause does not exist in {} table", name, side),
+            LQPError::AmbiguousColumn(name) => write!(f, "column reference \"{}\" is ambiguous", name),
+            LQPError::MissingFromEntry(name) => write!(f, "missing FROM-clause entry for table \"{}\"", name),
+            LQPError::DuplicateAlias(name) => write!(f, "table name \"{}\" specified more than once", name),
+            LQPError::WildcardWithoutFrom => write!(f, "SELECT * with no tables specified is not valid"),
+            LQPError::Table(err) => write!(f, "{}", err),
+            LQPError::InvalidLiteral(literal) => write!(f, "invalid literal: {}", literal),
+            LQPError::OperatorDoesNotExist(operation) => write!(f, "operator does not exist: {}", operation),
+            LQPError::DatatypeMismatch(msg) => write!(f, "{}", msg),
+            LQPError::FunctionDoesNotExist(function) => write!(f, "function {} does not exist", function),
+            LQPError::CannotCast(from, to) => write!(f, "cannot cast type {} to {}", from, to),
+            LQPError::GroupingError(msg) => write!(f, "{}", msg),
+            LQPError::InvalidColumnReference(msg) => write!(f, "{}", msg)
+        }
+    }
+}
+
+impl LQPError {
+    pub fn sqlstate(&self) -> &'static str {
+        match self {
+            LQPError::Generic | LQPError::ASTError(_) => "42000",
+            LQPError::NotSupported(_) => "0A000",
+            LQPError::Catalog(err) => err.sqlstate(),
+            LQPError::UnknownColumn(_) | LQPError::UnknownJoinColumn(_, _) => "42703",
+            LQPError::AmbiguousColumn(_) => "42702",
+            LQPError::MissingFromEntry(_) => "42P01",
+            LQPError::DuplicateAlias(_) => "42712",
+            LQPError::WildcardWithoutFrom => "42601",
+            LQPError::Table(err) => err.sqlstate(),
+            LQPError::InvalidLiteral(_) => "22P02",
+            LQPError::OperatorDoesNotExist(_) | LQPError::FunctionDoesNotExist(_) => "42883",
+            LQPError::DatatypeMismatch(_) => "42804",
+            LQPError::CannotCast(_, _) => "42846",
+            LQPError::GroupingError(_) => "42803",
+            LQPError::InvalidColumnReference(_) => "42P10"
+        }
+    }
+}
+
+impl From<CatalogError> for LQPError {
+    fn from(err: CatalogError) -> Self {
+        LQPError::Catalog(err)
+    }
+}
+
+// column of a USING or NATURAL join, which unqualified references refer to instead of the joined columns
+#[derive(Debug, Clone)]
+pub struct MergedColumn {
+    name: String,
+    expression: usize,
+    // the joined columns (table node and column id)
+    columns: Vec<(usize, ColumnId)>
+}
+
+// the tables of a FROM clause, which column references are resolved against
+#[derive(Debug, Clone, Default)]
+pub struct Scope {
+    // ids of the table nodes, in the order the tables are named
+    tables: Vec<usize>,
+    // tables on the inner side of outer joins, whose columns are NULL for rows without a match
+    nullable_tables: Vec<usize>,
+    merged_columns: Vec<MergedColumn>
+}
+
+impl Scope {
+    fn extend(&mut self, other: Scope) {
+        self.tables.extend(other.tables);
+        self.nullable_tables.extend(other.nullable_tables);
+        self.merged_columns.extend(other.merged_columns);
+    }
+
+    fn merged_column(&self, node: usize, column_id: ColumnId) -> Option<usize> {
+        self.merged_columns.iter().position(|merged| merged.columns.contains(&(node, column_id)))
+    }
+
+    // names of the columns that can be referred to without qualifier
+    fn column_names(&self, lqp: &LQP) -> Vec<String> {
+        let mut names: Vec<String> = self.merged_columns.iter().map(|merged| merged.name.clone()).collect();
+        for node in self.tables.iter() {
+            if let LQPNodeData::Table { columns, .. } = &lqp.nodes[*node].data {
+                for (column_id, column) in columns.iter().enumerate() {
+                    if self.merged_column(*node, column_id).is_none() && !names.contains(&column.name) {
+                        names.push(column.name.clone());
+                    }
+                }
+            }
+        }
+        names
+    }
+}
+
+impl fmt::Display for ArithmeticOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArithmeticOperator::Addition => write!(f, "+"),
+            ArithmeticOperator::Subtraction => write!(f, "-"),
+            ArithmeticOperator::Multiplication => write!(f, "*"),
+            ArithmeticOperator::Division => write!(f, "/"),
+            ArithmeticOperator::Modulo => write!(f, "%")
+        }
+    }
+}
+
+impl fmt::Display for LogicalOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogicalOperator::And => write!(f, "AND"),
+            LogicalOperator::Or => write!(f, "OR")
+        }
+    }
+}
+
+// name of a type in error messages
+fn type_name(data_type: Option<DataType>) -> String {
+    match data_type {
+        Some(data_type) => data_type.to_string(),
+        None => String::from("unknown")
+    }
+}
+
+fn is_numeric(data_type: DataType) -> bool {
+    matches!(data_type, DataType::BigInt | DataType::Double | DataType::Decimal(_, _))
+}
+
+fn is_character(data_type: DataType) -> bool {
+    matches!(data_type, DataType::Char(_) | DataType::Varchar(_) | DataType::Text)
+}
+
+// whether values of the types can be compared (see Value::compare)
+fn is_comparable(left: DataType, right: DataType) -> bool {
+    (is_numeric(left) && is_numeric(right)) || (is_character(left) && is_character(right)) || left == right
+}
+
+// whether CAST supports the conversion (values of any type can be converted from and to text)
+fn is_castable(from: DataType, to: DataType) -> bool {
+    (is_numeric(from) && is_numeric(to)) || is_character(from) || is_character(to) || from == to
+}
+
+fn decimal_scale(data_type: DataType) -> u32 {
+    match data_type {
+        DataType::Decimal(_, scale) => scale,
+        _ => 0
+    }
+}
+
+// type that values of both types can be converted to (e.g., for the results of CASE), None if there is none
+fn common_type(left: DataType, right: DataType) -> Option<DataType> {
+    match (left, right) {
+        _ if left == right => Some(left),
+        _ if is_character(left) && is_character(right) => Some(DataType::Text),
+        _ if !is_numeric(left) || !is_numeric(right) => None,
+        (DataType::Double, _) | (_, DataType::Double) => Some(DataType::Double),
+        _ => Some(DataType::Decimal(MAX_DECIMAL_PRECISION, std::cmp::max(decimal_scale(left), decimal_scale(right))))
+    }
+}
+
+// result type of an arithmetic operation, None if the operator does not exist for the types
+//  integer division truncates like in PostgreSQL, decimal division has at least MIN_DIVISION_SCALE fractional digits
+fn arithmetic_type(operator: ArithmeticOperator, left: DataType, right: DataType) -> Option<DataType> {
+    match (operator, left, right) {
+        (ArithmeticOperator::Addition, DataType::Date, DataType::BigInt) | (ArithmeticOperator::Addition, DataType::BigInt, DataType::Date) => Some(DataType::Date),
+        (ArithmeticOperator::Subtraction, DataType::Date, DataType::BigInt) => Some(DataType::Date),
+        // number of days between the dates
+        (ArithmeticOperator::Subtraction, DataType::Date, DataType::Date) => Some(DataType::BigInt),
+        _ if !is_numeric(left) || !is_numeric(right) => None,
+        _ => match common_type(left, right)? {
+            DataType::Decimal(_, _) => {
+                let scale = match operator {
+                    ArithmeticOperator::Multiplication => decimal_scale(left) + decimal_scale(right),
+                    ArithmeticOperator::Division => std::cmp::max(MIN_DIVISION_SCALE, std::cmp::max(decimal_scale(left), decimal_scale(right))),
+                    _ => std::cmp::max(decimal_scale(left), decimal_scale(right))
+                };
+                Some(DataType::Decimal(MAX_DECIMAL_PRECISION, std::cmp::min(scale, MAX_DECIMAL_PRECISION)))
+            },
+            data_type => Some(data_type)
         }
     }
 }
 
+// result type of an aggregate function, None if it does not exist for the type of the argument
+//  like in PostgreSQL, averages of integers are decimals, standard deviations are computed with doubles
+fn aggregate_type(function: AggregateFunction, argument: DataType) -> Option<DataType> {
+    match function {
+        AggregateFunction::Count | AggregateFunction::CountDistinct => Some(DataType::BigInt),
+        AggregateFunction::Min | AggregateFunction::Max => Some(argument),
+        _ if !is_numeric(argument) => None,
+        AggregateFunction::Sum => match argument {
+            DataType::Decimal(_, scale) => Some(DataType::Decimal(MAX_DECIMAL_PRECISION, scale)),
+            data_type => Some(data_type)
+        },
+        AggregateFunction::Avg => match argument {
+            DataType::Double => Some(DataType::Double),
+            _ => arithmetic_type(ArithmeticOperator::Division, DataType::Decimal(MAX_DECIMAL_PRECISION, decimal_scale(argument)), DataType::BigInt)
+        },
+        AggregateFunction::StandardDeviationSample => Some(DataType::Double)
+    }
+}
+
+// type of CAST and typed literals
+pub fn convert_data_type(data_type: &sqlparser::ast::DataType) -> Result<DataType, LQPError> {
+    match data_type {
+        sqlparser::ast::DataType::SmallInt | sqlparser::ast::DataType::Int | sqlparser::ast::DataType::BigInt => Ok(DataType::BigInt),
+        sqlparser::ast::DataType::Real | sqlparser::ast::DataType::Double | sqlparser::ast::DataType::Float(_) => Ok(DataType::Double),
+        sqlparser::ast::DataType::Decimal(precision, scale) => {
+            // without precision and scale, the maximum precision and no fractional digits (as in the SQL standard)
+            let precision = precision.unwrap_or(MAX_DECIMAL_PRECISION as u64);
+            let scale = scale.unwrap_or(0);
+            if precision == 0 || precision > MAX_DECIMAL_PRECISION as u64 || scale > precision {
+                return Err(LQPError::NotSupported("DECIMAL precision must be between 1 and 38 and not below the scale"))
+            }
+            Ok(DataType::Decimal(precision as u32, scale as u32))
+        },
+        sqlparser::ast::DataType::Char(len) => Ok(DataType::Char(len.unwrap_or(1) as u32)),
+        sqlparser::ast::DataType::Varchar(len) => Ok(DataType::Varchar(len.map(|len| len as u32))),
+        sqlparser::ast::DataType::Text | sqlparser::ast::DataType::String => Ok(DataType::Text),
+        sqlparser::ast::DataType::Date => Ok(DataType::Date),
+        sqlparser::ast::DataType::Boolean => Ok(DataType::Boolean),
+        _ => Err(LQPError::NotSupported("Data type"))
+    }
+}
+
+// parameters of prepared statements are passed as identifiers like $1, which are not valid unquoted identifiers otherwise
+//  (see parse_prepared_statement)
+fn placeholder_number(ident: &Ident) -> Option<usize> {
+    if ident.quote_style.is_some() {
+        return None
+    }
+    ident.value.strip_prefix('$')?.parse().ok().filter(|number| *number > 0)
+}
+
 impl LQPExpression {
-    fn get_dot_str(&self, _expressions: &Vec<LQPExpression>) -> String {
+    fn get_dot_str(&self, lqp: &LQP) -> String {
+        let argument = |i: usize| lqp.expressions[self.arguments[i]].get_dot_str(lqp);
         match &self.data {
             LQPExpressionData::Function(func) => {
                 format!("{:?}()", func)
+            },
+            LQPExpressionData::Column { node, column_id } => {
+                match &lqp.nodes[*node].data {
+                    LQPNodeData::Table { table_name, alias, columns, .. } => format!("{}.{}", alias.as_ref().unwrap_or(table_name), columns[*column_id].name),
+                    _ => format!("#{}.{}", node, column_id)
+                }
+            },
+            LQPExpressionData::Value(Value::Text(text)) => format!("'{}'", text),
+            LQPExpressionData::Value(Value::Date(date)) => format!("DATE '{}'", date),
+            LQPExpressionData::Value(value) => value.to_string(),
+            LQPExpressionData::Placeholder(number) => format!("${}", number),
+            LQPExpressionData::Arithmetic(operator) => format!("({} {} {})", argument(0), operator, argument(1)),
+            LQPExpressionData::UnaryMinus => format!("-{}", argument(0)),
+            LQPExpressionData::Predicate(condition) => {
+                let symbol = match condition {
+                    PredicateCondition::Equals => "=",
+                    PredicateCondition::NotEquals => "<>",
+                    PredicateCondition::LessThan => "<",
+                    PredicateCondition::LessThanEquals => "<=",
+                    PredicateCondition::GreaterThan => ">",
+                    PredicateCondition::GreaterThanEquals => ">=",
+                    PredicateCondition::BetweenInclusive => return format!("({} BETWEEN {} AND {})", argument(0), argument(1), argument(2)),
+                    PredicateCondition::NotBetweenInclusive => return format!("({} NOT BETWEEN {} AND {})", argument(0), argument(1), argument(2)),
+                    PredicateCondition::In | PredicateCondition::NotIn => {
+                        let list: Vec<String> = (1..self.arguments.len()).map(argument).collect();
+                        let operator = if *condition == PredicateCondition::In { "IN" } else { "NOT IN" };
+                        return format!("({} {} ({}))", argument(0), operator, list.join(", "))
+                    },
+                    PredicateCondition::Like => "LIKE",
+                    PredicateCondition::NotLike => "NOT LIKE",
+                    PredicateCondition::IsNull => return format!("({} IS NULL)", argument(0)),
+                    PredicateCondition::IsNotNull => return format!("({} IS NOT NULL)", argument(0))
+                };
+                format!("({} {} {})", argument(0), symbol, argument(1))
+            },
+            LQPExpressionData::Logical(operator) => format!("({} {} {})", argument(0), operator, argument(1)),
+            LQPExpressionData::Not => format!("NOT {}", argument(0)),
+            LQPExpressionData::Case => {
+                let mut result = String::from("CASE");
+                for i in (0..self.arguments.len() - 1).step_by(2) {
+                    result.push_str(&format!(" WHEN {} THEN {}", argument(i), argument(i + 1)));
+                }
+                format!("{} ELSE {} END", result, argument(self.arguments.len() - 1))
+            },
+            LQPExpressionData::Cast(data_type) => format!("CAST({} AS {})", argument(0), data_type),
+            LQPExpressionData::Extract(part) => format!("EXTRACT({} FROM {})", format!("{:?}", part).to_uppercase(), argument(0)),
+            LQPExpressionData::Aggregate(AggregateFunction::Count) if self.arguments.is_empty() => String::from("COUNT(*)"),
+            LQPExpressionData::Aggregate(AggregateFunction::CountDistinct) => format!("COUNT(DISTINCT {})", argument(0)),
+            LQPExpressionData::Aggregate(AggregateFunction::StandardDeviationSample) => format!("STDDEV_SAMP({})", argument(0)),
+            LQPExpressionData::Aggregate(function) => format!("{}({})", format!("{:?}", function).to_uppercase(), argument(0))
+        }
+    }
+
+    // translates a scalar expression, column references are resolved against the tables of the scope
+    //  the types of the operands are checked and expressions of unknown type get the type of the expressions they are used with
+    pub fn from_expr(expr: &Expr, lqp: &mut LQP, scope: &Scope) -> Result<usize, LQPError> {
+        match expr {
+            Expr::Identifier(ident) => {
+                // in this context, a column or session information (see https://www.postgresql.org/docs/9.1/functions-info.html)
+                if ident.quote_style.is_none() && ident.value.eq_ignore_ascii_case("session_user") {
+                    Ok(lqp.add_typed_expression(LQPExpressionData::Function(FunctionType::SessionUser), Vec::new(), Some(DataType::Text), false))
+                } else if let Some(number) = placeholder_number(ident) {
+                    Ok(lqp.add_typed_expression(LQPExpressionData::Placeholder(number), Vec::new(), None, true))
+                } else {
+                    LQPExpression::resolve_column(std::slice::from_ref(ident), lqp, scope)
+                }
+            },
+            Expr::CompoundIdentifier(idents) => LQPExpression::resolve_column(idents, lqp, scope),
+            Expr::Function(func) => {
+                if func.name.0.len() > 1 {
+                    return Err(LQPError::NotSupported("Multipart function names are not supported"))
+                }
+                if func.over.is_some() {
+                    return Err(LQPError::NotSupported("Window functions"))
+                }
+                let name = normalize_identifier(&func.name.0[0]);
+                match name.as_str() {
+                    "current_schema" => Ok(lqp.add_typed_expression(LQPExpressionData::Function(FunctionType::CurrentSchema), Vec::new(), Some(DataType::Text), false)),
+                    "count" | "sum" | "avg" | "min" | "max" | "stddev" | "stddev_samp" => LQPExpression::from_aggregate(&name, func, lqp, scope),
+                    _ => Err(LQPError::NotSupported("Unsupported function name"))
+                }
+            },
+            Expr::Value(sqlparser::ast::Value::Number(number, _)) => {
+                let value = parse_number(number).ok_or_else(|| LQPError::InvalidLiteral(number.clone()))?;
+                let data_type = match &value {
+                    Value::BigInt(_) => DataType::BigInt,
+                    Value::Decimal(val) => DataType::Decimal(MAX_DECIMAL_PRECISION, val.scale()),
+                    _ => DataType::Double
+                };
+                Ok(lqp.add_typed_expression(LQPExpressionData::Value(value), Vec::new(), Some(data_type), false))
+            },
+            // like in PostgreSQL, string literals are of unknown type until they are used with an expression of known type
+            Expr::Value(sqlparser::ast::Value::SingleQuotedString(text)) => {
+                Ok(lqp.add_typed_expression(LQPExpressionData::Value(Value::Text(text.clone())), Vec::new(), None, false))
+            },
+            Expr::Value(sqlparser::ast::Value::Boolean(val)) => {
+                Ok(lqp.add_typed_expression(LQPExpressionData::Value(Value::Boolean(*val)), Vec::new(), Some(DataType::Boolean), false))
+            },
+            Expr::Value(sqlparser::ast::Value::Null) => Ok(lqp.add_typed_expression(LQPExpressionData::Value(Value::Null), Vec::new(), None, true)),
+            Expr::Value(_) => Err(LQPError::NotSupported("Literals other than numbers, strings, booleans and NULL")),
+            // typed literals like DATE '1998-12-01'
+            Expr::TypedString { data_type, value } => {
+                let data_type = convert_data_type(data_type)?;
+                let literal = lqp.add_typed_expression(LQPExpressionData::Value(Value::Text(value.clone())), Vec::new(), None, false);
+                lqp.coerce(literal, data_type)?;
+                Ok(literal)
+            },
+            Expr::Nested(expr) => LQPExpression::from_expr(expr, lqp, scope),
+            Expr::UnaryOp { op: UnaryOperator::Plus, expr } => {
+                let operand = LQPExpression::from_expr(expr, lqp, scope)?;
+                match lqp.expressions[operand].data_type {
+                    Some(data_type) if is_numeric(data_type) => Ok(operand),
+                    data_type => Err(LQPError::OperatorDoesNotExist(format!("+ {}", type_name(data_type))))
+                }
+            },
+            Expr::UnaryOp { op: UnaryOperator::Minus, expr } => {
+                let operand = LQPExpression::from_expr(expr, lqp, scope)?;
+                match lqp.expressions[operand].data_type {
+                    Some(data_type) if is_numeric(data_type) => {
+                        let nullable = lqp.expressions[operand].nullable;
+                        Ok(lqp.add_typed_expression(LQPExpressionData::UnaryMinus, vec![operand], Some(data_type), nullable))
+                    },
+                    data_type => Err(LQPError::OperatorDoesNotExist(format!("- {}", type_name(data_type))))
+                }
+            },
+            Expr::UnaryOp { op: UnaryOperator::Not, expr } => {
+                let operand = LQPExpression::from_expr(expr, lqp, scope)?;
+                lqp.check_boolean(operand, "NOT")?;
+                let nullable = lqp.expressions[operand].nullable;
+                Ok(lqp.add_typed_expression(LQPExpressionData::Not, vec![operand], Some(DataType::Boolean), nullable))
+            },
+            Expr::UnaryOp { .. } => Err(LQPError::NotSupported("Unary operators other than +, - and NOT")),
+            Expr::BinaryOp { left, op, right } => LQPExpression::from_binary_op(left, op, right, lqp, scope),
+            Expr::IsNull(operand) | Expr::IsNotNull(operand) => {
+                let operand = LQPExpression::from_expr(operand, lqp, scope)?;
+                let condition = if let Expr::IsNull(_) = expr { PredicateCondition::IsNull } else { PredicateCondition::IsNotNull };
+                Ok(lqp.add_typed_expression(LQPExpressionData::Predicate(condition), vec![operand], Some(DataType::Boolean), false))
+            },
+            Expr::Between { expr, negated, low, high } => {
+                let arguments = vec![
+                    LQPExpression::from_expr(expr, lqp, scope)?,
+                    LQPExpression::from_expr(low, lqp, scope)?,
+                    LQPExpression::from_expr(high, lqp, scope)?
+                ];
+                lqp.coerce_operands(&arguments)?;
+                lqp.check_comparable(arguments[0], arguments[1], ">=")?;
+                lqp.check_comparable(arguments[0], arguments[2], "<=")?;
+                let condition = if *negated { PredicateCondition::NotBetweenInclusive } else { PredicateCondition::BetweenInclusive };
+                Ok(lqp.add_predicate(condition, arguments))
+            },
+            Expr::InList { expr, list, negated } => {
+                let mut arguments = vec![LQPExpression::from_expr(expr, lqp, scope)?];
+                for element in list {
+                    arguments.push(LQPExpression::from_expr(element, lqp, scope)?);
+                }
+                lqp.coerce_operands(&arguments)?;
+                for element in arguments[1..].iter() {
+                    lqp.check_comparable(arguments[0], *element, "=")?;
+                }
+                let condition = if *negated { PredicateCondition::NotIn } else { PredicateCondition::In };
+                Ok(lqp.add_predicate(condition, arguments))
+            },
+            Expr::Case { operand, conditions, results, else_result } => {
+                LQPExpression::from_case(operand.as_deref(), conditions, results, else_result.as_deref(), lqp, scope)
+            },
+            Expr::Cast { expr, data_type } => {
+                let data_type = convert_data_type(data_type)?;
+                let operand = LQPExpression::from_expr(expr, lqp, scope)?;
+                match lqp.expressions[operand].data_type {
+                    // e.g., CAST('1998-12-01' AS DATE) is a date literal
+                    None => {
+                        lqp.coerce(operand, data_type)?;
+                        Ok(operand)
+                    },
+                    Some(from) if from == data_type => Ok(operand),
+                    Some(from) if is_castable(from, data_type) => {
+                        let nullable = lqp.expressions[operand].nullable;
+                        Ok(lqp.add_typed_expression(LQPExpressionData::Cast(data_type), vec![operand], Some(data_type), nullable))
+                    },
+                    Some(from) => Err(LQPError::CannotCast(from, data_type))
+                }
+            },
+            Expr::Extract { field, expr } => {
+                let part = match field {
+                    DateTimeField::Year => DatePart::Year,
+                    DateTimeField::Month => DatePart::Month,
+                    DateTimeField::Day => DatePart::Day,
+                    _ => return Err(LQPError::NotSupported("EXTRACT of fields other than YEAR, MONTH and DAY"))
+                };
+                let operand = LQPExpression::from_expr(expr, lqp, scope)?;
+                lqp.coerce(operand, DataType::Date)?;
+                match lqp.expressions[operand].data_type {
+                    Some(DataType::Date) => {
+                        let nullable = lqp.expressions[operand].nullable;
+                        Ok(lqp.add_typed_expression(LQPExpressionData::Extract(part), vec![operand], Some(DataType::BigInt), nullable))
+                    },
+                    data_type => Err(LQPError::FunctionDoesNotExist(format!("extract({}, {})", field, type_name(data_type))))
+                }
+            },
+            _ => Err(LQPError::NotSupported("Unsupported expression type"))
+        }
+    }
+
+    // aggregate function call, whose argument is translated like any other expression (aggregates of aggregates are invalid)
+    fn from_aggregate(name: &str, func: &Function, lqp: &mut LQP, scope: &Scope) -> Result<usize, LQPError> {
+        let function = match (name, func.distinct) {
+            ("count", false) => AggregateFunction::Count,
+            ("count", true) => AggregateFunction::CountDistinct,
+            (_, true) => return Err(LQPError::NotSupported("DISTINCT in aggregates other than COUNT")),
+            ("sum", _) => AggregateFunction::Sum,
+            ("avg", _) => AggregateFunction::Avg,
+            ("min", _) => AggregateFunction::Min,
+            ("max", _) => AggregateFunction::Max,
+            _ => AggregateFunction::StandardDeviationSample
+        };
+        let mut arguments = Vec::new();
+        for arg in func.args.iter() {
+            match arg {
+                // COUNT(*) counts the rows, so it has no argument
+                FunctionArg::Unnamed(Expr::Wildcard) if function == AggregateFunction::Count && func.args.len() == 1 => {},
+                FunctionArg::Unnamed(expr) => arguments.push(LQPExpression::from_expr(expr, lqp, scope)?),
+                FunctionArg::Named { .. } => return Err(LQPError::NotSupported("Named function arguments"))
+            }
+        }
+        for argument in arguments.iter() {
+            if lqp.contains_aggregate(*argument) {
+                return Err(LQPError::GroupingError(String::from("aggregate function calls cannot be nested")))
+            }
+            // e.g., MIN('a') or COUNT(NULL)
+            if function != AggregateFunction::Sum && function != AggregateFunction::Avg && function != AggregateFunction::StandardDeviationSample {
+                lqp.coerce(*argument, DataType::Text)?;
+            }
+        }
+        let argument_types: Vec<Option<DataType>> = arguments.iter().map(|argument| lqp.expressions[*argument].data_type).collect();
+        let data_type = match argument_types.as_slice() {
+            [] if func.args.len() == 1 => Some(DataType::BigInt),
+            [Some(argument_type)] => aggregate_type(function, *argument_type),
+            _ => None
+        };
+        let data_type = data_type.ok_or_else(|| {
+            let argument_types: Vec<String> = argument_types.into_iter().map(type_name).collect();
+            LQPError::FunctionDoesNotExist(format!("{}({})", name, argument_types.join(", ")))
+        })?;
+        // aggregates of empty groups are NULL, except for counts
+        let nullable = function != AggregateFunction::Count && function != AggregateFunction::CountDistinct;
+        Ok(lqp.add_typed_expression(LQPExpressionData::Aggregate(function), arguments, Some(data_type), nullable))
+    }
+
+    fn from_binary_op(left: &Expr, op: &BinaryOperator, right: &Expr, lqp: &mut LQP, scope: &Scope) -> Result<usize, LQPError> {
+        let left = LQPExpression::from_expr(left, lqp, scope)?;
+        let right = LQPExpression::from_expr(right, lqp, scope)?;
+        let nullable = lqp.expressions[left].nullable || lqp.expressions[right].nullable;
+        let arithmetic_operator = match op {
+            BinaryOperator::Plus => Some(ArithmeticOperator::Addition),
+            BinaryOperator::Minus => Some(ArithmeticOperator::Subtraction),
+            BinaryOperator::Multiply => Some(ArithmeticOperator::Multiplication),
+            BinaryOperator::Divide => Some(ArithmeticOperator::Division),
+            BinaryOperator::Modulus => Some(ArithmeticOperator::Modulo),
+            _ => None
+        };
+        if let Some(operator) = arithmetic_operator {
+            lqp.coerce_operands(&[left, right])?;
+            let (left_type, right_type) = (lqp.expressions[left].data_type.unwrap(), lqp.expressions[right].data_type.unwrap());
+            return match arithmetic_type(operator, left_type, right_type) {
+                Some(data_type) => Ok(lqp.add_typed_expression(LQPExpressionData::Arithmetic(operator), vec![left, right], Some(data_type), nullable)),
+                None => Err(LQPError::OperatorDoesNotExist(format!("{} {} {}", left_type, op, right_type)))
+            }
+        }
+        let condition = match op {
+            BinaryOperator::Eq => PredicateCondition::Equals,
+            BinaryOperator::NotEq => PredicateCondition::NotEquals,
+            BinaryOperator::Lt => PredicateCondition::LessThan,
+            BinaryOperator::LtEq => PredicateCondition::LessThanEquals,
+            BinaryOperator::Gt => PredicateCondition::GreaterThan,
+            BinaryOperator::GtEq => PredicateCondition::GreaterThanEquals,
+            BinaryOperator::Like | BinaryOperator::NotLike => {
+                lqp.coerce(left, DataType::Text)?;
+                lqp.coerce(right, DataType::Text)?;
+                let (left_type, right_type) = (lqp.expressions[left].data_type.unwrap(), lqp.expressions[right].data_type.unwrap());
+                if !is_character(left_type) || !is_character(right_type) {
+                    return Err(LQPError::OperatorDoesNotExist(format!("{} {} {}", left_type, op, right_type)))
+                }
+                let condition = if let BinaryOperator::Like = op { PredicateCondition::Like } else { PredicateCondition::NotLike };
+                return Ok(lqp.add_predicate(condition, vec![left, right]))
+            },
+            BinaryOperator::And | BinaryOperator::Or => {
+                let operator = if let BinaryOperator::And = op { LogicalOperator::And } else { LogicalOperator::Or };
+                lqp.check_boolean(left, &operator.to_string())?;
+                lqp.check_boolean(right, &operator.to_string())?;
+                return Ok(lqp.add_typed_expression(LQPExpressionData::Logical(operator), vec![left, right], Some(DataType::Boolean), nullable))
+            },
+            _ => return Err(LQPError::NotSupported("Operators other than arithmetic, comparisons, LIKE, AND and OR"))
+        };
+        lqp.coerce_operands(&[left, right])?;
+        lqp.check_comparable(left, right, &op.to_string())?;
+        Ok(lqp.add_predicate(condition, vec![left, right]))
+    }
+
+    // CASE x WHEN y THEN ... is translated like CASE WHEN x = y THEN ..., without ELSE, the result is NULL
+    fn from_case(operand: Option<&Expr>, conditions: &[Expr], results: &[Expr], else_result: Option<&Expr>, lqp: &mut LQP, scope: &Scope) -> Result<usize, LQPError> {
+        let operand = match operand {
+            Some(operand) => Some(LQPExpression::from_expr(operand, lqp, scope)?),
+            None => None
+        };
+        let mut arguments = Vec::new();
+        let mut result_expressions = Vec::new();
+        for (condition, result) in conditions.iter().zip(results.iter()) {
+            let mut condition = LQPExpression::from_expr(condition, lqp, scope)?;
+            match operand {
+                Some(operand) => {
+                    lqp.coerce_operands(&[operand, condition])?;
+                    lqp.check_comparable(operand, condition, "=")?;
+                    condition = lqp.add_predicate(PredicateCondition::Equals, vec![operand, condition]);
+                },
+                None => lqp.check_boolean(condition, "CASE/WHEN")?
+            }
+            let result = LQPExpression::from_expr(result, lqp, scope)?;
+            arguments.push(condition);
+            arguments.push(result);
+            result_expressions.push(result);
+        }
+        let else_result = match else_result {
+            Some(else_result) => LQPExpression::from_expr(else_result, lqp, scope)?,
+            None => lqp.add_typed_expression(LQPExpressionData::Value(Value::Null), Vec::new(), None, true)
+        };
+        arguments.push(else_result);
+        result_expressions.push(else_result);
+
+        // the results are converted to a common type, which is text if all of them are of unknown type
+        let mut data_type = None;
+        for result in result_expressions.iter() {
+            data_type = match (data_type, lqp.expressions[*result].data_type) {
+                (Some(data_type), Some(result_type)) => match common_type(data_type, result_type) {
+                    Some(data_type) => Some(data_type),
+                    None => return Err(LQPError::DatatypeMismatch(format!("CASE types {} and {} cannot be matched", data_type, result_type)))
+                },
+                (data_type, result_type) => data_type.or(result_type)
+            };
+        }
+        let data_type = data_type.unwrap_or(DataType::Text);
+        for result in result_expressions.iter() {
+            lqp.coerce(*result, data_type)?;
+        }
+        let nullable = result_expressions.iter().any(|result| lqp.expressions[*result].nullable);
+        Ok(lqp.add_typed_expression(LQPExpressionData::Case, arguments, Some(data_type), nullable))
+    }
+
+    // resolves a column reference: column, table.column or schema.table.column, where table is the alias of the table if
+    //  it has one
+    fn resolve_column(idents: &[Ident], lqp: &mut LQP, scope: &Scope) -> Result<usize, LQPError> {
+        let names: Vec<String> = idents.iter().map(normalize_identifier).collect();
+        LQPExpression::resolve_column_name(&names, lqp, scope)
+    }
+
+    fn resolve_column_name(names: &[String], lqp: &mut LQP, scope: &Scope) -> Result<usize, LQPError> {
+        let (column_name, qualifier) = names.split_last().unwrap();
+        if qualifier.len() > 2 {
+            return Err(LQPError::NotSupported("Column references with more than three parts"))
+        }
+        let mut qualifier_matched = false;
+        let mut result = None;
+        if qualifier.is_empty() {
+            for merged in scope.merged_columns.iter().filter(|merged| &merged.name == column_name) {
+                if result.is_some() {
+                    return Err(LQPError::AmbiguousColumn(names.join(".")))
+                }
+                result = Some(merged.expression);
+            }
+        }
+        for node in scope.tables.iter() {
+            let (schema_name, table_name, alias, columns) = match &lqp.nodes[*node].data {
+                LQPNodeData::Table { schema_name, table_name, alias, columns } => (schema_name, table_name, alias, columns),
+                _ => unreachable!("scopes only contain table nodes")
+            };
+            let matches = match qualifier {
+                [] => true,
+                [table] => alias.as_ref().unwrap_or(table_name) == table,
+                [schema, table] => alias.is_none() && schema_name == schema && table_name == table,
+                _ => unreachable!()
+            };
+            if !matches {
+                continue
+            }
+            qualifier_matched = true;
+            let column_id = match columns.iter().position(|column| &column.name == column_name) {
+                // without qualifier, the joined columns of USING and NATURAL joins are referred to by their merged column
+                Some(column_id) if !qualifier.is_empty() || scope.merged_column(*node, column_id).is_none() => column_id,
+                _ => continue
+            };
+            if result.is_some() {
+                return Err(LQPError::AmbiguousColumn(names.join(".")))
+            }
+            result = Some(lqp.add_column_expression(*node, column_id, scope.nullable_tables.contains(node)));
+        }
+        match result {
+            Some(result) => Ok(result),
+            None if !qualifier.is_empty() && !qualifier_matched => Err(LQPError::MissingFromEntry(qualifier.join("."))),
+            None => Err(LQPError::UnknownColumn(names.join(".")))
+        }
+    }
+
+    // all columns of the tables of the scope (whose alias or name is the qualifier, if given)
+    //  without qualifier, the joined columns of USING and NATURAL joins are replaced by their merged column
+    pub fn from_wildcard(qualifier: Option<&ObjectName>, lqp: &mut LQP, scope: &Scope) -> Result<Vec<usize>, LQPError> {
+        if scope.tables.is_empty() {
+            return Err(LQPError::WildcardWithoutFrom)
+        }
+        let qualifier: Option<Vec<String>> = qualifier.map(|name| name.0.iter().map(normalize_identifier).collect());
+        let mut expressions = Vec::new();
+        let mut merged_columns = Vec::new();
+        for node in scope.tables.iter() {
+            let (schema_name, table_name, alias, column_count) = match &lqp.nodes[*node].data {
+                LQPNodeData::Table { schema_name, table_name, alias, columns } => (schema_name, table_name, alias, columns.len()),
+                _ => unreachable!("scopes only contain table nodes")
+            };
+            let matches = match qualifier.as_deref() {
+                None => true,
+                Some([table]) => alias.as_ref().unwrap_or(table_name) == table,
+                Some([schema, table]) => alias.is_none() && schema_name == schema && table_name == table,
+                Some(_) => return Err(LQPError::NotSupported("Wildcards with more than two qualifiers"))
+            };
+            if !matches {
+                continue
             }
+            for column_id in 0..column_count {
+                match scope.merged_column(*node, column_id) {
+                    Some(merged) if qualifier.is_none() => {
+                        if !merged_columns.contains(&merged) {
+                            merged_columns.push(merged);
+                            expressions.push(scope.merged_columns[merged].expression);
+                        }
+                    },
+                    _ => expressions.push(lqp.add_column_expression(*node, column_id, scope.nullable_tables.contains(node)))
+                }
+            }
+        }
+        if let (Some(qualifier), true) = (qualifier, expressions.is_empty()) {
+            return Err(LQPError::MissingFromEntry(qualifier.join(".")))
         }
+        Ok(expressions)
     }
 }
 
 impl LQP {
-    pub fn from(sql_statement: &Statement) -> Result<LQP, LQPError> {
+    // tables are looked up in the catalog, which is only needed during the translation
+    pub fn from(sql_statement: &Statement, catalog: &Catalog) -> Result<LQP, LQPError> {
         let mut result = LQP { expressions: Vec::new(), nodes: Vec::new(), root_node: 0 };
-        let node = LQPNode::from(&sql_statement, &mut result)?;
+        let node = LQPNode::from(&sql_statement, &mut result, catalog)?;
         result.root_node = node;
         Ok(result)
     }
 
-    pub fn get_dot_graph(&self) -> String {
+    pub fn nodes(&self) -> &Vec<LQPNode> {
+        &self.nodes
+    }
+
+    pub fn root_node(&self) -> usize {
+        self.root_node
+    }
+
+    pub fn expressions(&self) -> &Vec<LQPExpression> {
+        &self.expressions
+    }
+
+    // cardinalities are the estimated output rows per node (see CardinalityEstimator) and methods the physical operators
+    //  per node (see Planner), which are added to the node labels
+    pub fn get_dot_graph(&self, cardinalities: Option<&[f64]>, methods: Option<&[Option<NodeMethod>]>) -> String {
         let mut nodes = String::new();
         let mut edges = String::new();
-        self.create_dot_plan_nodes_and_edges(self.root_node, &mut nodes, &mut edges);
+        self.create_dot_plan_nodes_and_edges(self.root_node, cardinalities, methods, &mut nodes, &mut edges);
         format!("digraph logical_plan {{\n{}\n{}}}", nodes, edges)
     }
 
-    pub fn create_dot_plan_nodes_and_edges(&self, id: usize, nodes: &mut String, edges: &mut String) {
+    pub fn create_dot_plan_nodes_and_edges(&self, id: usize, cardinalities: Option<&[f64]>, methods: Option<&[Option<NodeMethod>]>, nodes: &mut String, edges: &mut String) {
         let node = &self.nodes[id];
-        nodes.push_str(&node.get_dot_node(id, &self.expressions));
+        let method = methods.and_then(|methods| methods[id].as_ref());
+        nodes.push_str(&node.get_dot_node(id, self, cardinalities.map(|cardinalities| cardinalities[id]), method));
         if let Some(left) = node.inputs[0] {
             edges.push_str(&format!("plannode_{}->plannode_{}\n", id, left));
-            self.create_dot_plan_nodes_and_edges(left, nodes, edges);
+            self.create_dot_plan_nodes_and_edges(left, cardinalities, methods, nodes, edges);
         }
         if let Some(right) = node.inputs[1] {
             edges.push_str(&format!("plannode_{}->plannode_{}\n", id, right));
-            self.create_dot_plan_nodes_and_edges(right, nodes, edges);
+            self.create_dot_plan_nodes_and_edges(right, cardinalities, methods, nodes, edges);
         }
     }
 
@@ -181,11 +961,227 @@ impl LQP {
     pub fn set_output(&mut self, node_id: usize, output_node_id: usize) {
         self.nodes[node_id].output = Some(output_node_id)
     }
+
+    fn add_typed_expression(&mut self, data: LQPExpressionData, arguments: Vec<usize>, data_type: Option<DataType>, nullable: bool) -> usize {
+        self.add_expression(LQPExpression { arguments, data, data_type, nullable })
+    }
+
+    // column of a table node, which has the type of the column of the table (and is nullable if the table is on the inner
+    //  side of an outer join)
+    fn add_column_expression(&mut self, node: usize, column_id: ColumnId, outer_joined: bool) -> usize {
+        let (data_type, nullable) = match &self.nodes[node].data {
+            LQPNodeData::Table { columns, .. } => (columns[column_id].data_type, columns[column_id].nullable || outer_joined),
+            _ => unreachable!("columns refer to table nodes")
+        };
+        self.add_typed_expression(LQPExpressionData::Column { node, column_id }, Vec::new(), Some(data_type), nullable)
+    }
+
+    // predicates are NULL if any of their arguments is
+    fn add_predicate(&mut self, condition: PredicateCondition, arguments: Vec<usize>) -> usize {
+        let nullable = arguments.iter().any(|argument| self.expressions[*argument].nullable);
+        self.add_typed_expression(LQPExpressionData::Predicate(condition), arguments, Some(DataType::Boolean), nullable)
+    }
+
+    // gives an expression of unknown type the given type, string literals are converted to values of the type
+    fn coerce(&mut self, expression: usize, data_type: DataType) -> Result<(), LQPError> {
+        let expression = &mut self.expressions[expression];
+        if expression.data_type.is_some() {
+            return Ok(())
+        }
+        if let LQPExpressionData::Value(Value::Text(text)) = &expression.data {
+            expression.data = LQPExpressionData::Value(data_type.parse(text).map_err(LQPError::Table)?);
+        }
+        expression.data_type = Some(data_type);
+        Ok(())
+    }
+
+    // operands of unknown type get the type of the first operand of known type (text if there is none)
+    fn coerce_operands(&mut self, operands: &[usize]) -> Result<(), LQPError> {
+        let data_type = operands.iter().find_map(|operand| self.expressions[*operand].data_type).unwrap_or(DataType::Text);
+        for operand in operands {
+            self.coerce(*operand, data_type)?;
+        }
+        Ok(())
+    }
+
+    fn check_comparable(&self, left: usize, right: usize, operator: &str) -> Result<(), LQPError> {
+        match (self.expressions[left].data_type, self.expressions[right].data_type) {
+            (Some(left_type), Some(right_type)) if is_comparable(left_type, right_type) => Ok(()),
+            (left_type, right_type) => Err(LQPError::OperatorDoesNotExist(format!("{} {} {}", type_name(left_type), operator, type_name(right_type))))
+        }
+    }
+
+    pub fn add_join(&mut self, mode: JoinMode, left: usize, right: usize, predicates: Vec<usize>) -> usize {
+        let join = self.add_node(LQPNode { output: None, inputs: [Some(left), Some(right)], expressions: predicates, data: LQPNodeData::Join(mode) });
+        self.set_output(left, join);
+        self.set_output(right, join);
+        join
+    }
+
+    pub fn add_filter(&mut self, input: Option<usize>, predicates: Vec<usize>) -> usize {
+        let filter = self.add_node(LQPNode { output: None, inputs: [input, None], expressions: predicates, data: LQPNodeData::Filter });
+        if let Some(input) = input {
+            self.set_output(input, filter);
+        }
+        filter
+    }
+
+    // whether the expressions compute the same (e.g., the same column referenced twice)
+    fn expressions_equal(&self, left: usize, right: usize) -> bool {
+        let (left, right) = (&self.expressions[left], &self.expressions[right]);
+        left.data == right.data && left.data_type == right.data_type && left.arguments.len() == right.arguments.len() &&
+            left.arguments.iter().zip(right.arguments.iter()).all(|(left, right)| self.expressions_equal(*left, *right))
+    }
+
+    fn contains_aggregate(&self, expression: usize) -> bool {
+        let expression = &self.expressions[expression];
+        matches!(expression.data, LQPExpressionData::Aggregate(_)) || expression.arguments.iter().any(|argument| self.contains_aggregate(*argument))
+    }
+
+    fn check_no_aggregates(&self, expression: usize, clause: &str) -> Result<(), LQPError> {
+        if self.contains_aggregate(expression) {
+            return Err(LQPError::GroupingError(format!("aggregate functions are not allowed in {}", clause)))
+        }
+        Ok(())
+    }
+
+    // adds the aggregates of an expression to the result, unless an equal one is in there already
+    fn collect_aggregates(&self, expression: usize, result: &mut Vec<usize>) {
+        if let LQPExpressionData::Aggregate(_) = self.expressions[expression].data {
+            if !result.iter().any(|aggregate| self.expressions_equal(*aggregate, expression)) {
+                result.push(expression);
+            }
+            return
+        }
+        for argument in self.expressions[expression].arguments.iter() {
+            self.collect_aggregates(*argument, result);
+        }
+    }
+
+    // expressions above an aggregate can only use its group-by expressions and aggregates, columns outside of them are invalid
+    fn check_grouped(&self, expression: usize, group_by: &[usize]) -> Result<(), LQPError> {
+        if group_by.iter().any(|group_by_expression| self.expressions_equal(*group_by_expression, expression)) {
+            return Ok(())
+        }
+        let expr = &self.expressions[expression];
+        match expr.data {
+            LQPExpressionData::Aggregate(_) => Ok(()),
+            LQPExpressionData::Column { .. } => Err(LQPError::GroupingError(
+                format!("column \"{}\" must appear in the GROUP BY clause or be used in an aggregate function", expr.get_dot_str(self)))),
+            _ => {
+                for argument in expr.arguments.iter() {
+                    self.check_grouped(*argument, group_by)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // table nodes whose columns an expression refers to
+    fn referenced_tables(&self, expression: usize, result: &mut Vec<usize>) {
+        let expression = &self.expressions[expression];
+        if let LQPExpressionData::Column { node, .. } = expression.data {
+            if !result.contains(&node) {
+                result.push(node);
+            }
+        }
+        for argument in expression.arguments.iter() {
+            self.referenced_tables(*argument, result);
+        }
+    }
+
+    // table nodes of the subtree of a node
+    fn subtree_tables(&self, node: usize, result: &mut Vec<usize>) {
+        if let LQPNodeData::Table { .. } = self.nodes[node].data {
+            result.push(node);
+        }
+        for input in self.nodes[node].inputs.iter().flatten() {
+            self.subtree_tables(*input, result);
+        }
+    }
+
+    // moves an equality of columns of both inputs of a cross or inner join into the join (which becomes an inner join)
+    //  the join is searched below the given node through cross and inner joins only, as predicates cannot be moved into
+    //  outer joins
+    //  returns whether the predicate was moved
+    fn push_down_join_predicate(&mut self, node: usize, predicate: usize) -> bool {
+        let expression = &self.expressions[predicate];
+        if !matches!(expression.data, LQPExpressionData::Predicate(PredicateCondition::Equals)) {
+            return false
+        }
+        let (mut left_tables, mut right_tables) = (Vec::new(), Vec::new());
+        self.referenced_tables(expression.arguments[0], &mut left_tables);
+        self.referenced_tables(expression.arguments[1], &mut right_tables);
+        if left_tables.is_empty() || right_tables.is_empty() {
+            return false
+        }
+        let mut node = node;
+        loop {
+            let (left, right) = match self.nodes[node] {
+                LQPNode { data: LQPNodeData::Join(JoinMode::Cross), inputs: [Some(left), Some(right)], .. } |
+                LQPNode { data: LQPNodeData::Join(JoinMode::Inner), inputs: [Some(left), Some(right)], .. } => (left, right),
+                _ => return false
+            };
+            let (mut left_input_tables, mut right_input_tables) = (Vec::new(), Vec::new());
+            self.subtree_tables(left, &mut left_input_tables);
+            self.subtree_tables(right, &mut right_input_tables);
+            let all_in = |tables: &Vec<usize>, input_tables: &Vec<usize>| tables.iter().all(|table| input_tables.contains(table));
+            if (all_in(&left_tables, &left_input_tables) && all_in(&right_tables, &right_input_tables)) ||
+                (all_in(&left_tables, &right_input_tables) && all_in(&right_tables, &left_input_tables)) {
+                self.nodes[node].data = LQPNodeData::Join(JoinMode::Inner);
+                self.nodes[node].expressions.push(predicate);
+                return true
+            }
+            let (all_left, all_right) = (all_in(&left_tables, &left_input_tables) && all_in(&right_tables, &left_input_tables),
+                all_in(&left_tables, &right_input_tables) && all_in(&right_tables, &right_input_tables));
+            node = match (all_left, all_right) {
+                (true, _) => left,
+                (_, true) => right,
+                _ => return false
+            };
+        }
+    }
+
+    // adds the conjuncts of a predicate to the result (a AND (b AND c) is split into a, b and c)
+    fn split_conjunction(&self, predicate: usize, result: &mut Vec<usize>) {
+        let expression = &self.expressions[predicate];
+        match expression.data {
+            LQPExpressionData::Logical(LogicalOperator::And) => {
+                for argument in expression.arguments.iter() {
+                    self.split_conjunction(*argument, result);
+                }
+            },
+            _ => result.push(predicate)
+        }
+    }
+
+    // operands of logical operators and conditions have to be boolean (NULL and other expressions of unknown type are
+    //  converted)
+    fn check_boolean(&mut self, expression: usize, operator: &str) -> Result<(), LQPError> {
+        self.coerce(expression, DataType::Boolean)?;
+        match self.expressions[expression].data_type {
+            Some(DataType::Boolean) => Ok(()),
+            data_type => Err(LQPError::DatatypeMismatch(format!("argument of {} must be type boolean, not type {}", operator, type_name(data_type))))
+        }
+    }
+}
+
+// characters with a special meaning in labels of record nodes
+fn escape_dot_label(label: &str) -> String {
+    let mut result = String::with_capacity(label.len());
+    for c in label.chars() {
+        if matches!(c, '"' | '\\' | '{' | '}' | '|' | '<' | '>') {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result
 }
 
 impl LQPNode {
-    pub fn get_dot_node(&self, id: usize, expressions: &Vec<LQPExpression>) -> String {
+    pub fn get_dot_node(&self, id: usize, lqp: &LQP, cardinality: Option<f64>, method: Option<&NodeMethod>) -> String {
         let label = match &self.data {
+            LQPNodeData::Table { table_name, alias: Some(alias), .. } => format!("Table [{} {}]", table_name, alias),
             LQPNodeData::Table { table_name, .. } => format!("Table [{}]", table_name),
             _ => format!("{:?}", self.data)
         };
@@ -194,7 +1190,7 @@ impl LQPNode {
         } else {
             let mut result = "|".to_owned();
             for (i, expr) in self.expressions.iter().enumerate() {
-                let expr_str = expressions[*expr].get_dot_str(&expressions);
+                let expr_str = escape_dot_label(&lqp.expressions[*expr].get_dot_str(lqp));
                 if i == 0 {
                     result = format!("{}{}", result, expr_str);
                 } else {
@@ -203,17 +1199,25 @@ impl LQPNode {
             }
             result
         };
-        format!("plannode_{}[label=\"{{{}{}}}\", style=\"rounded\", shape=record];\n", id, label, expressions)
+        let cardinality = match cardinality {
+            Some(cardinality) => format!("|~{:.0} rows", cardinality),
+            None => String::new()
+        };
+        let method = match method {
+            Some(method) => format!("|{}", method),
+            None => String::new()
+        };
+        format!("plannode_{}[label=\"{{{}{}{}{}}}\", style=\"rounded\", shape=record];\n", id, label, expressions, cardinality, method)
     }
 
-    pub fn from(sql_statement: &Statement, lqp: &mut LQP) -> Result<usize, LQPError> {
+    pub fn from(sql_statement: &Statement, lqp: &mut LQP, catalog: &Catalog) -> Result<usize, LQPError> {
         match sql_statement {
-            Statement::Query(query) => Ok(LQPNode::from_query(query, lqp)?),
+            Statement::Query(query) => Ok(LQPNode::from_query(query, lqp, catalog)?),
             _ => Err(LQPError::Generic)
         }
     }
 
-    pub fn from_query(query: &Query, lqp: &mut LQP) -> Result<usize, LQPError> {
+    pub fn from_query(query: &Query, lqp: &mut LQP, catalog: &Catalog) -> Result<usize, LQPError> {
         if let Some(_) = query.with {
             return Err(LQPError::NotSupported("WITH"))
         }
@@ -230,13 +1234,13 @@ impl LQPNode {
             return Err(LQPError::NotSupported("FETCH"))
         }
         if let SetExpr::Select(select) = &query.body {
-            LQPNode::from_select(&select, lqp)
+            LQPNode::from_select(&select, lqp, catalog)
         } else {
             Err(LQPError::NotSupported("SetExpr!=SELECT"))
         }
     }
 
-    pub fn from_select(select: &Select, lqp: &mut LQP) -> Result<usize, LQPError> {
+    pub fn from_select(select: &Select, lqp: &mut LQP, catalog: &Catalog) -> Result<usize, LQPError> {
         if select.distinct {
             return Err(LQPError::NotSupported("DISTINCT"))
         }
@@ -256,47 +1260,94 @@ impl LQPNode {
             return Err(LQPError::NotSupported("SORT BY"))
         }
 
-        let mut from = LQPNode::from_from(&select.from, lqp)?;
-        if let Some(_selection) = &select.selection {
-            // TODO: filter expressions
-            let new_from = lqp.add_node(LQPNode { output: None, inputs: [from, None], expressions: Vec::new(), data: LQPNodeData::Filter });
+        let (mut from, scope) = LQPNode::from_from(&select.from, lqp, catalog)?;
+        if let Some(selection) = &select.selection {
+            let predicate = LQPExpression::from_expr(selection, lqp, &scope)?;
+            lqp.check_no_aggregates(predicate, "WHERE")?;
+            lqp.check_boolean(predicate, "WHERE")?;
+            let mut predicates = Vec::new();
+            lqp.split_conjunction(predicate, &mut predicates);
+            // equalities of tables combined with commas (or CROSS JOIN) become join predicates
             if let Some(from) = from {
-                lqp.set_output(from, new_from);
+                predicates.retain(|predicate| !lqp.push_down_join_predicate(from, *predicate));
+            }
+            if !predicates.is_empty() {
+                from = Some(lqp.add_filter(from, predicates));
             }
-            from = Some(new_from);
         }
-        // TODO: group by
-        // TODO: having
         let mut projection_expressions = Vec::new();
+        // the aliases of the select items, which GROUP BY may refer to
+        let mut aliases = Vec::new();
         for expression in &select.projection {
             match expression {
-                SelectItem::UnnamedExpr(expr) => {
-                    match expr {
-                        Expr::Identifier(ident) => { // in this context, a column or session information (see https://www.postgresql.org/docs/9.1/functions-info.html)
-                            match ident.value.as_str() {
-                                "session_user" => {
-                                    projection_expressions.push(lqp.add_expression(LQPExpression { arguments: Vec::new(), data: LQPExpressionData::Function(FunctionType::SessionUser) }))
-                                },
-                                _ => return Err(LQPError::NotSupported("Column expressions are not yet supported"))
-                            }
-                        },
-                        Expr::Function(func) => {
-                            if func.name.0.len() > 1 {
-                                return Err(LQPError::NotSupported("Multipart function names are not supported"))
-                            } else {
-                                match func.name.0[0].value.as_str() {
-                                    "current_schema" => {
-                                        projection_expressions.push(lqp.add_expression(LQPExpression { arguments: Vec::new(), data: LQPExpressionData::Function(FunctionType::CurrentSchema) }))
-                                    },
-                                    _ => return Err(LQPError::NotSupported("Unsupported function name"))
-                                }
-                            }
-                        },
-                        _ => return Err(LQPError::NotSupported("Unsupported expression type"))
+                SelectItem::UnnamedExpr(expr) => projection_expressions.push(LQPExpression::from_expr(expr, lqp, &scope)?),
+                SelectItem::ExprWithAlias { expr, alias } => {
+                    let expression = LQPExpression::from_expr(expr, lqp, &scope)?;
+                    projection_expressions.push(expression);
+                    aliases.push((normalize_identifier(alias), expression));
+                },
+                SelectItem::Wildcard => projection_expressions.extend(LQPExpression::from_wildcard(None, lqp, &scope)?),
+                SelectItem::QualifiedWildcard(name) => projection_expressions.extend(LQPExpression::from_wildcard(Some(name), lqp, &scope)?)
+            }
+        }
+        let mut group_by = Vec::new();
+        for expr in &select.group_by {
+            let expression = match expr {
+                // position of an output column, like GROUP BY 1
+                Expr::Value(sqlparser::ast::Value::Number(number, _)) if number.parse::<usize>().is_ok() => {
+                    match number.parse::<usize>().unwrap().checked_sub(1).and_then(|i| projection_expressions.get(i)) {
+                        Some(expression) => *expression,
+                        None => return Err(LQPError::InvalidColumnReference(format!("GROUP BY position {} is not in select list", number)))
                     }
                 },
-                // TODO: support aliased expressions and wildcards
-                _ => return Err(LQPError::NotSupported("SelectItem != UnnamedExpr"))
+                // like in PostgreSQL, a name refers to an alias only if there is no column of that name
+                Expr::Identifier(ident) => match LQPExpression::from_expr(expr, lqp, &scope) {
+                    Err(LQPError::UnknownColumn(name)) => {
+                        let mut matching = aliases.iter().filter(|(alias, _)| *alias == name).map(|(_, expression)| *expression);
+                        match matching.next() {
+                            Some(expression) if matching.all(|other| lqp.expressions_equal(other, expression)) => expression,
+                            Some(_) => return Err(LQPError::AmbiguousColumn(normalize_identifier(ident))),
+                            None => return Err(LQPError::UnknownColumn(name))
+                        }
+                    },
+                    result => result?
+                },
+                expr => LQPExpression::from_expr(expr, lqp, &scope)?
+            };
+            lqp.check_no_aggregates(expression, "GROUP BY")?;
+            if !group_by.iter().any(|group_by_expression| lqp.expressions_equal(*group_by_expression, expression)) {
+                group_by.push(expression);
+            }
+        }
+        let having = match &select.having {
+            Some(having) => {
+                let predicate = LQPExpression::from_expr(having, lqp, &scope)?;
+                lqp.check_boolean(predicate, "HAVING")?;
+                Some(predicate)
+            },
+            None => None
+        };
+        // the select items and HAVING are computed from the rows of the aggregate if there is one (i.e., with GROUP BY,
+        //  aggregates or HAVING)
+        let mut aggregates = Vec::new();
+        for expression in projection_expressions.iter().chain(having.iter()) {
+            lqp.collect_aggregates(*expression, &mut aggregates);
+        }
+        if !group_by.is_empty() || !aggregates.is_empty() || having.is_some() {
+            for expression in projection_expressions.iter().chain(having.iter()) {
+                lqp.check_grouped(*expression, &group_by)?;
+            }
+            let mut expressions = group_by;
+            expressions.extend(aggregates);
+            let aggregate = lqp.add_node(LQPNode { output: None, inputs: [from, None], expressions, data: LQPNodeData::Aggregate });
+            if let Some(from) = from {
+                lqp.set_output(from, aggregate);
+            }
+            from = Some(aggregate);
+            if let Some(having) = having {
+                let mut predicates = Vec::new();
+                lqp.split_conjunction(having, &mut predicates);
+                from = Some(lqp.add_filter(from, predicates));
             }
         }
         let projection = lqp.add_node(LQPNode { output: None, inputs: [from, None], expressions: projection_expressions, data: LQPNodeData::Projection });
@@ -306,31 +1357,417 @@ impl LQPNode {
         return Ok(projection);
     }
 
-    pub fn from_from(from: &Vec<TableWithJoins>, lqp: &mut LQP) -> Result<Option<usize>, LQPError> {
+    // returns the node producing the rows of the FROM clause (None without FROM) and its tables
+    pub fn from_from(from: &Vec<TableWithJoins>, lqp: &mut LQP, catalog: &Catalog) -> Result<(Option<usize>, Scope), LQPError> {
         let mut node = None;
+        let mut scope = Scope::default();
+        let mut from_tables = Vec::new();
         for twj in from.iter() {
-            if twj.joins.len() > 0 {
-                // TODO: support joins
-                return Err(LQPError::NotSupported("JOIN"))
+            // the conditions of joins can only refer to the tables of the same item of the FROM clause
+            let (twj_node, twj_scope) = LQPNode::from_table_with_joins(twj, lqp, catalog, &mut from_tables)?;
+            scope.extend(twj_scope);
+            node = match node {
+                // cross product with other tables in the from clause
+                Some(prev_node) => Some(lqp.add_join(JoinMode::Cross, prev_node, twj_node, Vec::new())),
+                None => Some(twj_node)
+            };
+        }
+        Ok((node, scope))
+    }
+
+    // a table and the tables joined to it, returns the node producing the joined rows and the tables
+    //  from_tables are all tables of the FROM clause so far, which need to have different names
+    fn from_table_with_joins(twj: &TableWithJoins, lqp: &mut LQP, catalog: &Catalog, from_tables: &mut Vec<usize>) -> Result<(usize, Scope), LQPError> {
+        let (mut node, mut scope) = LQPNode::from_table_factor(&twj.relation, lqp, catalog, from_tables)?;
+        for join in twj.joins.iter() {
+            let (right_node, right_scope) = LQPNode::from_table_factor(&join.relation, lqp, catalog, from_tables)?;
+            let (mode, constraint) = match &join.join_operator {
+                JoinOperator::Inner(constraint) => (JoinMode::Inner, constraint),
+                JoinOperator::LeftOuter(constraint) => (JoinMode::Left, constraint),
+                JoinOperator::RightOuter(constraint) => (JoinMode::Right, constraint),
+                JoinOperator::FullOuter(constraint) => (JoinMode::FullOuter, constraint),
+                JoinOperator::CrossJoin => (JoinMode::Cross, &JoinConstraint::None),
+                _ => return Err(LQPError::NotSupported("CROSS APPLY and OUTER APPLY"))
+            };
+            let using_columns = match constraint {
+                JoinConstraint::Using(columns) => columns.iter().map(normalize_identifier).collect(),
+                // the columns with the same name in both inputs
+                JoinConstraint::Natural => {
+                    let left_names = scope.column_names(lqp);
+                    right_scope.column_names(lqp).into_iter().filter(|name| left_names.contains(name)).collect()
+                },
+                JoinConstraint::None if !matches!(mode, JoinMode::Cross) => return Err(LQPError::ASTError("JOIN without ON, USING or NATURAL")),
+                _ => Vec::new()
+            };
+            let mut predicates = Vec::new();
+            let mut merged_columns = Vec::new();
+            for name in using_columns {
+                let (left, left_columns) = LQPNode::resolve_using_column(&name, lqp, &scope, "left")?;
+                let (right, right_columns) = LQPNode::resolve_using_column(&name, lqp, &right_scope, "right")?;
+                lqp.check_comparable(left, right, "=")?;
+                predicates.push(lqp.add_predicate(PredicateCondition::Equals, vec![left, right]));
+                // the merged column is the one of the outer side, for full outer joins the one that is not NULL
+                let expression = match mode {
+                    JoinMode::Right => right,
+                    JoinMode::FullOuter => {
+                        let (left_type, right_type) = (lqp.expressions[left].data_type.unwrap(), lqp.expressions[right].data_type.unwrap());
+                        let data_type = common_type(left_type, right_type).ok_or_else(|| LQPError::DatatypeMismatch(format!("JOIN/USING types {} and {} cannot be matched", left_type, right_type)))?;
+                        let condition = lqp.add_typed_expression(LQPExpressionData::Predicate(PredicateCondition::IsNotNull), vec![left], Some(DataType::Boolean), false);
+                        lqp.add_typed_expression(LQPExpressionData::Case, vec![condition, left, right], Some(data_type), true)
+                    },
+                    _ => left
+                };
+                merged_columns.push(MergedColumn { name, expression, columns: left_columns.into_iter().chain(right_columns).collect() });
+            }
+            // the merged columns replace the ones of the inputs
+            let mut right_scope = right_scope;
+            for scope in [&mut scope, &mut right_scope] {
+                scope.merged_columns.retain(|merged| !merged_columns.iter().any(|new| new.name == merged.name));
+            }
+            let (left_tables, right_tables) = (scope.tables.clone(), right_scope.tables.clone());
+            scope.extend(right_scope);
+            scope.merged_columns.extend(merged_columns);
+            if let JoinConstraint::On(condition) = constraint {
+                let condition = LQPExpression::from_expr(condition, lqp, &scope)?;
+                lqp.check_no_aggregates(condition, "JOIN conditions")?;
+                lqp.check_boolean(condition, "JOIN/ON")?;
+                lqp.split_conjunction(condition, &mut predicates);
             }
+            // rows of the outer side(s) without match are joined with NULLs
+            match mode {
+                JoinMode::Left => scope.nullable_tables.extend(right_tables),
+                JoinMode::Right => scope.nullable_tables.extend(left_tables),
+                JoinMode::FullOuter => scope.nullable_tables.extend(left_tables.into_iter().chain(right_tables)),
+                JoinMode::Inner | JoinMode::Cross => {}
+            }
+            node = lqp.add_join(mode, node, right_node, predicates);
+        }
+        Ok((node, scope))
+    }
+
+    // the column of one side of a USING or NATURAL join and the columns of the tables it refers to
+    fn resolve_using_column(name: &str, lqp: &mut LQP, scope: &Scope, side: &'static str) -> Result<(usize, Vec<(usize, ColumnId)>), LQPError> {
+        let expression = match LQPExpression::resolve_column_name(&[name.to_owned()], lqp, scope) {
+            Ok(expression) => expression,
+            Err(LQPError::UnknownColumn(_)) => return Err(LQPError::UnknownJoinColumn(name.to_owned(), side)),
+            Err(err) => return Err(err)
+        };
+        let columns = match (&lqp.expressions[expression].data, scope.merged_columns.iter().find(|merged| merged.name == name)) {
+            (_, Some(merged)) => merged.columns.clone(),
+            (LQPExpressionData::Column { node, column_id }, None) => vec![(*node, *column_id)],
+            _ => Vec::new()
+        };
+        Ok((expression, columns))
+    }
 
-            match &twj.relation {
-                TableFactor::Table { name, .. } => {
-                    let prev_node = node;
-                    let table_node = lqp.add_node(LQPNode { output: None, inputs: [None, None], expressions: Vec::new(), data: LQPNodeData::Table { schema_name: None, table_name: name.0[0].value.clone() } });
-                    node = match prev_node {
-                        Some(prev_node) => {
-                            // cross product with other tables in the from clause
-                            Some(lqp.add_node(LQPNode { output: None, inputs: [Some(table_node), Some(prev_node)], expressions: Vec::new(), data: LQPNodeData::Join(JoinMode::Cross) }))
-                        },
-                        None => {
-                            Some(table_node)
+    fn from_table_factor(factor: &TableFactor, lqp: &mut LQP, catalog: &Catalog, from_tables: &mut Vec<usize>) -> Result<(usize, Scope), LQPError> {
+        match factor {
+            TableFactor::Table { name, alias, args, with_hints } => {
+                if !args.is_empty() || !with_hints.is_empty() {
+                    return Err(LQPError::NotSupported("Table functions and hints"))
+                }
+                let (schema_name, table_name) = match name.0.as_slice() {
+                    [table_name] => (None, normalize_identifier(table_name)),
+                    [schema_name, table_name] => (Some(normalize_identifier(schema_name)), normalize_identifier(table_name)),
+                    _ => return Err(LQPError::NotSupported("Table names with more than two parts"))
+                };
+                let alias = match alias {
+                    Some(TableAlias { columns, .. }) if !columns.is_empty() => return Err(LQPError::NotSupported("Column aliases")),
+                    Some(alias) => Some(normalize_identifier(&alias.name)),
+                    None => None
+                };
+                let schema_name = catalog.resolve_table_schema(schema_name.as_deref(), &table_name)?;
+                let columns = catalog.get_table(Some(&schema_name), &table_name)?.read().unwrap().column_definitions().clone();
+                // tables are referred to by their alias or, without one, by their name
+                let reference_name = alias.as_ref().unwrap_or(&table_name);
+                for other in from_tables.iter() {
+                    if let LQPNodeData::Table { table_name: other_name, alias: other_alias, .. } = &lqp.nodes[*other].data {
+                        if other_alias.as_ref().unwrap_or(other_name) == reference_name {
+                            return Err(LQPError::DuplicateAlias(reference_name.clone()))
                         }
                     }
-                },
-                _ => return Err(LQPError::NotSupported("TableFactor!=Table"))
-            }
+                }
+                let table_node = lqp.add_node(LQPNode { output: None, inputs: [None, None], expressions: Vec::new(), data: LQPNodeData::Table { schema_name, table_name, alias, columns } });
+                from_tables.push(table_node);
+                Ok((table_node, Scope { tables: vec![table_node], ..Scope::default() }))
+            },
+            // parenthesized joins
+            TableFactor::NestedJoin(twj) => LQPNode::from_table_with_joins(twj, lqp, catalog, from_tables),
+            TableFactor::Derived { .. } => Err(LQPError::NotSupported("Subqueries in FROM")),
+            TableFactor::TableFunction { .. } => Err(LQPError::NotSupported("Table functions"))
         }
-        Ok(node)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlparser::dialect::GenericDialect;
+    use sqlparser::parser::Parser;
+
+    use super::*;
+    use crate::core::Table;
+
+    fn catalog() -> Catalog {
+        let mut catalog = Catalog::new();
+        let tables = vec![
+            ("orders", vec![ColumnDefinition::new("o_orderkey", DataType::BigInt, false), ColumnDefinition::new("o_custkey", DataType::BigInt, false),
+                ColumnDefinition::new("o_totalprice", DataType::Decimal(15, 2), false), ColumnDefinition::new("o_orderdate", DataType::Date, false),
+                ColumnDefinition::new("o_comment", DataType::Varchar(Some(79)), true)]),
+            ("customer", vec![ColumnDefinition::new("c_custkey", DataType::BigInt, false), ColumnDefinition::new("c_name", DataType::Varchar(Some(25)), false),
+                ColumnDefinition::new("c_nationkey", DataType::BigInt, false)]),
+            ("nation", vec![ColumnDefinition::new("n_nationkey", DataType::BigInt, false), ColumnDefinition::new("n_name", DataType::Char(25), false)]),
+            // for USING and NATURAL joins
+            ("t1", vec![ColumnDefinition::new("id", DataType::BigInt, false), ColumnDefinition::new("name", DataType::Text, true)]),
+            ("t2", vec![ColumnDefinition::new("id", DataType::BigInt, false), ColumnDefinition::new("name", DataType::Varchar(Some(10)), true),
+                ColumnDefinition::new("value", DataType::Double, false)])
+        ];
+        for (name, columns) in tables {
+            catalog.create_table(None, name, Table::new(columns, 1000)).unwrap();
+        }
+        catalog
+    }
+
+    fn translate(catalog: &Catalog, sql: &str) -> Result<LQP, LQPError> {
+        let statement = Parser::parse_sql(&GenericDialect {}, sql).unwrap().pop().unwrap();
+        LQP::from(&statement, catalog)
+    }
+
+    // the SQLSTATE of the error of a query
+    fn error(catalog: &Catalog, sql: &str) -> &'static str {
+        translate(catalog, sql).unwrap_err().sqlstate()
+    }
+
+    // the expressions of a node as shown in the dot graph
+    fn expressions(lqp: &LQP, node: usize) -> Vec<String> {
+        lqp.nodes[node].expressions.iter().map(|expression| lqp.expressions[*expression].get_dot_str(lqp)).collect()
+    }
+
+    #[test]
+    fn column_references_are_resolved_against_the_from_clause() {
+        let catalog = catalog();
+        let lqp = translate(&catalog, "SELECT o_orderkey, orders.o_custkey, public.orders.o_totalprice, \"o_orderdate\" FROM orders").unwrap();
+        assert!(matches!(lqp.nodes[lqp.root_node].data, LQPNodeData::Projection));
+        assert_eq!(expressions(&lqp, lqp.root_node), vec!["orders.o_orderkey", "orders.o_custkey", "orders.o_totalprice", "orders.o_orderdate"]);
+        let table = lqp.nodes[lqp.root_node].inputs[0].unwrap();
+        assert!(matches!(&lqp.nodes[table].data, LQPNodeData::Table { schema_name, table_name, alias: None, .. } if schema_name == "public" && table_name == "orders"));
+        let lqp = translate(&catalog, "SELECT O.C_NAME, n_name FROM customer o, nation").unwrap();
+        assert_eq!(expressions(&lqp, lqp.root_node), vec!["o.c_name", "nation.n_name"]);
+        let lqp = translate(&catalog, "SELECT *, n.* FROM nation n, customer").unwrap();
+        assert_eq!(expressions(&lqp, lqp.root_node), vec!["n.n_nationkey", "n.n_name", "customer.c_custkey", "customer.c_name", "customer.c_nationkey", "n.n_nationkey", "n.n_name"]);
+    }
+
+    #[test]
+    fn invalid_column_references_are_errors() {
+        let catalog = catalog();
+        assert_eq!(error(&catalog, "SELECT o_unknown FROM orders"), "42703");
+        assert_eq!(error(&catalog, "SELECT \"O_ORDERKEY\" FROM orders"), "42703");
+        assert_eq!(error(&catalog, "SELECT n_name FROM orders"), "42703");
+        assert_eq!(error(&catalog, "SELECT n_nationkey FROM nation, nation n"), "42702");
+        // the alias hides the name of the table
+        assert_eq!(error(&catalog, "SELECT orders.o_orderkey FROM orders o"), "42P01");
+        assert_eq!(error(&catalog, "SELECT public.orders.o_orderkey FROM orders o"), "42P01");
+        assert_eq!(error(&catalog, "SELECT nation.n_name FROM orders"), "42P01");
+        assert_eq!(error(&catalog, "SELECT * FROM unknown"), "42P01");
+        assert_eq!(error(&catalog, "SELECT * FROM nation, nation"), "42712");
+        assert_eq!(error(&catalog, "SELECT * FROM nation x JOIN customer x ON n_nationkey = c_nationkey"), "42712");
+        assert_eq!(error(&catalog, "SELECT *"), "42601");
+    }
+
+    // the types of the select items of a query
+    fn types(catalog: &Catalog, sql: &str) -> Vec<Option<DataType>> {
+        let lqp = translate(catalog, sql).unwrap();
+        lqp.nodes[lqp.root_node].expressions.iter().map(|expression| lqp.expressions[*expression].data_type).collect()
+    }
+
+    #[test]
+    fn expression_types_are_inferred() {
+        let catalog = catalog();
+        let division_type = DataType::Decimal(MAX_DECIMAL_PRECISION, MIN_DIVISION_SCALE);
+        assert_eq!(types(&catalog, "SELECT o_orderkey + 1, o_orderkey / 2, o_totalprice * o_totalprice, o_totalprice / 3, o_totalprice - 1.5, o_orderkey * CAST(1 AS REAL) FROM orders"),
+            vec![Some(DataType::BigInt), Some(DataType::BigInt), Some(DataType::Decimal(MAX_DECIMAL_PRECISION, 4)), Some(division_type),
+                Some(DataType::Decimal(MAX_DECIMAL_PRECISION, 2)), Some(DataType::Double)]);
+        assert_eq!(types(&catalog, "SELECT o_orderdate + 1, o_orderdate - 1, o_orderdate - o_orderdate, EXTRACT(YEAR FROM o_orderdate) FROM orders"),
+            vec![Some(DataType::Date), Some(DataType::Date), Some(DataType::BigInt), Some(DataType::BigInt)]);
+        assert_eq!(types(&catalog, "SELECT o_orderkey = 1, o_comment LIKE '%x%', CAST(o_orderkey AS DECIMAL(10, 2)), CASE WHEN o_orderkey = 1 THEN 1 ELSE 2.5 END FROM orders"),
+            vec![Some(DataType::Boolean), Some(DataType::Boolean), Some(DataType::Decimal(10, 2)), Some(DataType::Decimal(MAX_DECIMAL_PRECISION, 1))]);
+        // without a type to take, NULL and string literals are text
+        assert_eq!(types(&catalog, "SELECT CASE WHEN o_orderkey = 1 THEN 'a' END, CASE WHEN o_orderkey = 1 THEN NULL END FROM orders"), vec![Some(DataType::Text), Some(DataType::Text)]);
+        let lqp = translate(&catalog, "SELECT o_orderkey, o_comment, o_orderkey + NULL, o_comment IS NULL FROM orders").unwrap();
+        let nullable: Vec<bool> = lqp.nodes[lqp.root_node].expressions.iter().map(|expression| lqp.expressions[*expression].nullable).collect();
+        assert_eq!(nullable, vec![false, true, true, false]);
+    }
+
+    #[test]
+    fn literals_are_coerced_to_the_type_of_the_other_operand() {
+        let catalog = catalog();
+        let lqp = translate(&catalog, "SELECT * FROM orders WHERE o_orderdate >= '1995-03-15' AND o_totalprice < '100.5' AND o_orderdate BETWEEN '1995-01-01' AND DATE '1996-01-01'").unwrap();
+        let filter = lqp.nodes[lqp.root_node].inputs[0].unwrap();
+        assert_eq!(expressions(&lqp, filter), vec!["(orders.o_orderdate >= DATE '1995-03-15')", "(orders.o_totalprice < 100.5)",
+            "(orders.o_orderdate BETWEEN DATE '1995-01-01' AND DATE '1996-01-01')"]);
+        let predicate = &lqp.expressions[lqp.nodes[filter].expressions[1]];
+        assert_eq!(lqp.expressions[predicate.arguments[1]].data_type, Some(DataType::Decimal(15, 2)));
+        assert_eq!(error(&catalog, "SELECT * FROM orders WHERE o_orderdate = 'yesterday'"), "22007");
+        assert_eq!(error(&catalog, "SELECT * FROM orders WHERE o_orderkey = 'one'"), "22P02");
+    }
+
+    #[test]
+    fn type_errors_are_errors() {
+        let catalog = catalog();
+        assert_eq!(error(&catalog, "SELECT o_orderdate * 2 FROM orders"), "42883");
+        assert_eq!(error(&catalog, "SELECT * FROM orders WHERE o_comment < 1"), "42883");
+        assert_eq!(error(&catalog, "SELECT * FROM orders WHERE o_orderkey"), "42804");
+        assert_eq!(error(&catalog, "SELECT * FROM orders WHERE o_orderkey = 1 AND o_custkey"), "42804");
+        assert_eq!(error(&catalog, "SELECT * FROM orders WHERE NOT o_comment"), "42804");
+        assert_eq!(error(&catalog, "SELECT CASE WHEN o_orderkey THEN 1 END FROM orders"), "42804");
+        assert_eq!(error(&catalog, "SELECT CASE WHEN o_orderkey = 1 THEN o_orderdate ELSE o_orderkey END FROM orders"), "42804");
+        assert_eq!(error(&catalog, "SELECT CAST(o_orderdate AS BIGINT) FROM orders"), "42846");
+    }
+
+    #[test]
+    fn where_clauses_are_split_into_conjuncts() {
+        let catalog = catalog();
+        let lqp = translate(&catalog, "SELECT o_orderkey FROM orders WHERE o_orderkey = 1 AND (o_custkey = 2 AND o_totalprice > 10) AND (o_orderkey = 3 OR o_comment LIKE '%x%')").unwrap();
+        let filter = lqp.nodes[lqp.root_node].inputs[0].unwrap();
+        assert!(matches!(lqp.nodes[filter].data, LQPNodeData::Filter));
+        assert_eq!(expressions(&lqp, filter), vec!["(orders.o_orderkey = 1)", "(orders.o_custkey = 2)", "(orders.o_totalprice > 10)",
+            "((orders.o_orderkey = 3) OR (orders.o_comment LIKE '%x%'))"]);
+        let table = lqp.nodes[filter].inputs[0].unwrap();
+        assert!(matches!(lqp.nodes[table].data, LQPNodeData::Table { .. }));
+        assert_eq!((lqp.nodes[table].output, lqp.nodes[filter].output), (Some(filter), Some(lqp.root_node)));
+        // without FROM, the filter has no input
+        let lqp = translate(&catalog, "SELECT 1 WHERE 1 = 1 AND NOT 1 = 2").unwrap();
+        let filter = lqp.nodes[lqp.root_node].inputs[0].unwrap();
+        assert_eq!(expressions(&lqp, filter), vec!["(1 = 1)", "NOT (1 = 2)"]);
+        assert_eq!(lqp.nodes[filter].inputs, [None, None]);
+        let lqp = translate(&catalog, "SELECT o_orderkey FROM orders").unwrap();
+        assert!(matches!(lqp.nodes[lqp.nodes[lqp.root_node].inputs[0].unwrap()].data, LQPNodeData::Table { .. }));
+    }
+
+    #[test]
+    fn where_equalities_become_join_predicates() {
+        let catalog = catalog();
+        let lqp = translate(&catalog, "SELECT c_name FROM orders, customer, nation WHERE c_nationkey = n_nationkey AND o_custkey = c_custkey AND o_totalprice > 10 AND o_orderkey = c_custkey + n_nationkey").unwrap();
+        let filter = lqp.nodes[lqp.root_node].inputs[0].unwrap();
+        // the equality of columns of all three tables is not a predicate of either join
+        assert_eq!(expressions(&lqp, filter), vec!["(orders.o_totalprice > 10)", "(orders.o_orderkey = (customer.c_custkey + nation.n_nationkey))"]);
+        let join = lqp.nodes[filter].inputs[0].unwrap();
+        assert!(matches!(lqp.nodes[join].data, LQPNodeData::Join(JoinMode::Inner)));
+        assert_eq!(expressions(&lqp, join), vec!["(customer.c_nationkey = nation.n_nationkey)"]);
+        let lower_join = lqp.nodes[join].inputs[0].unwrap();
+        assert!(matches!(lqp.nodes[lower_join].data, LQPNodeData::Join(JoinMode::Inner)));
+        assert_eq!(expressions(&lqp, lower_join), vec!["(orders.o_custkey = customer.c_custkey)"]);
+        // predicates are not moved into outer joins, but into the joins above them
+        let lqp = translate(&catalog, "SELECT * FROM orders LEFT JOIN customer ON o_custkey = c_custkey, nation WHERE c_nationkey = n_nationkey AND c_name = o_comment").unwrap();
+        let filter = lqp.nodes[lqp.root_node].inputs[0].unwrap();
+        assert_eq!(expressions(&lqp, filter), vec!["(customer.c_name = orders.o_comment)"]);
+        let join = lqp.nodes[filter].inputs[0].unwrap();
+        assert!(matches!(lqp.nodes[join].data, LQPNodeData::Join(JoinMode::Inner)));
+        assert_eq!(expressions(&lqp, join), vec!["(customer.c_nationkey = nation.n_nationkey)"]);
+        let lqp = translate(&catalog, "SELECT * FROM orders, customer WHERE o_custkey > c_custkey").unwrap();
+        let filter = lqp.nodes[lqp.root_node].inputs[0].unwrap();
+        assert!(matches!(lqp.nodes[lqp.nodes[filter].inputs[0].unwrap()].data, LQPNodeData::Join(JoinMode::Cross)));
+    }
+
+    #[test]
+    fn explicit_joins_are_translated() {
+        let catalog = catalog();
+        let lqp = translate(&catalog, "SELECT o_comment, c_name, n_name FROM orders RIGHT JOIN customer ON o_custkey = c_custkey AND o_totalprice > 10 FULL JOIN nation ON c_nationkey = n_nationkey").unwrap();
+        let nullable: Vec<bool> = lqp.nodes[lqp.root_node].expressions.iter().map(|expression| lqp.expressions[*expression].nullable).collect();
+        assert_eq!(nullable, vec![true, true, true]);
+        let join = lqp.nodes[lqp.root_node].inputs[0].unwrap();
+        assert!(matches!(lqp.nodes[join].data, LQPNodeData::Join(JoinMode::FullOuter)));
+        assert_eq!(expressions(&lqp, join), vec!["(customer.c_nationkey = nation.n_nationkey)"]);
+        let lower_join = lqp.nodes[join].inputs[0].unwrap();
+        assert!(matches!(lqp.nodes[lower_join].data, LQPNodeData::Join(JoinMode::Right)));
+        assert_eq!(expressions(&lqp, lower_join), vec!["(orders.o_custkey = customer.c_custkey)", "(orders.o_totalprice > 10)"]);
+        let lqp = translate(&catalog, "SELECT c_name FROM customer LEFT JOIN nation ON c_nationkey = n_nationkey CROSS JOIN orders").unwrap();
+        assert_eq!(lqp.nodes[lqp.root_node].expressions.iter().map(|expression| lqp.expressions[*expression].nullable).collect::<Vec<_>>(), vec![false]);
+        assert!(matches!(lqp.nodes[lqp.nodes[lqp.root_node].inputs[0].unwrap()].data, LQPNodeData::Join(JoinMode::Cross)));
+        // ON can only refer to the tables joined so far
+        assert_eq!(error(&catalog, "SELECT * FROM orders, customer JOIN nation ON o_custkey = n_nationkey"), "42703");
+        assert_eq!(error(&catalog, "SELECT * FROM orders JOIN customer ON o_custkey"), "42804");
+    }
+
+    #[test]
+    fn using_and_natural_joins_merge_the_joined_columns() {
+        let catalog = catalog();
+        let lqp = translate(&catalog, "SELECT id, t1.id, t2.id, value FROM t1 JOIN t2 USING (id)").unwrap();
+        assert_eq!(expressions(&lqp, lqp.root_node), vec!["t1.id", "t1.id", "t2.id", "t2.value"]);
+        let join = lqp.nodes[lqp.root_node].inputs[0].unwrap();
+        assert!(matches!(lqp.nodes[join].data, LQPNodeData::Join(JoinMode::Inner)));
+        assert_eq!(expressions(&lqp, join), vec!["(t1.id = t2.id)"]);
+        let lqp = translate(&catalog, "SELECT * FROM t1 JOIN t2 USING (id)").unwrap();
+        assert_eq!(expressions(&lqp, lqp.root_node), vec!["t1.id", "t1.name", "t2.name", "t2.value"]);
+        let lqp = translate(&catalog, "SELECT *, t2.* FROM t1 NATURAL JOIN t2").unwrap();
+        assert_eq!(expressions(&lqp, lqp.root_node), vec!["t1.id", "t1.name", "t2.value", "t2.id", "t2.name", "t2.value"]);
+        assert_eq!(expressions(&lqp, lqp.nodes[lqp.root_node].inputs[0].unwrap()), vec!["(t1.id = t2.id)", "(t1.name = t2.name)"]);
+        // the merged column is the one of the outer side, of a full join the one that is not NULL
+        let lqp = translate(&catalog, "SELECT id FROM t1 RIGHT JOIN t2 USING (id)").unwrap();
+        assert_eq!(expressions(&lqp, lqp.root_node), vec!["t2.id"]);
+        let lqp = translate(&catalog, "SELECT id, name FROM t1 NATURAL FULL JOIN t2").unwrap();
+        assert_eq!(expressions(&lqp, lqp.root_node), vec!["CASE WHEN (t1.id IS NOT NULL) THEN t1.id ELSE t2.id END",
+            "CASE WHEN (t1.name IS NOT NULL) THEN t1.name ELSE t2.name END"]);
+        let merged: Vec<(Option<DataType>, bool)> = lqp.nodes[lqp.root_node].expressions.iter().map(|expression| (lqp.expressions[*expression].data_type, lqp.expressions[*expression].nullable)).collect();
+        assert_eq!(merged, vec![(Some(DataType::BigInt), true), (Some(DataType::Text), true)]);
+        // later joins use the merged column
+        let lqp = translate(&catalog, "SELECT id FROM t1 JOIN t2 USING (id) JOIN t1 x USING (id)").unwrap();
+        assert_eq!(expressions(&lqp, lqp.nodes[lqp.root_node].inputs[0].unwrap()), vec!["(t1.id = x.id)"]);
+        assert_eq!(error(&catalog, "SELECT name FROM t1 JOIN t2 USING (id)"), "42702");
+        assert_eq!(error(&catalog, "SELECT * FROM t1 JOIN t2 USING (value)"), "42703");
+        assert_eq!(error(&catalog, "SELECT * FROM t1 JOIN nation USING (id)"), "42703");
+        assert_eq!(error(&catalog, "SELECT * FROM t1 JOIN t2 USING (id) JOIN t1 x ON id = x.id"), "42702");
+    }
+
+    #[test]
+    fn group_by_and_aggregates_add_an_aggregate_node() {
+        let catalog = catalog();
+        let lqp = translate(&catalog, "SELECT o_custkey, sum(o_totalprice), count(*) + 1 FROM orders WHERE o_orderkey > 1 GROUP BY o_custkey, orders.o_custkey HAVING count(*) > 1").unwrap();
+        assert_eq!(expressions(&lqp, lqp.root_node), vec!["orders.o_custkey", "SUM(orders.o_totalprice)", "(COUNT(*) + 1)"]);
+        let having = lqp.nodes[lqp.root_node].inputs[0].unwrap();
+        assert!(matches!(lqp.nodes[having].data, LQPNodeData::Filter));
+        assert_eq!(expressions(&lqp, having), vec!["(COUNT(*) > 1)"]);
+        let aggregate = lqp.nodes[having].inputs[0].unwrap();
+        assert!(matches!(lqp.nodes[aggregate].data, LQPNodeData::Aggregate));
+        assert_eq!(expressions(&lqp, aggregate), vec!["orders.o_custkey", "SUM(orders.o_totalprice)", "COUNT(*)"]);
+        assert_eq!(expressions(&lqp, lqp.nodes[aggregate].inputs[0].unwrap()), vec!["(orders.o_orderkey > 1)"]);
+        // without GROUP BY, there is one group
+        let lqp = translate(&catalog, "SELECT count(DISTINCT o_custkey), avg(o_orderkey), max(o_orderdate) FROM orders").unwrap();
+        let aggregate = lqp.nodes[lqp.root_node].inputs[0].unwrap();
+        assert_eq!(expressions(&lqp, aggregate), vec!["COUNT(DISTINCT orders.o_custkey)", "AVG(orders.o_orderkey)", "MAX(orders.o_orderdate)"]);
+        assert_eq!(types(&catalog, "SELECT count(DISTINCT o_custkey), avg(o_orderkey), max(o_orderdate), sum(o_totalprice) FROM orders"),
+            vec![Some(DataType::BigInt), Some(DataType::Decimal(MAX_DECIMAL_PRECISION, MIN_DIVISION_SCALE)), Some(DataType::Date), Some(DataType::Decimal(MAX_DECIMAL_PRECISION, 2))]);
+        // grouped expressions can be used in other expressions
+        let lqp = translate(&catalog, "SELECT o_orderkey + 1, (o_orderkey + 1) * 2 FROM orders GROUP BY 1").unwrap();
+        assert_eq!(expressions(&lqp, lqp.nodes[lqp.root_node].inputs[0].unwrap()), vec!["(orders.o_orderkey + 1)"]);
+    }
+
+    #[test]
+    fn invalid_grouping_is_an_error() {
+        let catalog = catalog();
+        assert_eq!(error(&catalog, "SELECT o_orderkey, count(*) FROM orders GROUP BY o_custkey"), "42803");
+        assert_eq!(error(&catalog, "SELECT o_orderkey, count(*) FROM orders"), "42803");
+        assert_eq!(error(&catalog, "SELECT o_orderkey FROM orders HAVING count(*) > 1"), "42803");
+        assert_eq!(error(&catalog, "SELECT o_custkey FROM orders GROUP BY o_custkey HAVING o_orderkey > 1"), "42803");
+        assert_eq!(error(&catalog, "SELECT * FROM orders WHERE sum(o_totalprice) > 10"), "42803");
+        assert_eq!(error(&catalog, "SELECT count(*) FROM orders GROUP BY sum(o_totalprice)"), "42803");
+        assert_eq!(error(&catalog, "SELECT * FROM orders JOIN customer ON count(*) > 1"), "42803");
+        assert_eq!(error(&catalog, "SELECT o_custkey FROM orders GROUP BY 2"), "42P10");
+        assert_eq!(error(&catalog, "SELECT o_custkey FROM orders GROUP BY o_custkey HAVING sum(o_totalprice)"), "42804");
+        assert_eq!(error(&catalog, "SELECT sum(o_orderdate) FROM orders"), "42883");
+    }
+
+    #[test]
+    fn group_by_refers_to_aliases_of_select_items() {
+        let catalog = catalog();
+        let lqp = translate(&catalog, "SELECT o_custkey AS key, o_orderkey % 10 AS \"Bucket\", count(*) AS orders FROM orders GROUP BY key, \"Bucket\"").unwrap();
+        assert_eq!(expressions(&lqp, lqp.root_node), vec!["orders.o_custkey", "(orders.o_orderkey % 10)", "COUNT(*)"]);
+        let aggregate = lqp.nodes[lqp.root_node].inputs[0].unwrap();
+        assert_eq!(expressions(&lqp, aggregate), vec!["orders.o_custkey", "(orders.o_orderkey % 10)", "COUNT(*)"]);
+        // like in PostgreSQL, columns take precedence over aliases
+        assert_eq!(error(&catalog, "SELECT o_orderkey AS o_custkey FROM orders GROUP BY o_custkey"), "42803");
+        assert_eq!(error(&catalog, "SELECT o_orderkey AS key, o_custkey AS key FROM orders GROUP BY key"), "42702");
+        assert!(translate(&catalog, "SELECT o_orderkey AS key, o_orderkey AS key FROM orders GROUP BY key").is_ok());
+        assert_eq!(error(&catalog, "SELECT count(*) AS n FROM orders GROUP BY n"), "42803");
+        assert_eq!(error(&catalog, "SELECT o_custkey AS key FROM orders GROUP BY bucket"), "42703");
+    }
+}
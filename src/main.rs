@@ -1,155 +1,99 @@
+mod analyze;
+mod cardinality;
+mod checkpoint;
+mod commands;
+mod copy;
 mod core;
+mod delimited;
 mod threadpool;
+mod loader;
 mod lqp;
+mod planner;
 mod ps_protocol;
 mod query;
+mod tpch;
 mod transaction;
+mod wal;
 
-use std::collections::HashMap;
+use std::env;
 use std::net::TcpListener;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::RwLock;
-use std::fmt::Debug;
-use std::fs::{self};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
 
-use crate::core::AttributeValueContainer;
-use crate::core::MainAttributeValueContainer;
+use crate::checkpoint::Checkpointer;
 use crate::ps_protocol::handle_connection;
 use crate::threadpool::ThreadPool;
-use crate::core::DynAttributeValueContainer;
-use crate::core::ValueId;
+use crate::core::{Catalog, Chunk, ColumnDefinition, DataType, Table, DEFAULT_CHUNK_SIZE};
 use crate::transaction::TransactionManager;
+use crate::wal::{SyncPolicy, Wal};
 
-enum RawColumn {
-    BigInt(Vec<i64>),
-    Date(Vec<i64>),
-    Double(Vec<f64>)
-}
+// minimum number of rows in the delta store of a chunk before it is merged into the main store
+const MERGE_THRESHOLD: usize = 4096;
 
-fn create_avc<T: 'static + Copy + PartialOrd + Sized + Send + Sync + Debug>(column: &Vec<T>) -> core::MainAttributeValueContainer<T> {
-    let mut column_with_indices: Vec<(usize, T)> = column.iter().enumerate().map(|(i, val)| (i, *val)).collect();
-    column_with_indices.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-    let mut dict = Vec::new();
-    let mut current: Option<T> = None;
-    let mut dv: Vec<ValueId> = vec![0; column_with_indices.len()];
-    for (i, val) in column_with_indices.iter() {
-        if current == Some(*val) {
-            dv[*i] = (dict.len() - 1) as ValueId;
-        } else {
-            dict.push(*val);
-            current = Some(*val);
-            dv[*i] = (dict.len() - 1) as ValueId;
-        }
-    }
-    return core::MainAttributeValueContainer::<T> { data: dv, dict: Box::new(core::FixedSizeDict { entries: dict }) };
-}
+// directory of the TPC-H data
+const TPCH_DIR: &str = "tpc-h/sf1";
+// write-ahead log, replayed on top of the loaded tables on startup
+const WAL_PATH: &str = "rdbms.wal";
+// the default sync policy, RDBMS_WAL_SYNC=commit|periodic|never selects another one
+const WAL_SYNC_POLICY: SyncPolicy = SyncPolicy::Commit;
+const WAL_SYNC_INTERVAL: Duration = Duration::from_secs(1);
+// directory of the checkpoints, the latest of which replaces the TPC-H data once it exists
+const DATA_DIR: &str = "data";
+// a checkpoint is made once the log reaches this size, or after this interval if anything was logged
+const CHECKPOINT_WAL_SIZE: u64 = 64 * 1024 * 1024;
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(300);
 
 fn main() {
-    // load TPC-H data
-    //  NOTE: for now, only importing integer columns from LINEITEM as a first step
-    let mut columns: HashMap<String, RawColumn> = HashMap::new();
-    columns.insert(String::from("L_ORDERKEY"), RawColumn::BigInt(Vec::new()));
-    columns.insert(String::from("L_PARTKEY"), RawColumn::BigInt(Vec::new()));
-    columns.insert(String::from("L_SUPPKEY"), RawColumn::BigInt(Vec::new()));
-    columns.insert(String::from("L_LINENUMBER"), RawColumn::BigInt(Vec::new()));
-    columns.insert(String::from("L_QUANTITY"), RawColumn::Double(Vec::new()));
-    columns.insert(String::from("L_EXTENDEDPRICE"), RawColumn::Double(Vec::new()));
-    columns.insert(String::from("L_DISCOUNT"), RawColumn::Double(Vec::new()));
-    columns.insert(String::from("L_TAX"), RawColumn::Double(Vec::new()));
-    for f in fs::read_dir("tpc-h/sf1").unwrap() {
-        let f = f.unwrap();
-        let path = f.path();
-        if path.is_file() {
-            if path.extension().unwrap().to_str().unwrap() == "tbl" {
-                let tbl_name = path.file_stem().unwrap().to_str().unwrap();
-                println!("{}", tbl_name);
-                if tbl_name == "lineitem" {
-                    for (i, line) in fs::read_to_string(path).unwrap().lines().enumerate() {
-                        for (j, value) in line.trim_matches('|').split('|').enumerate() {
-                            let col = match j {
-                                0 => Some("L_ORDERKEY"),
-                                1 => Some("L_PARTKEY"),
-                                2 => Some("L_SUPPKEY"),
-                                3 => Some("L_LINENUMBER"),
-                                4 => Some("L_QUANTITY"),
-                                5 => Some("L_EXTENDEDPRICE"),
-                                6 => Some("L_DISCOUNT"),
-                                7 => Some("L_TAX"),
-                                _ => None
-                            };
-
-                            if let Some(col_name) = col {
-                                let col = columns.get_mut(col_name).unwrap();
-                                match col {
-                                    RawColumn::BigInt(vec) => {
-                                        vec.push(i64::from_str_radix(value, 10).unwrap());
-                                    },
-                                    RawColumn::Date(vec) => {
-                                        // TODO
-                                    },
-                                    RawColumn::Double(vec) => {
-                                        let parsed = value.parse::<f64>().unwrap();
-                                        vec.push(parsed)
-                                    }
-                                }
-                            }
-                        }
-                        if i % 1000000 == 0 {
-                            println!("{}", i);
-                        }
-                    }
-                }
-            }
-        }
+    // `rdbms convert [directory]` converts the .tbl files into column files once, which are mapped on later starts
+    let args: Vec<String> = env::args().collect();
+    // one pool for parsing and encoding loaded rows, used at startup and by COPY FROM
+    let loader_pool = ThreadPool::new(thread::available_parallelism().map_or(1, |count| count.get()));
+    if args.get(1).map(String::as_str) == Some("convert") {
+        tpch::convert_tpch(&loader_pool, Path::new(args.get(2).map_or(TPCH_DIR, String::as_str)));
+        return
     }
 
-    // domain encoding of columns
-    let mut avcs: HashMap<String, Box<dyn core::DynAttributeValueContainer>> = HashMap::new();
-    for (name, column) in columns.iter() {
-        match column {
-            RawColumn::BigInt(vec) => { avcs.insert(name.to_string(), Box::new(create_avc(vec))); }
-            RawColumn::Date(vec) => { avcs.insert(name.to_string(), Box::new(create_avc(vec))); }
-            RawColumn::Double(vec) => { avcs.insert(name.to_string(), Box::new(create_avc(vec))); }
-        }
-    }
-    for (name, _) in &avcs {
-        print!("{:>18}", name);
-    }
-    print!("\n");
-    for i in 0..10 {
-        for (name, avc) in &avcs {
-            match columns.get(name).unwrap() {
-                RawColumn::BigInt(_) => { print!("{:>18}", (avc.as_any().downcast_ref::<MainAttributeValueContainer<i64>>()).unwrap().lookup(i).unwrap()); },
-                RawColumn::Double(_) => { print!("{:>18}", (avc.as_any().downcast_ref::<MainAttributeValueContainer<f64>>()).unwrap().lookup(i).unwrap()); }
-                _ => {}
-            }
+    let mut catalog = Catalog::new();
+    let checkpoint = checkpoint::load_checkpoint(Path::new(DATA_DIR), &mut catalog).expect("could not load the last checkpoint");
+    match checkpoint {
+        Some(id) => println!("Loaded checkpoint {}", id),
+        None => {
+            // load TPC-H data
+            tpch::load_tpch(&loader_pool, Path::new(TPCH_DIR), &mut catalog);
+
+            // value id 4 is the NULL value id of the dictionary
+            let dict = Box::new(core::FixedSizeDict { entries: vec![1, 5, 7, 2311].into() });
+            let avc = core::MainAttributeValueContainer::<i64>::from_value_ids(&[1, 2, 0, 0, 4, 1, 3, 1, 0, 4, 1], dict);
+            let mut test_table = Table::new(vec![ColumnDefinition::new("val", DataType::BigInt, true)], DEFAULT_CHUNK_SIZE);
+            test_table.append_chunk(Chunk::new(vec![Box::new(avc)]).unwrap()).unwrap();
+            catalog.create_table(None, "test", test_table).unwrap();
         }
-        print!("\n");
     }
+    let checkpoint = checkpoint.unwrap_or(0);
 
-    let dict = Box::new(core::FixedSizeDict { entries: vec![1, 5, 7, 2311] });
-    let mut avc = core::MainAttributeValueContainer::<i64> { data: Vec::new(), dict: dict };
-    avc.data.push(1);
-    avc.data.push(2);
-    avc.data.push(0);
-    avc.data.push(0);
-    avc.data.push(avc.null_value_id() as u32);
-    avc.data.push(1);
-    avc.data.push(3);
-    avc.data.push(1);
-    avc.data.push(0);
-    avc.data.push(avc.null_value_id() as u32);
-    avc.data.push(1);
-    let db = Arc::new(RwLock::new(core::Database { transaction_manager: Mutex::new(TransactionManager {}), avc: RwLock::new(Box::new(avc)) }));
-    // avc lookup test
-    //{
-    //    let db = db.read().unwrap();
-    //    let avc = db.avc.read().unwrap();
-    //    for i in 0..avc.len() {
-    //        println!("{:?}", avc.lookup(i));
-    //    }
-    //}
+    // redo the changes committed since the tables were persisted
+    let (transactions, rows) = wal::recover(Path::new(WAL_PATH), &mut catalog, checkpoint).expect("could not recover from the write-ahead log");
+    println!("Replayed {} transactions ({} rows) from the write-ahead log", transactions, rows);
+    let sync_policy = match env::var("RDBMS_WAL_SYNC").as_deref() {
+        Ok("commit") => SyncPolicy::Commit,
+        Ok("periodic") => SyncPolicy::Periodic(WAL_SYNC_INTERVAL),
+        Ok("never") => SyncPolicy::Never,
+        Ok(policy) => panic!("unknown sync policy of the write-ahead log: {}", policy),
+        Err(_) => WAL_SYNC_POLICY
+    };
+    let wal = Wal::open(Path::new(WAL_PATH), sync_policy, checkpoint).unwrap();
+    let transaction_manager = TransactionManager::new(Some(wal.clone()));
+    let checkpointer = Checkpointer::new(Path::new(DATA_DIR), checkpoint, wal, transaction_manager.writer_gate());
+    let db = Arc::new(RwLock::new(core::Database {
+        transaction_manager: Mutex::new(transaction_manager),
+        catalog: RwLock::new(catalog),
+        checkpointer,
+        loader_pool
+    }));
 
     let pool = Arc::new(Mutex::new(ThreadPool::new(4)));
     let pool_clone = pool.clone();
@@ -167,5 +111,32 @@ fn main() {
     };
     pool.lock().unwrap().execute(ps_protocol_listener);
 
+    // background merge of delta stores into the main stores and automatic checkpoints
+    let db_clone = db.clone();
+    let delta_merger = move || {
+        loop {
+            thread::sleep(Duration::from_secs(1));
+            let db = db_clone.read().unwrap();
+            let tables = db.catalog.read().unwrap().all_tables();
+            for (schema_name, table_name, table) in tables {
+                let chunk_ids = table.read().unwrap().chunks_to_merge(MERGE_THRESHOLD);
+                for chunk_id in chunk_ids {
+                    println!("Merging delta of chunk {} of {}.{}", chunk_id, schema_name, table_name);
+                    if let Err(err) = core::merge_chunk(&table, chunk_id) {
+                        println!("Merge error: {}", err);
+                    }
+                }
+            }
+            if db.checkpointer.is_due(CHECKPOINT_WAL_SIZE, CHECKPOINT_INTERVAL) {
+                match db.checkpointer.checkpoint(&db.catalog) {
+                    Ok(stats) => println!("Checkpoint {}: {} tables ({} rows, {} merged chunks) in {:.2?}", stats.id, stats.tables, stats.rows, stats.merged_chunks, stats.duration),
+                    Err(err) => println!("Checkpoint error: {}", err)
+                }
+            }
+        }
+    };
+    // on a thread of its own, so that it does not take a worker from the client connections
+    thread::spawn(delta_merger);
+
     loop {}
 }
\ No newline at end of file
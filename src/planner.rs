@@ -0,0 +1,240 @@
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use crate::cardinality::{column_comparison, predicate_selectivity};
+use crate::core::{Catalog, CatalogError, ColumnId, ScanPredicate, Table, Value};
+use crate::lqp::{JoinMode, LQPExpressionData, LQPNode, LQPNodeData, PredicateCondition, LQP};
+
+// choice of the physical operators for the nodes of a logical query plan, based on the estimated cardinalities
+//  so far, the planner decides where indexes are used: for predicates on indexed columns that are selective enough and for
+//  the inner side of joins whose outer side is small compared to the inner table
+
+// an index is used for predicates whose estimated selectivity is at most this (above, reading the positions from the index
+//  and fetching the rows is slower than scanning the column)
+pub const INDEX_SCAN_SELECTIVITY: f64 = 0.01;
+// relative cost of an index lookup compared to hashing a row of the inner table
+pub const INDEX_LOOKUP_COST: f64 = 4.0;
+
+#[derive(Debug, PartialEq)]
+pub enum ScanMethod {
+    TableScan,
+    // id of the index within the table (see Table::indexes)
+    IndexScan(usize)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum JoinMethod {
+    Hash,
+    // id of the index on the join column of the inner table, which is probed with every row of the outer side
+    IndexNestedLoop(usize)
+}
+
+// physical operator of a node of a logical plan
+#[derive(Debug, PartialEq)]
+pub enum NodeMethod {
+    // filters on top of stored tables
+    Scan(ScanMethod),
+    Join(JoinMethod)
+}
+
+impl fmt::Display for NodeMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeMethod::Scan(ScanMethod::TableScan) => write!(f, "table scan"),
+            NodeMethod::Scan(ScanMethod::IndexScan(index_id)) => write!(f, "index scan (index {})", index_id),
+            NodeMethod::Join(JoinMethod::Hash) => write!(f, "hash join"),
+            NodeMethod::Join(JoinMethod::IndexNestedLoop(index_id)) => write!(f, "index nested loop join (index {})", index_id)
+        }
+    }
+}
+
+// how the rows of a table matching a predicate on one of its columns are found
+pub fn choose_scan_method(table: &Table, column_id: ColumnId, predicate: &ScanPredicate<Value>) -> ScanMethod {
+    match table.column_index(column_id) {
+        Some(index_id) if predicate_selectivity(table, column_id, predicate) <= INDEX_SCAN_SELECTIVITY => ScanMethod::IndexScan(index_id),
+        _ => ScanMethod::TableScan
+    }
+}
+
+// how an equi-join of an input with the estimated number of rows and a column of a table is executed
+pub fn choose_join_method(outer_rows: f64, inner: &Table, inner_column_id: ColumnId) -> JoinMethod {
+    match inner.column_index(inner_column_id) {
+        Some(index_id) if outer_rows * INDEX_LOOKUP_COST < inner.row_count() as f64 => JoinMethod::IndexNestedLoop(index_id),
+        _ => JoinMethod::Hash
+    }
+}
+
+// the scan predicate of a comparison of a column with literals, None for conditions that cannot be evaluated by an index
+fn scan_predicate(condition: PredicateCondition, values: Vec<Value>) -> Option<ScanPredicate<Value>> {
+    let mut values = values.into_iter();
+    match (condition, values.next(), values.next()) {
+        (PredicateCondition::Equals, Some(value), None) => Some(ScanPredicate::Equals(value)),
+        (PredicateCondition::LessThan, Some(value), None) => Some(ScanPredicate::LessThan(value)),
+        (PredicateCondition::LessThanEquals, Some(value), None) => Some(ScanPredicate::LessThanEquals(value)),
+        (PredicateCondition::GreaterThan, Some(value), None) => Some(ScanPredicate::GreaterThan(value)),
+        (PredicateCondition::GreaterThanEquals, Some(value), None) => Some(ScanPredicate::GreaterThanEquals(value)),
+        (PredicateCondition::BetweenInclusive, Some(low), Some(high)) => Some(ScanPredicate::Between(low, high)),
+        (PredicateCondition::IsNull, None, None) => Some(ScanPredicate::IsNull),
+        (PredicateCondition::IsNotNull, None, None) => Some(ScanPredicate::IsNotNull),
+        _ => None
+    }
+}
+
+pub struct Planner<'a> {
+    catalog: &'a Catalog
+}
+
+impl<'a> Planner<'a> {
+    pub fn new(catalog: &'a Catalog) -> Self {
+        Planner { catalog }
+    }
+
+    // physical operator of each filter on a stored table and each join, indexed by node id (None for the other nodes)
+    //  cardinalities are the estimated output rows of the nodes (see CardinalityEstimator)
+    pub fn plan(&self, lqp: &LQP, cardinalities: &[f64]) -> Result<Vec<Option<NodeMethod>>, CatalogError> {
+        let mut methods = Vec::with_capacity(lqp.nodes().len());
+        for node in lqp.nodes() {
+            methods.push(match &node.data {
+                LQPNodeData::Filter => self.scan_method(lqp, node)?.map(NodeMethod::Scan),
+                LQPNodeData::Join(mode) => Some(NodeMethod::Join(self.join_method(lqp, node, mode, cardinalities)?)),
+                _ => None
+            });
+        }
+        Ok(methods)
+    }
+
+    // the stored table of a node, None for other nodes
+    fn table(&self, lqp: &LQP, node_id: usize) -> Result<Option<Arc<RwLock<Table>>>, CatalogError> {
+        match &lqp.nodes()[node_id].data {
+            LQPNodeData::Table { schema_name, table_name, .. } => Ok(Some(self.catalog.get_table(Some(schema_name), table_name)?)),
+            _ => Ok(None)
+        }
+    }
+
+    // the first predicate of the filter that an index is chosen for is evaluated with the index, the others on its result
+    //  None for filters on the results of other nodes
+    fn scan_method(&self, lqp: &LQP, node: &LQPNode) -> Result<Option<ScanMethod>, CatalogError> {
+        let table_node = match node.inputs[0] {
+            Some(input) => input,
+            None => return Ok(None)
+        };
+        let table = match self.table(lqp, table_node)? {
+            Some(table) => table,
+            None => return Ok(None)
+        };
+        let table = table.read().unwrap();
+        for predicate in node.expressions.iter() {
+            let comparison = match column_comparison(lqp, *predicate) {
+                Some(comparison) if comparison.node == table_node => comparison,
+                _ => continue
+            };
+            if let Some(predicate) = scan_predicate(comparison.condition, comparison.values) {
+                if let ScanMethod::IndexScan(index_id) = choose_scan_method(&table, comparison.column_id, &predicate) {
+                    return Ok(Some(ScanMethod::IndexScan(index_id)))
+                }
+            }
+        }
+        Ok(Some(ScanMethod::TableScan))
+    }
+
+    // an index nested loop join needs an index on the join column of a stored table on the inner side, which is the right
+    //  input of left outer joins and the left input of right outer joins (either input of inner joins)
+    fn join_method(&self, lqp: &LQP, node: &LQPNode, mode: &JoinMode, cardinalities: &[f64]) -> Result<JoinMethod, CatalogError> {
+        let (left, right) = match node.inputs {
+            [Some(left), Some(right)] => (left, right),
+            _ => return Ok(JoinMethod::Hash)
+        };
+        let candidates = match mode {
+            JoinMode::Inner => vec![(left, right), (right, left)],
+            JoinMode::Left => vec![(left, right)],
+            JoinMode::Right => vec![(right, left)],
+            JoinMode::FullOuter | JoinMode::Cross => vec![]
+        };
+        let expressions = lqp.expressions();
+        for (outer, inner) in candidates {
+            let table = match self.table(lqp, inner)? {
+                Some(table) => table,
+                None => continue
+            };
+            let table = table.read().unwrap();
+            for predicate in node.expressions.iter() {
+                let expression = &expressions[*predicate];
+                if expression.data != LQPExpressionData::Predicate(PredicateCondition::Equals) {
+                    continue
+                }
+                for argument in expression.arguments.iter() {
+                    if let LQPExpressionData::Column { node, column_id } = expressions[*argument].data {
+                        if node != inner {
+                            continue
+                        }
+                        if let JoinMethod::IndexNestedLoop(index_id) = choose_join_method(cardinalities[outer], &table, column_id) {
+                            return Ok(JoinMethod::IndexNestedLoop(index_id))
+                        }
+                    }
+                }
+            }
+        }
+        Ok(JoinMethod::Hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlparser::dialect::GenericDialect;
+    use sqlparser::parser::Parser;
+
+    use super::*;
+    use crate::cardinality::CardinalityEstimator;
+    use crate::core::{merge_chunk, ColumnDefinition, DataType};
+
+    fn create_table(catalog: &mut Catalog, name: &str, key: &str, rows: usize) {
+        let columns = vec![ColumnDefinition::new(key, DataType::BigInt, false), ColumnDefinition::new("value", DataType::BigInt, false)];
+        let table = catalog.create_table(None, name, Table::new(columns, 1000)).unwrap();
+        let index_name = format!("{}_index", key);
+        catalog.create_index(None, name, &index_name).unwrap();
+        table.write().unwrap().create_index(&index_name, 0).unwrap();
+        table.write().unwrap().insert((0..rows).map(|i| vec![Value::BigInt(i as i64), Value::BigInt((i % 10) as i64)]).collect()).unwrap();
+        let chunks = table.read().unwrap().chunks_to_merge(1);
+        for chunk_id in chunks {
+            merge_chunk(&table, chunk_id).unwrap();
+        }
+    }
+
+    fn catalog() -> Catalog {
+        let mut catalog = Catalog::new();
+        create_table(&mut catalog, "orders", "o_orderkey", 10000);
+        create_table(&mut catalog, "nation", "n_nationkey", 25);
+        catalog
+    }
+
+    // the physical operators of the filters and joins of the plan of a query
+    fn methods(catalog: &Catalog, sql: &str) -> Vec<NodeMethod> {
+        let statement = Parser::parse_sql(&GenericDialect {}, sql).unwrap().pop().unwrap();
+        let lqp = LQP::from(&statement, catalog).unwrap();
+        let cardinalities = CardinalityEstimator::new(catalog).estimate(&lqp).unwrap();
+        Planner::new(catalog).plan(&lqp, &cardinalities).unwrap().into_iter().flatten().collect()
+    }
+
+    #[test]
+    fn selective_predicates_on_indexed_columns_use_the_index() {
+        let catalog = catalog();
+        assert_eq!(methods(&catalog, "SELECT * FROM orders WHERE o_orderkey = 4711"), vec![NodeMethod::Scan(ScanMethod::IndexScan(0))]);
+        assert_eq!(methods(&catalog, "SELECT * FROM orders WHERE 4711 = o_orderkey"), vec![NodeMethod::Scan(ScanMethod::IndexScan(0))]);
+        assert_eq!(methods(&catalog, "SELECT * FROM orders WHERE value = 3 AND o_orderkey BETWEEN 10 AND 20"), vec![NodeMethod::Scan(ScanMethod::IndexScan(0))]);
+        assert_eq!(methods(&catalog, "SELECT * FROM orders WHERE o_orderkey > 10"), vec![NodeMethod::Scan(ScanMethod::TableScan)]);
+        assert_eq!(methods(&catalog, "SELECT * FROM orders WHERE value = 3"), vec![NodeMethod::Scan(ScanMethod::TableScan)]);
+    }
+
+    #[test]
+    fn small_outer_inputs_are_joined_with_index_lookups() {
+        let catalog = catalog();
+        let index_join = vec![NodeMethod::Join(JoinMethod::IndexNestedLoop(0))];
+        assert_eq!(methods(&catalog, "SELECT * FROM nation JOIN orders ON n_nationkey = o_orderkey"), index_join);
+        assert_eq!(methods(&catalog, "SELECT * FROM orders JOIN nation ON n_nationkey = o_orderkey"), index_join);
+        assert_eq!(methods(&catalog, "SELECT * FROM nation LEFT JOIN orders ON n_nationkey = o_orderkey"), index_join);
+        // the inner side of outer joins has to be the table with the index
+        assert_eq!(methods(&catalog, "SELECT * FROM orders LEFT JOIN nation ON n_nationkey = o_orderkey"), vec![NodeMethod::Join(JoinMethod::Hash)]);
+        assert_eq!(methods(&catalog, "SELECT * FROM orders o1 JOIN orders o2 ON o1.o_orderkey = o2.o_orderkey"), vec![NodeMethod::Join(JoinMethod::Hash)]);
+        assert_eq!(methods(&catalog, "SELECT * FROM nation JOIN orders ON n_nationkey = orders.value"), vec![NodeMethod::Join(JoinMethod::Hash)]);
+    }
+}
@@ -1,5 +1,6 @@
 use std::io;
 use std::io::prelude::*;
+use std::io::BufReader;
 use std::fmt;
 use std::fs::File;
 use std::net::TcpStream;
@@ -9,11 +10,20 @@ use std::str;
 use std::sync::Arc;
 use std::sync::RwLock;
 
+use sqlparser::ast::{ObjectType, Statement};
 use sqlparser::dialect::GenericDialect;
 use sqlparser::parser::{Parser, ParserError};
+use sqlparser::tokenizer::{Token, Tokenizer};
 
-use crate::core::Database;
+use crate::analyze::{execute_analyze, parse_analyze};
+use crate::cardinality::CardinalityEstimator;
+use crate::checkpoint::is_checkpoint;
+use crate::commands::{execute_constant_select, execute_create_index, execute_drop_index, execute_insert, CommandError};
+use crate::copy::{execute_copy, parse_copy, prepare_copy_in, prepare_copy_out, CopyDirection, CopyLocation, CopyStatement};
+use crate::core::{CatalogError, DataType, Database, Value};
 use crate::lqp::{LQP, LQPError};
+use crate::planner::{NodeMethod, Planner};
+use crate::transaction::TransactionContext;
 
 pub fn handle_connection(mut stream: TcpStream, db: Arc<RwLock<Database>>) {
     let mut parameters = HashMap::new();
@@ -135,9 +145,8 @@ pub fn handle_connection(mut stream: TcpStream, db: Arc<RwLock<Database>>) {
                 }
 
                 // parse
-                let dialect = GenericDialect {};
                 // TODO: parse and store as prepared statement
-                match Parser::parse_sql(&dialect, query_string) {
+                match parse_prepared_statement(query_string) {
                     Ok(statements) => {
                         if statements.len() > 1 {
                             send_error_response(&mut stream, ProtocolError::with_detail(ErrorSeverity::Error, String::from("42000"), String::from("Multiple SQL statements"), String::from("Only a single statement is supported in prepared statements"))).unwrap();
@@ -149,13 +158,15 @@ pub fn handle_connection(mut stream: TcpStream, db: Arc<RwLock<Database>>) {
                         } else {
                             let statement = &statements[0];
                             println!("Parsed SQL: {:?}", statement);
-                            let lqp = LQP::from(&statement);
+                            let lqp = LQP::from(&statement, &db.read().unwrap().catalog.read().unwrap());
                             match lqp {
                                 Ok(lqp) => {
                                     println!("LQP: {:?}", lqp);
+                                    let cardinalities = estimate_cardinalities(&db.read().unwrap(), &lqp);
+                                    let methods = plan_methods(&db.read().unwrap(), &lqp, cardinalities.as_deref());
                                     // TEMPORARY: write the LQP to file as a dot graph
                                     let mut file = File::create("lqp.dot").unwrap();
-                                    file.write_all(lqp.get_dot_graph().as_bytes()).unwrap();
+                                    file.write_all(lqp.get_dot_graph(cardinalities.as_deref(), methods.as_deref()).as_bytes()).unwrap();
                                     // TODO: ...
                                 },
                                 Err(err) => {
@@ -209,55 +220,197 @@ pub fn handle_connection(mut stream: TcpStream, db: Arc<RwLock<Database>>) {
             },
             'Q' => {
                 let db = db.read().unwrap();
-                // for now just use a new TransactionContext for each incoming query message
+                // for now just use a new TransactionContext for each incoming query message, which is committed before
+                //  ReadyForQuery
                 // TODO: proper handling of BEGIN/COMMIT/ROLLBACK/ABORT
-                let _transaction_context = db.transaction_manager.lock().unwrap().new_transaction_context();
+                let mut transaction = db.transaction_manager.lock().unwrap().new_transaction_context();
 
                 // get the query string
                 let (query_string, _) = read_string(&message_content).unwrap();
+                if is_checkpoint(query_string) {
+                    match db.checkpointer.checkpoint(&db.catalog) {
+                        Ok(stats) => {
+                            println!("Checkpoint {}: {} tables ({} rows, {} merged chunks) in {:.2?}", stats.id, stats.tables, stats.rows, stats.merged_chunks, stats.duration);
+                            // CommandComplete
+                            send_protocol_message(&mut stream, 'C', b"CHECKPOINT\0").unwrap();
+                        },
+                        Err(err) => {
+                            println!("Checkpoint error: {}", err);
+                            send_error_response(&mut stream, ProtocolError::from(CommandError::Checkpoint(err))).unwrap();
+                        }
+                    }
+                    // ReadyForQuery
+                    send_protocol_message(&mut stream, 'Z', b"I").unwrap();
+                    continue;
+                }
+                // ANALYZE is not supported by the SQL parser (see analyze::parse_analyze)
+                match parse_analyze(query_string) {
+                    Ok(Some(analyze)) => {
+                        match execute_analyze(&db, &analyze) {
+                            Ok(()) => {
+                                // CommandComplete
+                                send_protocol_message(&mut stream, 'C', b"ANALYZE\0").unwrap();
+                            },
+                            Err(err) => {
+                                println!("ANALYZE error: {:?}", err);
+                                send_error_response(&mut stream, ProtocolError::from(err)).unwrap();
+                            }
+                        }
+                        // ReadyForQuery
+                        send_protocol_message(&mut stream, 'Z', b"I").unwrap();
+                        continue;
+                    },
+                    Ok(None) => {},
+                    Err(err) => {
+                        println!("Syntax error: {:?}", err);
+                        send_error_response(&mut stream, ProtocolError::from(err)).unwrap();
+                        // ReadyForQuery
+                        send_protocol_message(&mut stream, 'Z', b"I").unwrap();
+                        continue;
+                    }
+                }
+                // COPY is not supported by the SQL parser (see copy::parse_copy)
+                match parse_copy(query_string) {
+                    Ok(Some(copy)) => {
+                        match (&copy.location, &copy.direction) {
+                            (CopyLocation::Client, CopyDirection::From) => copy_from_client(&mut stream, &db, &mut transaction, &copy),
+                            (CopyLocation::Client, CopyDirection::To) => copy_to_client(&mut stream, &db, &copy),
+                            (CopyLocation::File(_), _) => match execute_copy(&db, &mut transaction, &copy) {
+                                Ok(row_count) => {
+                                    // CommandComplete
+                                    send_protocol_message(&mut stream, 'C', format!("COPY {}\0", row_count).as_bytes()).unwrap();
+                                },
+                                Err(err) => {
+                                    println!("COPY error: {:?}", err);
+                                    send_error_response(&mut stream, ProtocolError::from(err)).unwrap();
+                                }
+                            }
+                        }
+                        commit_transaction(&mut stream, &db, &mut transaction);
+                        // ReadyForQuery
+                        send_protocol_message(&mut stream, 'Z', b"I").unwrap();
+                        continue;
+                    },
+                    Ok(None) => {},
+                    Err(err) => {
+                        println!("Syntax error: {:?}", err);
+                        send_error_response(&mut stream, ProtocolError::from(err)).unwrap();
+                        // ReadyForQuery
+                        send_protocol_message(&mut stream, 'Z', b"I").unwrap();
+                        continue;
+                    }
+                }
                 let dialect = GenericDialect {};
                 match Parser::parse_sql(&dialect, query_string) {
                     Ok(statements) => {
                         for statement in statements {
-                            let lqp = LQP::from(&statement);
+                            if let Statement::Insert { table_name, columns, source, .. } = &statement {
+                                match execute_insert(&db, &mut transaction, table_name, columns, source) {
+                                    Ok(row_count) => {
+                                        // CommandComplete
+                                        send_protocol_message(&mut stream, 'C', format!("INSERT 0 {}\0", row_count).as_bytes()).unwrap();
+                                    },
+                                    Err(err) => {
+                                        println!("INSERT error: {:?}", err);
+                                        send_error_response(&mut stream, ProtocolError::from(err)).unwrap();
+                                    }
+                                }
+                                continue;
+                            }
+                            if let Statement::CreateIndex { name, table_name, columns, unique, if_not_exists } = &statement {
+                                match execute_create_index(&db, &mut transaction, name, table_name, columns, *unique, *if_not_exists) {
+                                    Ok(size) => {
+                                        match size {
+                                            Some(size) => println!("Created index {} on {} ({} bytes)", name, table_name, size),
+                                            None => println!("Index {} exists already, skipping", name)
+                                        }
+                                        // CommandComplete
+                                        send_protocol_message(&mut stream, 'C', b"CREATE INDEX\0").unwrap();
+                                    },
+                                    Err(err) => {
+                                        println!("CREATE INDEX error: {:?}", err);
+                                        send_error_response(&mut stream, ProtocolError::from(err)).unwrap();
+                                    }
+                                }
+                                continue;
+                            }
+                            if let Statement::Drop { object_type: ObjectType::Index, if_exists, names, .. } = &statement {
+                                match execute_drop_index(&db, &mut transaction, names, *if_exists) {
+                                    Ok(()) => {
+                                        // CommandComplete
+                                        send_protocol_message(&mut stream, 'C', b"DROP INDEX\0").unwrap();
+                                    },
+                                    Err(err) => {
+                                        println!("DROP INDEX error: {:?}", err);
+                                        send_error_response(&mut stream, ProtocolError::from(err)).unwrap();
+                                    }
+                                }
+                                continue;
+                            }
+                            if let Statement::Query(query) = &statement {
+                                match execute_constant_select(query) {
+                                    Ok(Some((fields, row))) => {
+                                        send_row_description(&mut stream, &fields).unwrap();
+                                        send_data_row(&mut stream, &row).unwrap();
+                                        // CommandComplete
+                                        send_protocol_message(&mut stream, 'C', "SELECT 1\0".as_bytes()).unwrap();
+                                        continue;
+                                    },
+                                    Ok(None) => {},
+                                    Err(err) => {
+                                        send_error_response(&mut stream, ProtocolError::from(err)).unwrap();
+                                        continue;
+                                    }
+                                }
+                            }
+                            let lqp = LQP::from(&statement, &db.catalog.read().unwrap());
                             println!("Parsed SQL: {:?}", statement);
                             match lqp {
                                 Ok(lqp) => {
                                     println!("LQP: {:?}", lqp);
+                                    let cardinalities = estimate_cardinalities(&db, &lqp);
+                                    let methods = plan_methods(&db, &lqp, cardinalities.as_deref());
                                     // TEMPORARY: write the LQP to file as a dot graph
                                     let mut file = File::create("lqp.dot").unwrap();
-                                    file.write_all(lqp.get_dot_graph().as_bytes()).unwrap();
+                                    file.write_all(lqp.get_dot_graph(cardinalities.as_deref(), methods.as_deref()).as_bytes()).unwrap();
 
-                                    // RowDescription
-                                    //                                   OID         ANUM  TYPE_OID    TYPLENTYPMOD      FORMAT_CODE
-                                    let row_desc_buf = [0, 2, 'i' as u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 4, 0, 0, 0, 0, 0, 0, 'v' as u8, 'a' as u8, 'l' as u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 4, 0, 0, 0, 0, 0, 0];
-                                    send_protocol_message(&mut stream, 'T', &row_desc_buf).unwrap();
                                     // read some dummy data from db
-                                    let avc = db.avc.read().unwrap();
-                                    for i in 0..avc.len() {
-                                        // DataRow
-                                        let mut data_row_buf = Vec::<u8>::new();
-                                        data_row_buf.push(0);
-                                        data_row_buf.push(2);
-                                        // value 1
-                                        let val_str = i.to_string();
-                                        let val_str_b = val_str.as_bytes();
-                                        data_row_buf.extend_from_slice(&(val_str_b.len() as u32).to_be_bytes());
-                                        data_row_buf.extend_from_slice(val_str_b);
-                                        // value 2
-                                        match avc.lookup(i) {
-                                            None => data_row_buf.extend_from_slice(&(-1 as i32).to_be_bytes()),
-                                            Some(val) => {
-                                                let val_str = val.to_string();
-                                                let val_str_b = val_str.as_bytes();
-                                                data_row_buf.extend_from_slice(&(val_str_b.len() as u32).to_be_bytes());
-                                                data_row_buf.extend_from_slice(val_str_b);
+                                    let table = match db.catalog.read().unwrap().get_table(None, "test") {
+                                        Ok(table) => table,
+                                        Err(err) => {
+                                            send_error_response(&mut stream, ProtocolError::from(err)).unwrap();
+                                            continue;
+                                        }
+                                    };
+                                    let table = table.read().unwrap();
+                                    // RowDescription (row index followed by the table columns)
+                                    let mut fields = vec![(String::from("i"), DataType::BigInt)];
+                                    for column in table.column_definitions() {
+                                        fields.push((column.name.clone(), column.data_type));
+                                    }
+                                    send_row_description(&mut stream, &fields).unwrap();
+                                    let mut row_index = 0;
+                                    let mut result = Ok(());
+                                    'chunks: for chunk in table.chunks() {
+                                        for i in 0..chunk.len() {
+                                            match chunk.get_value(0, i) {
+                                                Ok(value) => {
+                                                    send_data_row(&mut stream, &[Value::BigInt((row_index + i) as i64), value]).unwrap();
+                                                },
+                                                Err(err) => {
+                                                    result = Err(err);
+                                                    break 'chunks
+                                                }
                                             }
                                         }
-                                        send_protocol_message(&mut stream, 'D', &data_row_buf).unwrap();
+                                        row_index += chunk.len();
                                     }
-                                    // CommandComplete
-                                    send_protocol_message(&mut stream, 'C', "SELECT\0".as_bytes()).unwrap();
+                                    match result {
+                                        // CommandComplete
+                                        Ok(()) => send_protocol_message(&mut stream, 'C', "SELECT\0".as_bytes()).unwrap(),
+                                        // the rows sent so far are discarded by the client
+                                        Err(err) => send_error_response(&mut stream, ProtocolError::from(CommandError::Read(err))).unwrap()
+                                    };
                                 },
                                 Err(err) => {
                                     println!("LQP creation error: {:?}", err);
@@ -271,6 +424,7 @@ pub fn handle_connection(mut stream: TcpStream, db: Arc<RwLock<Database>>) {
                         send_error_response(&mut stream, ProtocolError::from(err)).unwrap();
                     }
                 }
+                commit_transaction(&mut stream, &db, &mut transaction);
                 // ReadyForQuery
                 send_protocol_message(&mut stream, 'Z', &['I' as u8]).unwrap();
             },
@@ -284,6 +438,30 @@ pub fn handle_connection(mut stream: TcpStream, db: Arc<RwLock<Database>>) {
     println!("Client disconnected");
 }
 
+// estimated output rows of the nodes of a plan (None if the plan references unknown tables)
+fn estimate_cardinalities(db: &Database, lqp: &LQP) -> Option<Vec<f64>> {
+    match CardinalityEstimator::new(&db.catalog.read().unwrap()).estimate(lqp) {
+        // shown in the dot graph of the plan
+        Ok(cardinalities) => Some(cardinalities),
+        Err(err) => {
+            println!("Cardinality estimation error: {}", err);
+            None
+        }
+    }
+}
+
+// physical operators of the filters and joins of a plan (None without estimated cardinalities)
+fn plan_methods(db: &Database, lqp: &LQP, cardinalities: Option<&[f64]>) -> Option<Vec<Option<NodeMethod>>> {
+    match Planner::new(&db.catalog.read().unwrap()).plan(lqp, cardinalities?) {
+        // shown in the dot graph of the plan
+        Ok(methods) => Some(methods),
+        Err(err) => {
+            println!("Planning error: {}", err);
+            None
+        }
+    }
+}
+
 fn send_protocol_message(stream: &mut TcpStream, message_type: char, buf: &[u8]) -> io::Result<usize> {
     let message_len = buf.len() + 4;
     if message_len > u32::MAX as usize {
@@ -295,6 +473,193 @@ fn send_protocol_message(stream: &mut TcpStream, message_type: char, buf: &[u8])
     return Ok(result);
 }
 
+fn send_row_description(stream: &mut TcpStream, fields: &[(String, DataType)]) -> io::Result<usize> {
+    let mut buf = Vec::<u8>::new();
+    buf.extend_from_slice(&(fields.len() as u16).to_be_bytes());
+    for (name, data_type) in fields {
+        buf.extend_from_slice(name.as_bytes());
+        buf.push(0);
+        // table OID and attribute number (not a column of a specific table)
+        buf.extend_from_slice(&0u32.to_be_bytes());
+        buf.extend_from_slice(&0u16.to_be_bytes());
+        buf.extend_from_slice(&data_type.type_oid().to_be_bytes());
+        buf.extend_from_slice(&data_type.type_len().to_be_bytes());
+        buf.extend_from_slice(&data_type.type_modifier().to_be_bytes());
+        // format code (text)
+        buf.extend_from_slice(&0u16.to_be_bytes());
+    }
+    send_protocol_message(stream, 'T', &buf)
+}
+
+// values in text format, NULL is sent as length -1 without any value bytes
+fn send_data_row(stream: &mut TcpStream, values: &[Value]) -> io::Result<usize> {
+    let mut buf = Vec::<u8>::new();
+    buf.extend_from_slice(&(values.len() as u16).to_be_bytes());
+    for value in values {
+        match value {
+            Value::Null => buf.extend_from_slice(&(-1i32).to_be_bytes()),
+            value => {
+                let value_str = value.to_string();
+                buf.extend_from_slice(&(value_str.len() as u32).to_be_bytes());
+                buf.extend_from_slice(value_str.as_bytes());
+            }
+        }
+    }
+    send_protocol_message(stream, 'D', &buf)
+}
+
+// CopyInResponse ('G') or CopyOutResponse ('H'), all columns in text format
+fn send_copy_response(stream: &mut TcpStream, message_type: char, column_count: usize) -> io::Result<usize> {
+    let mut buf = Vec::<u8>::new();
+    // overall format (text)
+    buf.push(0);
+    buf.extend_from_slice(&(column_count as u16).to_be_bytes());
+    for _ in 0..column_count {
+        buf.extend_from_slice(&0u16.to_be_bytes());
+    }
+    send_protocol_message(stream, message_type, &buf)
+}
+
+fn read_protocol_message(stream: &mut TcpStream) -> io::Result<(char, Vec<u8>)> {
+    let mut type_buffer = [0; 1];
+    let mut len_buffer = [0; 4];
+    stream.read_exact(&mut type_buffer)?;
+    stream.read_exact(&mut len_buffer)?;
+    let mut message_content = vec![0; u32::from_be_bytes(len_buffer) as usize - 4];
+    stream.read_exact(message_content.as_mut_slice())?;
+    Ok((type_buffer[0] as char, message_content))
+}
+
+// the payload of the CopyData messages sent by the client during COPY FROM STDIN, ending with CopyDone
+//  CopyFail (or any unexpected message) ends the input with an error, the reason is kept as failure
+struct CopyInReader<'a> {
+    stream: &'a mut TcpStream,
+    data: Vec<u8>,
+    position: usize,
+    done: bool,
+    failure: Option<ProtocolError>
+}
+
+impl CopyInReader<'_> {
+    fn new(stream: &mut TcpStream) -> CopyInReader<'_> {
+        CopyInReader { stream, data: Vec::new(), position: 0, done: false, failure: None }
+    }
+
+    // discards the remaining messages up to CopyDone or CopyFail
+    fn drain(&mut self) -> io::Result<()> {
+        let mut buf = [0; 4096];
+        while self.read(&mut buf)? > 0 {}
+        Ok(())
+    }
+}
+
+impl Read for CopyInReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.data.len() {
+            if self.done {
+                return Ok(0)
+            }
+            let (message_type, message_content) = read_protocol_message(self.stream)?;
+            match message_type {
+                'd' => { // CopyData
+                    self.data = message_content;
+                    self.position = 0;
+                },
+                'c' => self.done = true, // CopyDone
+                'f' => { // CopyFail
+                    self.done = true;
+                    let reason = read_string(&message_content).map(|(reason, _)| reason).unwrap_or("");
+                    self.failure = Some(ProtocolError::with_detail(ErrorSeverity::Error, String::from("57014"), String::from("COPY failed"), format!("COPY from stdin failed: {}", reason)));
+                    return Err(io::Error::other("COPY from stdin failed"))
+                },
+                // flush and sync are ignored during COPY
+                'H' | 'S' => {},
+                _ => {
+                    self.done = true;
+                    self.failure = Some(ProtocolError::with_detail(ErrorSeverity::Error, String::from("08P01"), String::from("Protocol violation"), format!("unexpected message type 0x{:02x} during COPY from stdin", message_type as u8)));
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "unexpected message during COPY from stdin"))
+                }
+            }
+        }
+        let len = std::cmp::min(buf.len(), self.data.len() - self.position);
+        buf[..len].copy_from_slice(&self.data[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+// sends everything written as CopyData messages (one per write)
+struct CopyOutWriter<'a> {
+    stream: &'a mut TcpStream
+}
+
+impl Write for CopyOutWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        send_protocol_message(self.stream, 'd', buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+// commits the changes of a query message, reporting an error if they could not be logged
+fn commit_transaction(stream: &mut TcpStream, db: &Database, transaction: &mut TransactionContext) {
+    if let Err(err) = transaction.commit(&db.catalog) {
+        println!("Commit error: {}", err);
+        send_error_response(stream, ProtocolError::from(CommandError::Log(err))).unwrap();
+    }
+}
+
+// COPY FROM STDIN, the rows are loaded while the CopyData messages arrive
+fn copy_from_client(stream: &mut TcpStream, db: &Database, transaction: &mut TransactionContext, copy: &CopyStatement) {
+    let copy_in = match prepare_copy_in(db, copy) {
+        Ok(copy_in) => copy_in,
+        Err(err) => {
+            send_error_response(stream, ProtocolError::from(err)).unwrap();
+            return
+        }
+    };
+    // CopyInResponse
+    send_copy_response(stream, 'G', copy_in.column_count()).unwrap();
+    let mut input = CopyInReader::new(stream);
+    let result = copy_in.execute(&db.loader_pool, transaction, BufReader::new(&mut input), "STDIN");
+    // the client sends all of its data before it waits for a response, even if loading stopped early
+    input.drain().ok();
+    let err = match (input.failure, result) {
+        (Some(failure), _) => failure,
+        (None, Ok(row_count)) => {
+            // CommandComplete
+            send_protocol_message(stream, 'C', format!("COPY {}\0", row_count).as_bytes()).unwrap();
+            return
+        },
+        (None, Err(err)) => {
+            println!("COPY error: {:?}", err);
+            ProtocolError::from(err)
+        }
+    };
+    send_error_response(stream, err).unwrap();
+}
+
+// COPY TO STDOUT
+fn copy_to_client(stream: &mut TcpStream, db: &Database, copy: &CopyStatement) {
+    let copy_out = match prepare_copy_out(db, copy) {
+        Ok(copy_out) => copy_out,
+        Err(err) => {
+            send_error_response(stream, ProtocolError::from(err)).unwrap();
+            return
+        }
+    };
+    // CopyOutResponse
+    send_copy_response(stream, 'H', copy_out.column_count()).unwrap();
+    let row_count = copy_out.execute(&mut CopyOutWriter { stream }).unwrap();
+    // CopyDone
+    send_protocol_message(stream, 'c', &[]).unwrap();
+    // CommandComplete
+    send_protocol_message(stream, 'C', format!("COPY {}\0", row_count).as_bytes()).unwrap();
+}
+
 #[allow(dead_code)]
 enum ErrorSeverity {
     Error,
@@ -368,8 +733,55 @@ impl From<ParserError> for ProtocolError {
 
 impl From<LQPError> for ProtocolError {
     fn from(err: LQPError) -> Self {
-        ProtocolError::with_detail(ErrorSeverity::Error, String::from("42000"), String::from("LQP error"), err.to_string())
+        ProtocolError::with_detail(ErrorSeverity::Error, String::from(err.sqlstate()), String::from("LQP error"), err.to_string())
+    }
+}
+
+impl From<CatalogError> for ProtocolError {
+    fn from(err: CatalogError) -> Self {
+        ProtocolError::with_detail(ErrorSeverity::Error, String::from(err.sqlstate()), String::from("Catalog error"), err.to_string())
+    }
+}
+
+impl From<CommandError> for ProtocolError {
+    fn from(err: CommandError) -> Self {
+        ProtocolError::with_detail(ErrorSeverity::Error, String::from(err.sqlstate()), String::from("Command error"), err.to_string())
+    }
+}
+
+// parses the SQL of a prepared statement, whose parameters ($1, $2, ...) are not known to the SQL parser
+//  they are passed to the LQP translation as identifiers, which cannot start with $ otherwise
+fn parse_prepared_statement(sql: &str) -> Result<Vec<Statement>, ParserError> {
+    let dialect = GenericDialect {};
+    let mut tokens = Tokenizer::new(&dialect, sql).tokenize()?.into_iter().peekable();
+    let mut result = Vec::new();
+    while let Some(token) = tokens.next() {
+        match (token, tokens.peek()) {
+            (Token::Char('$'), Some(Token::Number(number, false))) if number.chars().all(|c| c.is_ascii_digit()) => {
+                result.push(Token::make_word(&format!("${}", number), None));
+                tokens.next();
+            },
+            (token, _) => result.push(token)
+        }
+    }
+    // like Parser::parse_sql
+    let mut parser = Parser::new(result, &dialect);
+    let mut statements = Vec::new();
+    let mut expecting_delimiter = false;
+    loop {
+        while parser.consume_token(&Token::SemiColon) {
+            expecting_delimiter = false;
+        }
+        if parser.peek_token() == Token::EOF {
+            break
+        }
+        if expecting_delimiter {
+            return Err(ParserError::ParserError(format!("Expected end of statement, found: {}", parser.peek_token())))
+        }
+        statements.push(parser.parse_statement()?);
+        expecting_delimiter = true;
     }
+    Ok(statements)
 }
 
 // Err(true) indicates UTF-8 error, Err(false) indicates no string was found in buf
@@ -31,6 +31,11 @@ impl ThreadPool {
         ThreadPool { workers, sender }
     }
 
+    // number of worker threads
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
     pub fn execute<F>(&self, f: F)
     where
         F: FnOnce() + Send + 'static,
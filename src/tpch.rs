@@ -0,0 +1,191 @@
+use std::io;
+use std::path::Path;
+use std::thread;
+use std::time::Instant;
+
+use crate::core::{read_table, write_table, Catalog, ColumnDefinition, DataType, Table, COLUMN_FILE_EXTENSION, DEFAULT_CHUNK_SIZE};
+use crate::loader::load_table_file;
+use crate::threadpool::ThreadPool;
+
+// schema of the TPC-H benchmark (see section 1.4 of the specification), identifiers are stored as bigint
+
+pub struct TableSchema {
+    pub name: &'static str,
+    pub columns: &'static [(&'static str, DataType)]
+}
+
+const MONEY: DataType = DataType::Decimal(15, 2);
+
+pub const TPCH_TABLES: [TableSchema; 8] = [
+    TableSchema { name: "region", columns: &[
+        ("r_regionkey", DataType::BigInt),
+        ("r_name", DataType::Char(25)),
+        ("r_comment", DataType::Varchar(Some(152)))
+    ] },
+    TableSchema { name: "nation", columns: &[
+        ("n_nationkey", DataType::BigInt),
+        ("n_name", DataType::Char(25)),
+        ("n_regionkey", DataType::BigInt),
+        ("n_comment", DataType::Varchar(Some(152)))
+    ] },
+    TableSchema { name: "supplier", columns: &[
+        ("s_suppkey", DataType::BigInt),
+        ("s_name", DataType::Char(25)),
+        ("s_address", DataType::Varchar(Some(40))),
+        ("s_nationkey", DataType::BigInt),
+        ("s_phone", DataType::Char(15)),
+        ("s_acctbal", MONEY),
+        ("s_comment", DataType::Varchar(Some(101)))
+    ] },
+    TableSchema { name: "customer", columns: &[
+        ("c_custkey", DataType::BigInt),
+        ("c_name", DataType::Varchar(Some(25))),
+        ("c_address", DataType::Varchar(Some(40))),
+        ("c_nationkey", DataType::BigInt),
+        ("c_phone", DataType::Char(15)),
+        ("c_acctbal", MONEY),
+        ("c_mktsegment", DataType::Char(10)),
+        ("c_comment", DataType::Varchar(Some(117)))
+    ] },
+    TableSchema { name: "part", columns: &[
+        ("p_partkey", DataType::BigInt),
+        ("p_name", DataType::Varchar(Some(55))),
+        ("p_mfgr", DataType::Char(25)),
+        ("p_brand", DataType::Char(10)),
+        ("p_type", DataType::Varchar(Some(25))),
+        ("p_size", DataType::BigInt),
+        ("p_container", DataType::Char(10)),
+        ("p_retailprice", MONEY),
+        ("p_comment", DataType::Varchar(Some(23)))
+    ] },
+    TableSchema { name: "partsupp", columns: &[
+        ("ps_partkey", DataType::BigInt),
+        ("ps_suppkey", DataType::BigInt),
+        ("ps_availqty", DataType::BigInt),
+        ("ps_supplycost", MONEY),
+        ("ps_comment", DataType::Varchar(Some(199)))
+    ] },
+    TableSchema { name: "orders", columns: &[
+        ("o_orderkey", DataType::BigInt),
+        ("o_custkey", DataType::BigInt),
+        ("o_orderstatus", DataType::Char(1)),
+        ("o_totalprice", MONEY),
+        ("o_orderdate", DataType::Date),
+        ("o_orderpriority", DataType::Char(15)),
+        ("o_clerk", DataType::Char(15)),
+        ("o_shippriority", DataType::BigInt),
+        ("o_comment", DataType::Varchar(Some(79)))
+    ] },
+    TableSchema { name: "lineitem", columns: &[
+        ("l_orderkey", DataType::BigInt),
+        ("l_partkey", DataType::BigInt),
+        ("l_suppkey", DataType::BigInt),
+        ("l_linenumber", DataType::BigInt),
+        ("l_quantity", MONEY),
+        ("l_extendedprice", MONEY),
+        ("l_discount", MONEY),
+        ("l_tax", MONEY),
+        ("l_returnflag", DataType::Char(1)),
+        ("l_linestatus", DataType::Char(1)),
+        ("l_shipdate", DataType::Date),
+        ("l_commitdate", DataType::Date),
+        ("l_receiptdate", DataType::Date),
+        ("l_shipinstruct", DataType::Char(25)),
+        ("l_shipmode", DataType::Char(10)),
+        ("l_comment", DataType::Varchar(Some(44)))
+    ] }
+];
+
+impl TableSchema {
+    // all TPC-H columns are NOT NULL
+    pub fn column_definitions(&self) -> Vec<ColumnDefinition> {
+        self.columns.iter().map(|(name, data_type)| ColumnDefinition::new(name, *data_type, false)).collect()
+    }
+}
+
+// loads a table from <table name>.tbl in the given directory, skipping (and printing) invalid rows
+fn load_tbl_file(pool: &ThreadPool, dir: &Path, schema: &TableSchema) -> io::Result<Table> {
+    let path = dir.join(format!("{}.tbl", schema.name));
+    let (table, errors) = load_table_file(pool, &path, schema.column_definitions(), DEFAULT_CHUNK_SIZE)?;
+    for error in errors {
+        println!("{}: {}", path.display(), error);
+    }
+    Ok(table)
+}
+
+// maps a table from <table name>.col in the given directory (see convert_tpch), None if there is no such file or it
+//  cannot be used
+fn load_column_file(dir: &Path, schema: &TableSchema) -> Option<Table> {
+    let path = dir.join(schema.name).with_extension(COLUMN_FILE_EXTENSION);
+    if !path.exists() {
+        return None
+    }
+    match read_table(&path) {
+        Ok(table) if *table.column_definitions() == schema.column_definitions() => Some(table),
+        Ok(_) => {
+            println!("Ignoring {}: the columns do not match the TPC-H schema", path.display());
+            None
+        },
+        Err(err) => {
+            println!("Ignoring {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+// runs load for all TPC-H tables at the same time, sharing the thread pool for parsing and encoding
+fn load_all<R: Send, F: Fn(&ThreadPool, &TableSchema) -> R + Sync>(pool: &ThreadPool, load: F) -> Vec<(&'static str, R)> {
+    thread::scope(|scope| {
+        let loaders: Vec<_> = TPCH_TABLES.iter().map(|schema| {
+            let load = &load;
+            scope.spawn(move || (schema.name, load(pool, schema)))
+        }).collect();
+        loaders.into_iter().map(|loader| loader.join().unwrap()).collect()
+    })
+}
+
+// creates all TPC-H tables in the default schema, mapping <table name>.col from the given directory if it exists and
+//  loading <table name>.tbl otherwise
+//  tables whose files are missing or cannot be read are created empty
+pub fn load_tpch(pool: &ThreadPool, dir: &Path, catalog: &mut Catalog) {
+    let start = Instant::now();
+    let tables = load_all(pool, |pool, schema| {
+        if let Some(table) = load_column_file(dir, schema) {
+            return table
+        }
+        load_tbl_file(pool, dir, schema).unwrap_or_else(|err| {
+            println!("Could not load {}.tbl from {}: {}", schema.name, dir.display(), err);
+            Table::new(schema.column_definitions(), DEFAULT_CHUNK_SIZE)
+        })
+    });
+    for (name, table) in tables {
+        println!("{}: {} rows in {} chunks", name, table.row_count(), table.chunk_count());
+        if let Some(chunk) = table.chunks().first() {
+            for (column_id, column) in table.column_definitions().iter().enumerate() {
+                let memory_usage: usize = table.chunks().iter().map(|chunk| chunk.column(column_id).memory_usage()).sum();
+                let avc = chunk.column(column_id);
+                println!("{:>18} {:>18} {:>8} distinct in chunk 0 {:>12} bytes", column.name, avc.encoding().to_string(), avc.distinct_count(), memory_usage);
+            }
+        }
+        catalog.create_table(None, name, table).unwrap();
+    }
+    println!("Loaded TPC-H data in {:.2?}", start.elapsed());
+}
+
+// converts the .tbl files of the given directory into column files (<table name>.col) next to them, which are mapped by
+//  load_tpch instead of parsing the .tbl files again
+pub fn convert_tpch(pool: &ThreadPool, dir: &Path) {
+    let start = Instant::now();
+    let results = load_all(pool, |pool, schema| {
+        let table = load_tbl_file(pool, dir, schema)?;
+        write_table(&table, &dir.join(schema.name).with_extension(COLUMN_FILE_EXTENSION))?;
+        Ok::<_, io::Error>(table.row_count())
+    });
+    for (name, result) in results {
+        match result {
+            Ok(row_count) => println!("{}: converted {} rows", name, row_count),
+            Err(err) => println!("Could not convert {}: {}", name, err)
+        }
+    }
+    println!("Converted TPC-H data in {:.2?}", start.elapsed());
+}
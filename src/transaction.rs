@@ -1,15 +1,335 @@
+use std::io;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, RwLock};
 
+use crate::core::{Catalog, Chunk, ColumnId, Table, TableError, Value};
+use crate::wal::{RedoRecord, Wal};
 
 pub struct TransactionManager {
-
+    // None if changes are not logged
+    wal: Option<Arc<Wal>>,
+    writers: Arc<WriterGate>
 }
 
 pub struct TransactionContext {
     // TODO: this will have MVCC data for an open transaction
+    wal: Option<Arc<Wal>>,
+    // changes made since the last commit
+    redo: RedoRecord,
+    writers: Arc<WriterGate>,
+    // whether the transaction is counted as a writer (see begin_change)
+    writing: bool,
+    // rows that are appended to the tables once they are committed
+    pending: Vec<PendingInsert>,
+    // index changes, which are applied directly, in the order they were made
+    index_changes: Vec<IndexChange>
+}
+
+enum PendingInsert {
+    // rows returned by Table::coerce_rows
+    Rows(Arc<RwLock<Table>>, Vec<Vec<Value>>),
+    Chunks(Arc<RwLock<Table>>, Vec<Chunk>)
+}
+
+// what is needed to revert an index change if the commit fails
+enum IndexChange {
+    Created { schema_name: String, index_name: String },
+    Dropped { schema_name: String, table_name: String, index_name: String, column_id: ColumnId }
+}
+
+impl IndexChange {
+    fn revert(self, catalog: &RwLock<Catalog>) {
+        let mut catalog = catalog.write().unwrap();
+        match self {
+            IndexChange::Created { schema_name, index_name } => {
+                if let Ok((_, _, table)) = catalog.drop_index(Some(&schema_name), &index_name) {
+                    table.write().unwrap().drop_index(&index_name);
+                }
+            },
+            IndexChange::Dropped { schema_name, table_name, index_name, column_id } => {
+                if let Ok((_, table)) = catalog.create_index(Some(&schema_name), &table_name, &index_name) {
+                    // the index stays dropped if the values of the table cannot be read anymore
+                    if table.write().unwrap().create_index(&index_name, column_id).is_err() {
+                        let _ = catalog.drop_index(Some(&schema_name), &index_name);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct WriterState {
+    // transactions that changed tables but did not commit yet
+    active: usize,
+    exclusive: bool
+}
+
+// lets a checkpoint wait until no transaction has uncommitted changes and keeps new ones from changing tables until it
+//  is done, so that the persisted tables contain the changes of exactly the transactions in the log
+#[derive(Default)]
+pub struct WriterGate {
+    state: Mutex<WriterState>,
+    changed: Condvar
+}
+
+impl WriterGate {
+    fn wait_while<'a>(&'a self, mut state: MutexGuard<'a, WriterState>, condition: impl Fn(&WriterState) -> bool) -> MutexGuard<'a, WriterState> {
+        while condition(&state) {
+            state = self.changed.wait(state).unwrap();
+        }
+        state
+    }
+
+    fn enter(&self) {
+        let mut state = self.wait_while(self.state.lock().unwrap(), |state| state.exclusive);
+        state.active += 1;
+    }
+
+    fn leave(&self) {
+        self.state.lock().unwrap().active -= 1;
+        self.changed.notify_all();
+    }
+
+    // waits until all transactions with changes committed and blocks further changes until the guard is dropped (only
+    //  one guard exists at a time)
+    pub fn block_changes(self: &Arc<Self>) -> ChangesBlocked {
+        let mut state = self.wait_while(self.state.lock().unwrap(), |state| state.exclusive);
+        state.exclusive = true;
+        drop(self.wait_while(state, |state| state.active > 0));
+        ChangesBlocked { writers: self.clone() }
+    }
+}
+
+// no transaction can change tables while this is held (see WriterGate::block_changes)
+pub struct ChangesBlocked {
+    writers: Arc<WriterGate>
+}
+
+impl Drop for ChangesBlocked {
+    fn drop(&mut self) {
+        self.writers.state.lock().unwrap().exclusive = false;
+        self.writers.changed.notify_all();
+    }
 }
 
 impl TransactionManager {
+    pub fn new(wal: Option<Arc<Wal>>) -> Self {
+        TransactionManager { wal, writers: Arc::new(WriterGate::default()) }
+    }
+
     pub fn new_transaction_context(&mut self) -> TransactionContext {
-        TransactionContext {}
+        TransactionContext {
+            wal: self.wal.clone(),
+            redo: RedoRecord::default(),
+            writers: self.writers.clone(),
+            writing: false,
+            pending: Vec::new(),
+            index_changes: Vec::new()
+        }
+    }
+
+    pub fn writer_gate(&self) -> Arc<WriterGate> {
+        self.writers.clone()
+    }
+}
+
+impl TransactionContext {
+    // has to be called before the transaction changes the catalog or the indexes of a table directly (and before the
+    //  table is locked for it), waits while a checkpoint is running
+    //  inserted rows do not need it, as they are only appended on commit, which waits for the checkpoint then (so that
+    //  loading rows, e.g., of COPY FROM STDIN, does not hold up checkpoints)
+    pub fn begin_change(&mut self) {
+        if !self.writing {
+            self.writers.enter();
+            self.writing = true;
+        }
     }
-}
\ No newline at end of file
+
+    fn log_insert(&mut self, schema_name: &str, table_name: &str, rows: &[Vec<Value>]) {
+        if self.wal.is_some() {
+            self.redo.log_insert(schema_name, table_name, rows);
+        }
+    }
+
+    // inserts rows into a table on commit, fails if they do not match the columns of the table
+    pub fn insert(&mut self, schema_name: &str, table_name: &str, table: Arc<RwLock<Table>>, rows: Vec<Vec<Value>>) -> Result<(), TableError> {
+        let rows = table.read().unwrap().coerce_rows(rows)?;
+        self.log_insert(schema_name, table_name, &rows);
+        self.pending.push(PendingInsert::Rows(table, rows));
+        Ok(())
+    }
+
+    // appends chunks with the columns of the table on commit (e.g., loaded by COPY)
+    //  fails (without appending any of them) if the values of a chunk cannot be read
+    pub fn append_chunks(&mut self, schema_name: &str, table_name: &str, table: Arc<RwLock<Table>>, chunks: Vec<Chunk>) -> io::Result<()> {
+        let mut rows = Vec::new();
+        for chunk in chunks.iter() {
+            for i in 0..chunk.len() {
+                rows.push((0..chunk.column_count()).map(|column_id| chunk.get_value(column_id, i)).collect::<io::Result<Vec<_>>>()?);
+            }
+        }
+        self.log_insert(schema_name, table_name, &rows);
+        self.pending.push(PendingInsert::Chunks(table, chunks));
+        Ok(())
+    }
+
+    // records an index that was created, which is dropped again if the commit fails
+    pub fn log_create_index(&mut self, schema_name: &str, table_name: &str, index_name: &str, column_name: &str) {
+        if self.wal.is_some() {
+            self.redo.log_create_index(schema_name, table_name, index_name, column_name);
+        }
+        self.index_changes.push(IndexChange::Created { schema_name: schema_name.to_owned(), index_name: index_name.to_owned() });
+    }
+
+    // records an index that was dropped, which is created again if the commit fails
+    pub fn log_drop_index(&mut self, schema_name: &str, table_name: &str, index_name: &str, column_id: ColumnId) {
+        if self.wal.is_some() {
+            self.redo.log_drop_index(schema_name, index_name);
+        }
+        self.index_changes.push(IndexChange::Dropped {
+            schema_name: schema_name.to_owned(),
+            table_name: table_name.to_owned(),
+            index_name: index_name.to_owned(),
+            column_id
+        });
+    }
+
+    // makes the changes made so far durable by writing them to the log (see SyncPolicy)
+    //  inserted rows are only appended to the tables once their record is written, index changes are reverted if it cannot
+    //  be written, so a failed commit leaves no changes behind that would be lost on restart
+    pub fn commit(&mut self, catalog: &RwLock<Catalog>) -> io::Result<()> {
+        // the rows have to be appended before a checkpoint truncates the log containing them
+        if !self.pending.is_empty() {
+            self.begin_change();
+        }
+        let result = match &self.wal {
+            Some(wal) if !self.redo.is_empty() => wal.commit(&self.redo),
+            _ => Ok(())
+        };
+        self.redo = RedoRecord::default();
+        let pending = std::mem::take(&mut self.pending);
+        let index_changes = std::mem::take(&mut self.index_changes);
+        if result.is_ok() {
+            for insert in pending {
+                match insert {
+                    PendingInsert::Rows(table, rows) => table.write().unwrap().append_rows(rows),
+                    PendingInsert::Chunks(table, chunks) => {
+                        let mut table = table.write().unwrap();
+                        for chunk in chunks {
+                            // every value of the chunks was read when they were logged (see append_chunks), so creating
+                            //  their indexes cannot fail here
+                            table.append_chunk(chunk).expect("chunk of a committed transaction cannot be read");
+                        }
+                    }
+                }
+            }
+        } else {
+            for change in index_changes.into_iter().rev() {
+                change.revert(catalog);
+            }
+        }
+        self.end_changes();
+        result
+    }
+
+    fn end_changes(&mut self) {
+        if self.writing {
+            self.writers.leave();
+            self.writing = false;
+        }
+    }
+}
+
+impl Drop for TransactionContext {
+    fn drop(&mut self) {
+        self.end_changes();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::core::{ColumnDefinition, DataType};
+    use crate::wal::SyncPolicy;
+
+    fn catalog() -> RwLock<Catalog> {
+        let mut catalog = Catalog::new();
+        let columns = vec![ColumnDefinition::new("id", DataType::BigInt, false), ColumnDefinition::new("name", DataType::Text, true)];
+        catalog.create_table(None, "t", Table::new(columns, 100)).unwrap();
+        catalog.create_index(None, "t", "t_name").unwrap().1.write().unwrap().create_index("t_name", 1).unwrap();
+        RwLock::new(catalog)
+    }
+
+    fn change_table(transaction: &mut TransactionContext, catalog: &RwLock<Catalog>) {
+        let table = catalog.read().unwrap().get_table(None, "t").unwrap();
+        transaction.begin_change();
+        transaction.insert("public", "t", table.clone(), vec![vec![Value::BigInt(1), Value::Text(String::from("a"))]]).unwrap();
+        // the rows are checked when they are inserted
+        assert!(transaction.insert("public", "t", table.clone(), vec![vec![Value::Null, Value::Null]]).is_err());
+        catalog.write().unwrap().create_index(None, "t", "t_id").unwrap();
+        table.write().unwrap().create_index("t_id", 0).unwrap();
+        transaction.log_create_index("public", "t", "t_id", "id");
+        catalog.write().unwrap().drop_index(None, "t_name").unwrap();
+        table.write().unwrap().drop_index("t_name");
+        transaction.log_drop_index("public", "t", "t_name", 1);
+        // not visible before the commit
+        assert_eq!(table.read().unwrap().row_count(), 0);
+    }
+
+    fn index_names(catalog: &RwLock<Catalog>) -> Vec<String> {
+        let table = catalog.read().unwrap().get_table(None, "t").unwrap();
+        let table = table.read().unwrap();
+        table.indexes().iter().map(|index| index.name.clone()).collect()
+    }
+
+    #[test]
+    fn committed_changes_are_applied() {
+        let catalog = catalog();
+        let mut transaction = TransactionManager::new(None).new_transaction_context();
+        change_table(&mut transaction, &catalog);
+        transaction.commit(&catalog).unwrap();
+        let table = catalog.read().unwrap().get_table(None, "t").unwrap();
+        assert_eq!(table.read().unwrap().row_count(), 1);
+        assert_eq!(index_names(&catalog), vec![String::from("t_id")]);
+    }
+
+    // e.g., while COPY FROM STDIN waits for the data of the client
+    #[test]
+    fn inserts_do_not_block_checkpoints_before_the_commit() {
+        let catalog = catalog();
+        let mut manager = TransactionManager::new(None);
+        let mut transaction = manager.new_transaction_context();
+        let table = catalog.read().unwrap().get_table(None, "t").unwrap();
+        transaction.insert("public", "t", table.clone(), vec![vec![Value::BigInt(1), Value::Null]]).unwrap();
+        let gate = manager.writer_gate();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let blocked = gate.block_changes();
+            sender.send(()).unwrap();
+            drop(blocked);
+        });
+        receiver.recv_timeout(std::time::Duration::from_secs(10)).expect("the checkpoint waits for the insert");
+        transaction.commit(&catalog).unwrap();
+        assert_eq!(table.read().unwrap().row_count(), 1);
+    }
+
+    #[test]
+    fn failed_commits_leave_no_changes() {
+        let path = std::env::temp_dir().join(format!("rdbms-transaction-{}.wal", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let wal = Wal::open(&path, SyncPolicy::Never, 0).unwrap();
+        let catalog = catalog();
+        let mut transaction = TransactionManager::new(Some(wal.clone())).new_transaction_context();
+        change_table(&mut transaction, &catalog);
+        wal.fail();
+        assert!(transaction.commit(&catalog).is_err());
+        let table = catalog.read().unwrap().get_table(None, "t").unwrap();
+        assert_eq!(table.read().unwrap().row_count(), 0);
+        assert_eq!(index_names(&catalog), vec![String::from("t_name")]);
+        assert!(catalog.read().unwrap().resolve_index_schema(None, "t_id").is_err());
+        assert!(catalog.read().unwrap().resolve_index_schema(None, "t_name").is_ok());
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,719 @@
+use std::convert::TryInto;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use crate::core::{crc32c, Catalog, Date, Decimal, Value, ValueType};
+
+// write-ahead log: the changes of each committed transaction are appended to the log as a single record before the commit
+//  is acknowledged, so that they can be replayed on top of the persisted tables (see recover) after a restart or crash
+//  the log starts with a header naming the checkpoint whose tables it continues: "RDBMSWAL", checkpoint id (u64), logs
+//  written before there were checkpoints have no header (see recover_headerless)
+//  record: length of the payload (u32), CRC-32C of the payload (u32), payload
+//  payload: number of changes (u32) followed by the changes, each starting with a tag byte
+//  a record that was not written completely (e.g., because the process was killed during the commit) fails the checksum
+//  and is discarded by the recovery together with everything after it, so transactions are either replayed completely or
+//  not at all
+
+const MAGIC: &[u8; 8] = b"RDBMSWAL";
+const HEADER_SIZE: usize = 16;
+const RECORD_HEADER_SIZE: usize = 8;
+const INSERT_TAG: u8 = 1;
+const CREATE_INDEX_TAG: u8 = 2;
+const DROP_INDEX_TAG: u8 = 3;
+
+// when the log is flushed to disk
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyncPolicy {
+    // each commit waits until its record is on disk (fsync), commits arriving during an fsync are flushed together by the
+    //  next one (group commit)
+    Commit,
+    // commits wait until their record is written to the OS, which is flushed to disk periodically by a background thread
+    //  (commits of the last interval may be lost when the OS crashes, but not when only the process does)
+    Periodic(Duration),
+    // commits wait until their record is written to the OS, which decides when to flush it to disk
+    Never
+}
+
+// the changes of a transaction, serialized as they are made and written to the log as a single record on commit
+#[derive(Default)]
+pub struct RedoRecord {
+    data: Vec<u8>,
+    change_count: u32
+}
+
+fn write_string(buf: &mut Vec<u8>, value: &str) {
+    buf.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    buf.extend_from_slice(value.as_bytes());
+}
+
+// a tag byte followed by the value itself (see ValueType::write_bytes)
+fn write_value(buf: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => buf.push(0),
+        Value::BigInt(val) => {
+            buf.push(1);
+            val.write_bytes(buf);
+        },
+        Value::Double(val) => {
+            buf.push(2);
+            val.write_bytes(buf);
+        },
+        Value::Text(val) => {
+            buf.push(3);
+            val.write_bytes(buf);
+        },
+        Value::Date(val) => {
+            buf.push(4);
+            val.write_bytes(buf);
+        },
+        Value::Decimal(val) => {
+            buf.push(5);
+            val.write_bytes(buf);
+        },
+        Value::Boolean(val) => {
+            buf.push(6);
+            val.write_bytes(buf);
+        }
+    }
+}
+
+impl RedoRecord {
+    pub fn is_empty(&self) -> bool {
+        self.change_count == 0
+    }
+
+    // rows appended to a table: schema and table name, number of rows (u32) and columns (u32), values row by row
+    pub fn log_insert(&mut self, schema_name: &str, table_name: &str, rows: &[Vec<Value>]) {
+        self.data.push(INSERT_TAG);
+        write_string(&mut self.data, schema_name);
+        write_string(&mut self.data, table_name);
+        self.data.extend_from_slice(&(rows.len() as u32).to_le_bytes());
+        self.data.extend_from_slice(&(rows.first().map_or(0, |row| row.len()) as u32).to_le_bytes());
+        for row in rows {
+            for value in row {
+                write_value(&mut self.data, value);
+            }
+        }
+        self.change_count += 1;
+    }
+
+    // index created on a table: schema, table, index and column name
+    pub fn log_create_index(&mut self, schema_name: &str, table_name: &str, index_name: &str, column_name: &str) {
+        self.data.push(CREATE_INDEX_TAG);
+        for name in [schema_name, table_name, index_name, column_name].iter() {
+            write_string(&mut self.data, name);
+        }
+        self.change_count += 1;
+    }
+
+    // index dropped: schema and index name
+    pub fn log_drop_index(&mut self, schema_name: &str, index_name: &str) {
+        self.data.push(DROP_INDEX_TAG);
+        write_string(&mut self.data, schema_name);
+        write_string(&mut self.data, index_name);
+        self.change_count += 1;
+    }
+}
+
+struct WalState {
+    // records that were committed but not yet written to the file
+    buffer: Vec<u8>,
+    // number of records that were committed, written to the file and flushed to disk (so far)
+    committed: u64,
+    written: u64,
+    synced: u64,
+    // bytes of the records in the file
+    size: u64,
+    // the file is taken by the thread writing the buffer, so that the lock is not held during I/O
+    file: Option<File>,
+    // set after a failed write, as the log may be corrupt from then on
+    failed: bool
+}
+
+pub struct Wal {
+    path: PathBuf,
+    state: Mutex<WalState>,
+    // notified whenever the buffer was written
+    written: Condvar,
+    policy: SyncPolicy
+}
+
+fn log_failed() -> io::Error {
+    io::Error::other("the write-ahead log cannot be written after an earlier error")
+}
+
+// creates a log continuing the given checkpoint with the given records, replacing an existing log atomically
+fn create_log(path: &Path, checkpoint: u64, records: &[u8]) -> io::Result<File> {
+    let temp_path = path.with_extension("tmp");
+    let mut file = File::create(&temp_path)?;
+    file.write_all(MAGIC)?;
+    file.write_all(&checkpoint.to_le_bytes())?;
+    file.write_all(records)?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)?;
+    OpenOptions::new().append(true).open(path)
+}
+
+impl Wal {
+    // opens the log for appending new records, creating it for the given checkpoint if it does not exist
+    //  recover has to be called before, so that incomplete records and logs of older checkpoints are removed
+    pub fn open(path: &Path, policy: SyncPolicy, checkpoint: u64) -> io::Result<Arc<Wal>> {
+        let (file, size) = match fs::metadata(path) {
+            Ok(metadata) if metadata.len() > 0 => (OpenOptions::new().append(true).open(path)?, metadata.len() - HEADER_SIZE as u64),
+            _ => (create_log(path, checkpoint, &[])?, 0)
+        };
+        let state = WalState { buffer: Vec::new(), committed: 0, written: 0, synced: 0, size, file: Some(file), failed: false };
+        let wal = Arc::new(Wal { path: path.to_owned(), state: Mutex::new(state), written: Condvar::new(), policy });
+        if let SyncPolicy::Periodic(interval) = policy {
+            let wal = Arc::downgrade(&wal);
+            thread::spawn(move || {
+                loop {
+                    thread::sleep(interval);
+                    match wal.upgrade() {
+                        Some(wal) => {
+                            if let Err(err) = wal.sync() {
+                                println!("Could not flush the write-ahead log: {}", err);
+                            }
+                        },
+                        None => break
+                    }
+                }
+            });
+        }
+        Ok(wal)
+    }
+
+    // appends the record and waits until it is written (and flushed if required by the sync policy)
+    //  the first waiting thread writes the records of all threads that committed in the meantime
+    pub fn commit(&self, record: &RedoRecord) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.failed {
+            return Err(log_failed())
+        }
+        state.buffer.extend_from_slice(&(record.data.len() as u32 + 4).to_le_bytes());
+        let payload_start = state.buffer.len() + 4;
+        state.buffer.extend_from_slice(&[0; 4]);
+        state.buffer.extend_from_slice(&record.change_count.to_le_bytes());
+        state.buffer.extend_from_slice(&record.data);
+        let checksum = crc32c(&state.buffer[payload_start..]);
+        state.buffer[payload_start - 4..payload_start].copy_from_slice(&checksum.to_le_bytes());
+        state.committed += 1;
+        let sequence_number = state.committed;
+        let sync = self.policy == SyncPolicy::Commit;
+        loop {
+            let done = if sync { state.synced >= sequence_number } else { state.written >= sequence_number };
+            if done {
+                return Ok(())
+            }
+            if state.failed {
+                return Err(log_failed())
+            }
+            if state.file.is_some() {
+                let (guard, result) = self.write_buffer(state, sync);
+                state = guard;
+                result?;
+            } else {
+                state = self.written.wait(state).unwrap();
+            }
+        }
+    }
+
+    // flushes everything written so far to disk
+    pub fn sync(&self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        while state.file.is_none() {
+            state = self.written.wait(state).unwrap();
+        }
+        if state.failed || state.synced == state.committed {
+            return Ok(())
+        }
+        self.write_buffer(state, true).1
+    }
+
+    // lets the following commits fail as after a failed write
+    #[cfg(test)]
+    pub fn fail(&self) {
+        self.state.lock().unwrap().failed = true;
+    }
+
+    // bytes of the records written since the last checkpoint
+    pub fn size(&self) -> u64 {
+        self.state.lock().unwrap().size
+    }
+
+    // replaces the log by an empty one continuing the given checkpoint, which has to contain all changes of the log
+    //  (i.e., no transaction may commit meanwhile)
+    pub fn truncate(&self, checkpoint: u64) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        while state.file.is_none() {
+            state = self.written.wait(state).unwrap();
+        }
+        debug_assert!(state.buffer.is_empty());
+        state.file = Some(create_log(&self.path, checkpoint, &[])?);
+        state.size = 0;
+        // the partial record of a failed commit is gone with the old log (its changes were not applied), so the new log can
+        //  be written again
+        state.failed = false;
+        Ok(())
+    }
+
+    // writes the buffer to the file without holding the lock, requires the file not to be taken by another thread
+    fn write_buffer<'a>(&'a self, mut state: MutexGuard<'a, WalState>, sync: bool) -> (MutexGuard<'a, WalState>, io::Result<()>) {
+        let buffer = std::mem::take(&mut state.buffer);
+        let committed = state.committed;
+        let mut file = state.file.take().unwrap();
+        drop(state);
+        let result = file.write_all(&buffer).and_then(|_| if sync { file.sync_data() } else { Ok(()) });
+        let mut state = self.state.lock().unwrap();
+        state.file = Some(file);
+        match result {
+            Ok(()) => {
+                state.written = committed;
+                state.size += buffer.len() as u64;
+                if sync {
+                    state.synced = committed;
+                }
+            },
+            Err(_) => state.failed = true
+        }
+        self.written.notify_all();
+        (state, result)
+    }
+}
+
+// reads the values of a record, whose length was checked by the checksum already (reading beyond the end panics)
+struct RecordReader<'a> {
+    data: &'a [u8],
+    offset: usize
+}
+
+impl<'a> RecordReader<'a> {
+    fn read_u8(&mut self) -> u8 {
+        self.offset += 1;
+        self.data[self.offset - 1]
+    }
+
+    fn read_u32(&mut self) -> u32 {
+        let bytes = &self.data[self.offset..self.offset + 4];
+        self.offset += 4;
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    fn read_string(&mut self) -> io::Result<String> {
+        String::read_bytes(self.data, &mut self.offset)
+    }
+
+    fn read_value(&mut self) -> io::Result<Value> {
+        Ok(match self.read_u8() {
+            0 => Value::Null,
+            1 => Value::BigInt(i64::read_bytes(self.data, &mut self.offset)?),
+            2 => Value::Double(f64::read_bytes(self.data, &mut self.offset)?),
+            3 => Value::Text(String::read_bytes(self.data, &mut self.offset)?),
+            4 => Value::Date(Date::read_bytes(self.data, &mut self.offset)?),
+            5 => Value::Decimal(Decimal::read_bytes(self.data, &mut self.offset)?),
+            6 => Value::Boolean(bool::read_bytes(self.data, &mut self.offset)?),
+            tag => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown value tag {} in write-ahead log", tag)))
+        })
+    }
+}
+
+fn replay_record(payload: &[u8], catalog: &mut Catalog) -> io::Result<usize> {
+    let invalid_data = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    let mut reader = RecordReader { data: payload, offset: 0 };
+    let mut row_count = 0;
+    for _ in 0..reader.read_u32() {
+        match reader.read_u8() {
+            INSERT_TAG => {
+                let schema_name = reader.read_string()?;
+                let table_name = reader.read_string()?;
+                let rows = reader.read_u32() as usize;
+                let columns = reader.read_u32() as usize;
+                let mut values = Vec::with_capacity(rows);
+                for _ in 0..rows {
+                    values.push((0..columns).map(|_| reader.read_value()).collect::<io::Result<Vec<Value>>>()?);
+                }
+                let table = catalog.get_table(Some(&schema_name), &table_name).map_err(|err| invalid_data(err.to_string()))?;
+                table.write().unwrap().insert(values).map_err(|err| invalid_data(format!("{}.{}: {}", schema_name, table_name, err)))?;
+                row_count += rows;
+            },
+            CREATE_INDEX_TAG => {
+                let schema_name = reader.read_string()?;
+                let table_name = reader.read_string()?;
+                let index_name = reader.read_string()?;
+                let column_name = reader.read_string()?;
+                let (_, table) = catalog.create_index(Some(&schema_name), &table_name, &index_name).map_err(|err| invalid_data(err.to_string()))?;
+                let mut table = table.write().unwrap();
+                let column_id = table.column_id(&column_name).ok_or_else(|| invalid_data(format!("{}.{}: unknown column {}", schema_name, table_name, column_name)))?;
+                table.create_index(&index_name, column_id)?;
+            },
+            DROP_INDEX_TAG => {
+                let schema_name = reader.read_string()?;
+                let index_name = reader.read_string()?;
+                let (_, _, table) = catalog.drop_index(Some(&schema_name), &index_name).map_err(|err| invalid_data(err.to_string()))?;
+                table.write().unwrap().drop_index(&index_name);
+            },
+            tag => return Err(invalid_data(format!("unknown change tag {} in write-ahead log", tag)))
+        }
+    }
+    Ok(row_count)
+}
+
+// replays the complete records starting at the given offset, returns the number of replayed transactions and rows and the
+//  end of the last complete record
+fn replay_records(data: &[u8], mut offset: usize, catalog: &mut Catalog) -> io::Result<(usize, usize, usize)> {
+    let mut transactions = 0;
+    let mut rows = 0;
+    while data.len() - offset >= RECORD_HEADER_SIZE {
+        let len = u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]) as usize;
+        let checksum = u32::from_le_bytes([data[offset + 4], data[offset + 5], data[offset + 6], data[offset + 7]]);
+        let payload_start = offset + RECORD_HEADER_SIZE;
+        if len > data.len() - payload_start || crc32c(&data[payload_start..payload_start + len]) != checksum {
+            break
+        }
+        rows += replay_record(&data[payload_start..payload_start + len], catalog)?;
+        transactions += 1;
+        offset = payload_start + len;
+    }
+    Ok((transactions, rows, offset))
+}
+
+// logs written before there were checkpoints have no header, their changes were made on top of the tables loaded without
+//  a checkpoint
+//  they are replayed and written again with a header (without an incomplete record at the end), so that new records can be
+//  appended
+fn recover_headerless(path: &Path, data: &[u8], catalog: &mut Catalog, checkpoint: u64) -> io::Result<(usize, usize)> {
+    if checkpoint != 0 {
+        let message = format!("{} has no header, as it was written before checkpoints existed, and cannot be replayed on top of \
+            checkpoint {} (remove the checkpoint to replay the log, or the log to discard its changes)", path.display(), checkpoint);
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message))
+    }
+    let (transactions, rows, end) = replay_records(data, 0, catalog)?;
+    println!("Adding a header to the write-ahead log, which was written before checkpoints existed");
+    if end < data.len() {
+        println!("Discarding {} bytes of an incomplete record at the end of the write-ahead log", data.len() - end);
+    }
+    create_log(path, 0, &data[..end])?;
+    Ok((transactions, rows))
+}
+
+// replays the log on top of the tables of the given checkpoint, returns the number of replayed transactions and rows
+//  an incomplete record at the end of the log (and anything after it) is removed from the file, so that new records are not
+//  appended after it
+//  a log of an older checkpoint (which was not truncated because of a crash at the end of the checkpoint) is replaced by an
+//  empty one, as its changes are part of the checkpoint already
+pub fn recover(path: &Path, catalog: &mut Catalog, checkpoint: u64) -> io::Result<(usize, usize)> {
+    let mut file = match OpenOptions::new().read(true).write(true).open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok((0, 0)),
+        Err(err) => return Err(err)
+    };
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    if data.is_empty() {
+        return Ok((0, 0))
+    }
+    if !data.starts_with(MAGIC) {
+        return recover_headerless(path, &data, catalog, checkpoint)
+    }
+    if data.len() < HEADER_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} is not a write-ahead log", path.display())))
+    }
+    let log_checkpoint = u64::from_le_bytes(data[MAGIC.len()..HEADER_SIZE].try_into().unwrap());
+    if log_checkpoint < checkpoint {
+        println!("Discarding the write-ahead log of checkpoint {}, whose changes are part of checkpoint {}", log_checkpoint, checkpoint);
+        create_log(path, checkpoint, &[])?;
+        return Ok((0, 0))
+    }
+    if log_checkpoint > checkpoint {
+        let message = format!("the write-ahead log continues checkpoint {}, but the tables are from checkpoint {}", log_checkpoint, checkpoint);
+        return Err(io::Error::new(io::ErrorKind::InvalidData, message))
+    }
+    let (transactions, rows, offset) = replay_records(&data, HEADER_SIZE, catalog)?;
+    if offset < data.len() {
+        println!("Discarding {} bytes of an incomplete record at the end of the write-ahead log", data.len() - offset);
+        file.set_len(offset as u64)?;
+        file.sync_all()?;
+    }
+    Ok((transactions, rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+    use std::time::Instant;
+
+    use super::*;
+    use crate::core::{ColumnDefinition, DataType, Table};
+
+    const SCHEMA: &str = "public";
+    // path of the log written by committing_child
+    const CHILD_LOG_VAR: &str = "RDBMS_WAL_TEST_CHILD_LOG";
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rdbms-wal-{}-{}.wal", std::process::id(), name))
+    }
+
+    fn empty_catalog() -> Catalog {
+        let mut catalog = Catalog::new();
+        let columns = vec![ColumnDefinition::new("id", DataType::BigInt, false), ColumnDefinition::new("name", DataType::Text, true)];
+        catalog.create_table(None, "t", Table::new(columns, 100)).unwrap();
+        catalog
+    }
+
+    fn rows(catalog: &Catalog) -> Vec<Vec<Value>> {
+        let table = catalog.get_table(None, "t").unwrap();
+        let table = table.read().unwrap();
+        table.chunks().iter().flat_map(|chunk| {
+            (0..chunk.len()).map(move |i| (0..chunk.column_count()).map(|column_id| chunk.get_value(column_id, i).unwrap()).collect::<Vec<_>>())
+        }).collect()
+    }
+
+    fn transaction_rows(transaction: i64) -> Vec<Vec<Value>> {
+        (0..3).map(|i| vec![Value::BigInt(transaction * 10 + i), if i == 1 { Value::Null } else { Value::Text(format!("row {}", i)) }]).collect()
+    }
+
+    // writes one record per transaction, inserting transaction_rows, and returns the end offsets of the records
+    fn write_log(path: &Path, transactions: i64) -> Vec<u64> {
+        let _ = fs::remove_file(path);
+        let wal = Wal::open(path, SyncPolicy::Commit, 0).unwrap();
+        let mut ends = Vec::new();
+        for transaction in 0..transactions {
+            let mut record = RedoRecord::default();
+            record.log_insert(SCHEMA, "t", &transaction_rows(transaction));
+            wal.commit(&record).unwrap();
+            ends.push(HEADER_SIZE as u64 + wal.size());
+        }
+        ends
+    }
+
+    #[test]
+    fn committed_records_are_replayed() {
+        let path = temp_path("replay");
+        let _ = fs::remove_file(&path);
+        let wal = Wal::open(&path, SyncPolicy::Never, 0).unwrap();
+        let mut record = RedoRecord::default();
+        record.log_insert(SCHEMA, "t", &transaction_rows(0));
+        record.log_create_index(SCHEMA, "t", "t_id", "id");
+        wal.commit(&record).unwrap();
+        let mut record = RedoRecord::default();
+        record.log_insert(SCHEMA, "t", &transaction_rows(1));
+        wal.commit(&record).unwrap();
+        // the records were written without being flushed
+        assert_eq!(wal.state.lock().unwrap().written, 2);
+        assert_eq!(wal.state.lock().unwrap().synced, 0);
+        drop(wal);
+
+        let mut catalog = empty_catalog();
+        assert_eq!(recover(&path, &mut catalog, 0).unwrap(), (2, 6));
+        let expected: Vec<_> = transaction_rows(0).into_iter().chain(transaction_rows(1)).collect();
+        assert_eq!(rows(&catalog), expected);
+        let table = catalog.get_table(None, "t").unwrap();
+        assert!(table.read().unwrap().index_id("t_id").is_some());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn torn_records_are_dropped() {
+        let path = temp_path("torn");
+        let ends = write_log(&path, 3);
+        // the last record was not written completely
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(ends[2] - 5).unwrap();
+        drop(file);
+
+        let mut catalog = empty_catalog();
+        assert_eq!(recover(&path, &mut catalog, 0).unwrap(), (2, 6));
+        assert_eq!(rows(&catalog).len(), 6);
+        assert_eq!(fs::metadata(&path).unwrap().len(), ends[1]);
+
+        // new records are appended after the last complete one
+        let wal = Wal::open(&path, SyncPolicy::Commit, 0).unwrap();
+        let mut record = RedoRecord::default();
+        record.log_insert(SCHEMA, "t", &transaction_rows(5));
+        wal.commit(&record).unwrap();
+        drop(wal);
+        let mut catalog = empty_catalog();
+        assert_eq!(recover(&path, &mut catalog, 0).unwrap(), (3, 9));
+        assert_eq!(rows(&catalog)[6..], transaction_rows(5)[..]);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn checksum_mismatches_stop_the_replay() {
+        let path = temp_path("checksum");
+        let ends = write_log(&path, 3);
+        // a value of the second record
+        let mut data = fs::read(&path).unwrap();
+        data[ends[1] as usize - 3] ^= 0x01;
+        fs::write(&path, &data).unwrap();
+
+        let mut catalog = empty_catalog();
+        assert_eq!(recover(&path, &mut catalog, 0).unwrap(), (1, 3));
+        assert_eq!(rows(&catalog), transaction_rows(0));
+        // the damaged record and everything after it is removed
+        assert_eq!(fs::metadata(&path).unwrap().len(), ends[0]);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn periodic_syncs_flush_the_written_records() {
+        let path = temp_path("periodic");
+        let _ = fs::remove_file(&path);
+        let wal = Wal::open(&path, SyncPolicy::Periodic(Duration::from_millis(10)), 0).unwrap();
+        let mut record = RedoRecord::default();
+        record.log_insert(SCHEMA, "t", &transaction_rows(0));
+        wal.commit(&record).unwrap();
+        assert_eq!(wal.state.lock().unwrap().written, 1);
+        // by the background thread
+        let start = Instant::now();
+        while wal.state.lock().unwrap().synced < 1 {
+            assert!(start.elapsed() < Duration::from_secs(10), "the log was not flushed");
+            thread::sleep(Duration::from_millis(1));
+        }
+        drop(wal);
+
+        let mut catalog = empty_catalog();
+        assert_eq!(recover(&path, &mut catalog, 0).unwrap(), (1, 3));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn commits_waiting_for_a_failed_group_write_fail() {
+        let path = temp_path("failed-write");
+        let _ = fs::remove_file(&path);
+        let wal = Wal::open(&path, SyncPolicy::Commit, 0).unwrap();
+        let mut record = RedoRecord::default();
+        record.log_insert(SCHEMA, "t", &transaction_rows(0));
+        wal.commit(&record).unwrap();
+        // the file is taken as if another thread was writing (see write_buffer), so the following commits wait for its write
+        let file = wal.state.lock().unwrap().file.take().unwrap();
+        thread::scope(|scope| {
+            let commits: Vec<_> = (1..3).map(|transaction| {
+                let wal = &wal;
+                scope.spawn(move || {
+                    let mut record = RedoRecord::default();
+                    record.log_insert(SCHEMA, "t", &transaction_rows(transaction));
+                    wal.commit(&record)
+                })
+            }).collect();
+            while wal.state.lock().unwrap().committed < 3 {
+                thread::yield_now();
+            }
+            // the write of both records fails, as the log was opened read-only
+            drop(file);
+            let mut state = wal.state.lock().unwrap();
+            state.file = Some(File::open(&path).unwrap());
+            let (state, result) = wal.write_buffer(state, true);
+            drop(state);
+            assert!(result.is_err());
+            for commit in commits {
+                assert!(commit.join().unwrap().is_err());
+            }
+        });
+        // as are later commits, the log may end with a partial record
+        assert!(wal.commit(&record).is_err());
+        drop(wal);
+
+        let mut catalog = empty_catalog();
+        assert_eq!(recover(&path, &mut catalog, 0).unwrap(), (1, 3));
+        assert_eq!(rows(&catalog), transaction_rows(0));
+        fs::remove_file(path).unwrap();
+    }
+
+    // run by acknowledged_commits_survive_a_crash as a child process, which commits from several threads until it is killed
+    //  and prints the id of each acknowledged transaction
+    #[test]
+    #[ignore]
+    fn committing_child() {
+        let path = match std::env::var_os(CHILD_LOG_VAR) {
+            Some(path) => PathBuf::from(path),
+            None => return
+        };
+        let wal = Wal::open(&path, SyncPolicy::Commit, 0).unwrap();
+        thread::scope(|scope| {
+            for thread in 0..4 {
+                let wal = &wal;
+                scope.spawn(move || {
+                    for transaction in (thread..).step_by(4) {
+                        let mut record = RedoRecord::default();
+                        record.log_insert(SCHEMA, "t", &transaction_rows(transaction));
+                        wal.commit(&record).unwrap();
+                        println!("committed {}", transaction);
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn acknowledged_commits_survive_a_crash() {
+        let path = temp_path("crash");
+        let _ = fs::remove_file(&path);
+        let mut child = Command::new(std::env::current_exe().unwrap())
+            .args(["wal::tests::committing_child", "--exact", "--ignored", "--nocapture"])
+            .env(CHILD_LOG_VAR, &path)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut output = BufReader::new(child.stdout.take().unwrap());
+        let mut acknowledged = Vec::new();
+        let mut line = String::new();
+        while acknowledged.len() < 200 {
+            line.clear();
+            assert!(output.read_line(&mut line).unwrap() > 0, "the child exited");
+            if let Some(transaction) = line.trim_end().strip_prefix("committed ") {
+                acknowledged.push(transaction.parse::<i64>().unwrap());
+            }
+        }
+        // the other threads are in the middle of their commits, e.g., between writing their records and flushing them
+        child.kill().unwrap();
+        child.wait().unwrap();
+        drop(output);
+
+        let mut catalog = empty_catalog();
+        let (transactions, row_count) = recover(&path, &mut catalog, 0).unwrap();
+        assert_eq!(row_count, transactions * 3);
+        // every replayed transaction is complete, and every acknowledged one was replayed
+        let mut replayed = Vec::new();
+        for replayed_rows in rows(&catalog).chunks(3) {
+            let transaction = match replayed_rows[0][0] {
+                Value::BigInt(id) => id / 10,
+                _ => panic!("unexpected id")
+            };
+            assert_eq!(replayed_rows, &transaction_rows(transaction)[..]);
+            replayed.push(transaction);
+        }
+        for transaction in acknowledged {
+            assert!(replayed.contains(&transaction), "transaction {} was acknowledged but not replayed", transaction);
+        }
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn logs_without_header_are_converted() {
+        let path = temp_path("headerless");
+        write_log(&path, 3);
+        let data = fs::read(&path).unwrap();
+        // as written before there were checkpoints, with an incomplete record at the end
+        let mut headerless = data[HEADER_SIZE..].to_vec();
+        headerless.extend_from_slice(&[7, 0, 0]);
+        fs::write(&path, &headerless).unwrap();
+
+        let err = recover(&path, &mut empty_catalog(), 1).unwrap_err();
+        assert!(err.to_string().contains("has no header"), "{}", err);
+        let mut catalog = empty_catalog();
+        assert_eq!(recover(&path, &mut catalog, 0).unwrap(), (3, 9));
+        assert_eq!(rows(&catalog).len(), 9);
+        assert_eq!(fs::read(&path).unwrap(), data);
+
+        // replayed like any other log afterwards
+        let mut catalog = empty_catalog();
+        assert_eq!(recover(&path, &mut catalog, 0).unwrap(), (3, 9));
+        fs::remove_file(path).unwrap();
+    }
+}